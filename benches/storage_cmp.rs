@@ -28,6 +28,59 @@ where
     )
 }
 
+fn storage_append<C>(b: &mut Bencher, num: usize)
+where
+    C: Component + Default,
+    C::Storage: Default + storage::AppendStorage<C>,
+{
+    b.iter_with_setup(
+        || {
+            let mut world = World::new();
+
+            world.register::<C>();
+
+            world
+        },
+        |world| {
+            let entities = world.entities();
+            let mut storage = world.write_storage::<C>();
+
+            for e in entities.create_iter().take(num) {
+                storage.append(e, C::default()).unwrap();
+            }
+        },
+    )
+}
+
+fn storage_insert_reversed<C>(b: &mut Bencher, num: usize, batch: bool)
+where
+    C: Component + Default,
+    C::Storage: Default + storage::EnsureCapacity,
+{
+    b.iter_with_setup(
+        || {
+            let mut world = World::new();
+
+            world.register::<C>();
+            let entities: Vec<_> = world.create_iter().take(num).collect();
+
+            (world, entities)
+        },
+        |(world, entities)| {
+            let mut storage = world.write_storage::<C>();
+            let components = entities.into_iter().rev().map(|e| (e, C::default()));
+
+            if batch {
+                storage.insert_batch(components).unwrap();
+            } else {
+                for (e, c) in components {
+                    storage.insert(e, c).unwrap();
+                }
+            }
+        },
+    )
+}
+
 fn storage_remove<C>(b: &mut Bencher, num: usize)
 where
     C: Component + Default,
@@ -115,6 +168,22 @@ macro_rules! insert {
     }};
 }
 
+macro_rules! append {
+    ($b:ident, $num:expr, $bytes:expr, $store:ident) => {{
+        decl_comp!($bytes, $store);
+
+        storage_append::<Comp>($b, $num)
+    }};
+}
+
+macro_rules! insert_reversed {
+    ($b:ident, $num:expr, $bytes:expr, $store:ident, $batch:expr) => {{
+        decl_comp!($bytes, $store);
+
+        storage_insert_reversed::<Comp>($b, $num, $batch)
+    }};
+}
+
 macro_rules! remove {
     ($b:ident, $num:expr, $bytes:expr, $store:ident) => {{
         decl_comp!($bytes, $store);
@@ -131,6 +200,41 @@ macro_rules! get {
     }};
 }
 
+#[derive(Default)]
+struct Mesh {
+    version: u64,
+}
+
+impl storage::Versioned for Mesh {
+    fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl Component for Mesh {
+    type Storage = storage::VersionedStorage<Self>;
+}
+
+fn storage_maintain_versions(b: &mut Bencher, writes: usize) {
+    let mut world = World::new();
+    world.register::<Mesh>();
+    let entities: Vec<_> = world.create_iter().take(100_000).collect();
+
+    let mut storage = world.write_storage::<Mesh>();
+    for &e in &entities {
+        storage.insert(e, Mesh::default()).unwrap();
+    }
+    storage.maintain_versions();
+
+    let stride = entities.len() / writes;
+    b.iter(|| {
+        for i in 0..writes {
+            storage.get_mut(entities[i * stride]).unwrap().version += 1;
+        }
+        black_box(storage.maintain_versions());
+    })
+}
+
 #[cfg_attr(rustfmt, rustfmt_skip)]
 fn insert_benches(c: &mut Criterion) {
     c.bench_function_over_inputs(
@@ -170,6 +274,42 @@ fn insert_benches(c: &mut Criterion) {
     );
 }
 
+#[cfg_attr(rustfmt, rustfmt_skip)]
+fn append_benches(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "append 1b/dense",
+        |b, &&i| append!(b, i, 1, DenseVecStorage),
+        &[1, 16, 64, 256, 1024],
+    ).bench_function_over_inputs(
+        "append 1b/vec",
+        |b, &&i| append!(b, i, 1, VecStorage),
+        &[1, 16, 64, 256, 1024],
+    );
+
+    c.bench_function_over_inputs(
+        "append 32b/dense",
+        |b, &&i| append!(b, i, 32, DenseVecStorage),
+        &[1, 16, 64, 256, 1024],
+    ).bench_function_over_inputs(
+        "append 32b/vec",
+        |b, &&i| append!(b, i, 32, VecStorage),
+        &[1, 16, 64, 256, 1024],
+    );
+}
+
+#[cfg_attr(rustfmt, rustfmt_skip)]
+fn insert_batch_benches(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "insert reversed 32b/dense incremental",
+        |b, &&i| insert_reversed!(b, i, 32, DenseVecStorage, false),
+        &[100_000],
+    ).bench_function_over_inputs(
+        "insert reversed 32b/dense batch",
+        |b, &&i| insert_reversed!(b, i, 32, DenseVecStorage, true),
+        &[100_000],
+    );
+}
+
 #[cfg_attr(rustfmt, rustfmt_skip)]
 fn remove_benches(c: &mut Criterion) {
     c.bench_function_over_inputs(
@@ -248,9 +388,21 @@ fn get_benches(c: &mut Criterion) {
     );
 }
 
+#[cfg_attr(rustfmt, rustfmt_skip)]
+fn maintain_versions_benches(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "maintain versions 100k/writes",
+        |b, &&i| storage_maintain_versions(b, i),
+        &[1, 100, 10_000, 100_000],
+    );
+}
+
 criterion_group!(
     benches_storages,
     insert_benches,
+    append_benches,
+    insert_batch_benches,
+    maintain_versions_benches,
     remove_benches,
     get_benches
 );
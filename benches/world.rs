@@ -187,6 +187,39 @@ fn maintain_delete_later(b: &mut Bencher) {
     });
 }
 
+fn maintain_delete_many_storages(b: &mut Bencher) {
+    macro_rules! components {
+        ($($name:ident),*) => {
+            $(
+                struct $name(i32);
+
+                impl Component for $name {
+                    type Storage = VecStorage<Self>;
+                }
+            )*
+
+            fn setup() -> World {
+                let mut w = World::new();
+                $(w.register::<$name>();)*
+                let eids: Vec<_> = w.create_iter().take(10_000).collect();
+                $({
+                    let mut storage = w.write_storage::<$name>();
+                    for e in &eids {
+                        storage.insert(*e, $name(e.id() as i32)).unwrap();
+                    }
+                })*
+                w.delete_entities(&eids[..5_000]).unwrap();
+
+                w
+            }
+        };
+    }
+
+    components!(C0, C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15);
+
+    b.iter_with_large_setup(setup, |mut w| w.maintain());
+}
+
 fn join_single_threaded(b: &mut Bencher) {
     use test::black_box;
 
@@ -230,6 +263,84 @@ fn join_multi_threaded(b: &mut Bencher) {
     })
 }
 
+#[cfg(feature = "parallel")]
+fn par_iter_mut(b: &mut Bencher) {
+    use rayon::prelude::*;
+
+    let mut world = World::new();
+    world.register::<CompInt>();
+
+    {
+        let entities: Vec<_> = world.create_iter().take(1_000_000).collect();
+        let mut comp_int = world.write_storage();
+        for (i, e) in entities.iter().enumerate() {
+            comp_int.insert(*e, CompInt(i as i32)).unwrap();
+        }
+    }
+
+    b.iter(|| {
+        world
+            .write_storage::<CompInt>()
+            .par_iter_mut()
+            .for_each(|(_, comp)| comp.0 = comp.0.wrapping_mul(3));
+    })
+}
+
+#[cfg(feature = "parallel")]
+fn par_insert_many_storages(b: &mut Bencher) {
+    use specs::storage::ParInsert;
+
+    struct A(i32);
+    impl Component for A {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct B(i32);
+    impl Component for B {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct C([f32; 4]);
+    impl Component for C {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    struct D([f32; 4]);
+    impl Component for D {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    b.iter_with_large_setup(
+        || {
+            let mut w = World::new();
+            w.register::<A>();
+            w.register::<B>();
+            w.register::<C>();
+            w.register::<D>();
+            let eids: Vec<_> = w.create_iter().take(100_000).collect();
+            let a = eids.iter().map(|&e| (e, A(e.id() as i32))).collect();
+            let b = eids.iter().map(|&e| (e, B(e.id() as i32))).collect();
+            let c = eids.iter().map(|&e| (e, C([1.0; 4]))).collect();
+            let d = eids.iter().map(|&e| (e, D([2.0; 4]))).collect();
+
+            (w, a, b, c, d)
+        },
+        |(w, a, b, c, d)| {
+            let mut sa = w.write_storage::<A>();
+            let mut sb = w.write_storage::<B>();
+            let mut sc = w.write_storage::<C>();
+            let mut sd = w.write_storage::<D>();
+            ParInsert::new()
+                .with(&mut sa, a)
+                .with(&mut sb, b)
+                .with(&mut sc, c)
+                .with(&mut sd, d)
+                .run()
+                .unwrap();
+        },
+    );
+}
+
 fn world_benchmarks(c: &mut Criterion) {
     c.bench_function("world build", world_build)
         .bench_function("create now", create_now)
@@ -243,8 +354,13 @@ fn world_benchmarks(c: &mut Criterion) {
         .bench_function("maintain noop", maintain_noop)
         .bench_function("maintain add later", maintain_add_later)
         .bench_function("maintain delete later", maintain_delete_later)
+        .bench_function("maintain delete many storages", maintain_delete_many_storages)
         .bench_function("join single threaded", join_single_threaded)
         .bench_function("join multi threaded", join_multi_threaded);
+
+    #[cfg(feature = "parallel")]
+    c.bench_function("par iter mut", par_iter_mut)
+        .bench_function("par insert many storages", par_insert_many_storages);
 }
 
 criterion_group!(world, world_benchmarks);
@@ -0,0 +1,166 @@
+//! An event channel with bounded memory usage.
+//!
+//! `shrev::EventChannel` keeps all events until every reader has read
+//! them, so a reader which is never read from makes it grow forever.
+//! `BoundedChannel` instead drops the oldest events once it's full, and
+//! tells readers which fell behind that they lost events.
+
+use std::collections::vec_deque::{self, VecDeque};
+use std::iter::Skip;
+use std::marker::PhantomData;
+
+use error::EventsLost;
+
+/// An event channel keeping at most `capacity` events.
+///
+/// When a new event is written to a full channel, the oldest event is
+/// dropped, even if some reader hasn't read it yet. Such a reader gets an
+/// `EventsLost` error on its next `read`, so it can fall back to a full
+/// rescan of whatever the events describe.
+///
+/// Writers are never blocked by slow readers; this way, memory usage is
+/// bounded no matter how readers behave.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::channel::BoundedChannel;
+///
+/// let mut channel = BoundedChannel::new(2);
+/// let mut reader = channel.register_reader();
+///
+/// channel.single_write(1);
+/// assert_eq!(channel.read(&mut reader).unwrap().collect::<Vec<_>>(), vec![&1]);
+///
+/// channel.iter_write(vec![2, 3, 4]);
+/// assert_eq!(channel.read(&mut reader).err().unwrap().lost, 1);
+/// assert_eq!(channel.read(&mut reader).unwrap().count(), 0);
+/// ```
+#[derive(Debug)]
+pub struct BoundedChannel<E> {
+    events: VecDeque<E>,
+    capacity: usize,
+    /// The sequence number of the first buffered event.
+    first: u64,
+}
+
+impl<E> BoundedChannel<E> {
+    /// Creates a new channel keeping at most `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity != 0, "channel capacity must be non-zero");
+
+        BoundedChannel {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            first: 0,
+        }
+    }
+
+    /// Returns the maximum number of events this channel keeps.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Creates a new reader, which will read all events written from now on.
+    pub fn register_reader(&self) -> BoundedReader<E> {
+        BoundedReader {
+            next: self.end(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Writes a single event, dropping the oldest one if the channel is
+    /// full.
+    pub fn single_write(&mut self, event: E) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.first += 1;
+        }
+
+        self.events.push_back(event);
+    }
+
+    /// Writes multiple events, see `single_write`.
+    pub fn iter_write<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for event in events {
+            self.single_write(event);
+        }
+    }
+
+    /// Reads all events the reader hasn't read yet.
+    ///
+    /// Returns an error if events have been dropped before the reader
+    /// could read them. In that case, the reader skips all events which
+    /// are still buffered, too, so the next `read` only returns events
+    /// written after the error.
+    pub fn read(&self, reader: &mut BoundedReader<E>) -> Result<EventIter<E>, EventsLost> {
+        let end = self.end();
+
+        if reader.next < self.first {
+            let lost = self.first - reader.next;
+            reader.next = end;
+
+            return Err(EventsLost { lost });
+        }
+
+        let start = (reader.next - self.first) as usize;
+        reader.next = end;
+
+        Ok(self.events.iter().skip(start))
+    }
+
+    /// The sequence number the next written event gets.
+    fn end(&self) -> u64 {
+        self.first + self.events.len() as u64
+    }
+}
+
+/// A reader of a `BoundedChannel`, keeping track of which events it has
+/// read already.
+#[derive(Debug)]
+pub struct BoundedReader<E> {
+    next: u64,
+    phantom: PhantomData<E>,
+}
+
+/// Iterator over events read from a `BoundedChannel`.
+pub type EventIter<'a, E> = Skip<vec_deque::Iter<'a, E>>;
+
+#[cfg(test)]
+mod tests {
+    use super::BoundedChannel;
+
+    #[test]
+    fn slow_reader_overflows() {
+        let mut channel = BoundedChannel::new(4);
+        let mut fast = channel.register_reader();
+        let mut slow = channel.register_reader();
+
+        for i in 0..10 {
+            channel.single_write(i);
+            let read: Vec<_> = channel.read(&mut fast).unwrap().cloned().collect();
+            assert_eq!(read, vec![i]);
+        }
+
+        // 6 events have been dropped before `slow` read them
+        assert_eq!(channel.read(&mut slow).unwrap_err().lost, 6);
+        assert_eq!(channel.read(&mut slow).unwrap().count(), 0);
+
+        channel.iter_write(vec![10, 11]);
+        let read: Vec<_> = channel.read(&mut slow).unwrap().cloned().collect();
+        assert_eq!(read, vec![10, 11]);
+
+        // reading exactly up to the capacity doesn't lose anything
+        channel.iter_write(vec![12, 13, 14, 15]);
+        let read: Vec<_> = channel.read(&mut slow).unwrap().cloned().collect();
+        assert_eq!(read, vec![12, 13, 14, 15]);
+        assert_eq!(channel.read(&mut fast).unwrap_err().lost, 2);
+    }
+}
@@ -1,6 +1,6 @@
 //! Common functionality between crates using specs.
 //!
-//! At the moment, this module provides two types:
+//! At the moment, this module provides these types:
 //!
 //! * `Errors`: A resource you can use to store errors that occurred outside of
 //!             the ECS but were catched inside, therefore should be handled by the user
@@ -8,6 +8,9 @@
 //! * `Merge`: A system generic over `T` which automatically merges `Ready` futures into
 //!            the component storage for `T`.
 //!
+//! * `AsyncSystem`: A system which starts futures, which are polled on later dispatches
+//!                  by wrapping it in an `AsyncRunner`.
+//!
 //! To make use of these features, you need to ask for the `common` feature
 //! like this:
 //!
@@ -28,9 +31,9 @@ use futures::{Async, Future};
 
 use error::BoxedErr;
 use join::Join;
-use shred::{Read, RunningTime, System};
+use shred::{Read, RunningTime, System, SystemData};
 use storage::WriteStorage;
-use world::{Component, Entities, Entity};
+use world::{Component, Entities, Entity, LazyUpdate};
 
 /// A boxed, thread-safe future with `T` as item and `BoxedErr` as error type.
 pub type BoxedFuture<T> = Box<Future<Item = T, Error = BoxedErr> + Send + Sync + 'static>;
@@ -219,6 +222,153 @@ where
     }
 }
 
+/// A system which can start asynchronous work (e.g. loading assets), whose
+/// results are applied once it completes.
+///
+/// Wrap it in an `AsyncRunner` to add it to a `Dispatcher`.
+///
+/// ## Threads and executors
+///
+/// The futures are never blocked on; `AsyncRunner` polls all of them once
+/// per dispatch, from whatever thread the dispatcher runs it on, and
+/// ignores their wakeup notifications. So a future has to make progress
+/// on its own, e.g. by waiting for the result of a thread pool, a separate
+/// executor or a `futures::sync::oneshot` channel. Futures which need to be
+/// driven by polling (like most I/O futures) have to be spawned on an
+/// executor instead, returning a handle to their result here.
+///
+/// ## Examples
+///
+/// ```
+/// extern crate futures;
+/// extern crate specs;
+///
+/// use futures::sync::oneshot;
+/// use futures::Future;
+/// use specs::common::{AsyncRunner, AsyncSystem, BoxedErr, BoxedFuture};
+/// use specs::prelude::*;
+/// use std::thread;
+///
+/// struct Level(String);
+///
+/// struct LoadLevel {
+///     started: bool,
+/// }
+///
+/// impl<'a> AsyncSystem<'a> for LoadLevel {
+///     type SystemData = ();
+///     type Future = BoxedFuture<String>;
+///
+///     fn run(&mut self, _: ()) -> Option<Self::Future> {
+///         if self.started {
+///             return None;
+///         }
+///         self.started = true;
+///
+///         let (sender, receiver) = oneshot::channel();
+///         thread::spawn(move || sender.send("level 1".to_owned()));
+///
+///         Some(Box::new(receiver.map_err(BoxedErr::new)))
+///     }
+///
+///     fn complete(&mut self, name: String, lazy: &LazyUpdate) {
+///         lazy.exec_mut(move |world| world.add_resource(Level(name)));
+///     }
+/// }
+///
+/// fn main() {
+///     let mut world = World::new();
+///     let mut dispatcher = DispatcherBuilder::new()
+///         .with(AsyncRunner::new(LoadLevel { started: false }), "load_level", &[])
+///         .build();
+///     dispatcher.setup(&mut world.res);
+///
+///     while !world.res.has_value::<Level>() {
+///         dispatcher.dispatch(&world.res);
+///         world.maintain();
+///     }
+///
+///     assert_eq!(world.read_resource::<Level>().0, "level 1");
+/// }
+/// ```
+pub trait AsyncSystem<'a> {
+    /// The resources the system needs to start its work.
+    type SystemData: SystemData<'a>;
+    /// The asynchronous work.
+    type Future: Future<Error = BoxedErr> + Send + Sync + 'static;
+
+    /// Runs the system, possibly starting asynchronous work.
+    fn run(&mut self, data: Self::SystemData) -> Option<Self::Future>;
+
+    /// Applies the result of a completed future. This is called on a later
+    /// dispatch, or on the same one if the future is ready right away.
+    ///
+    /// Since the system data might not be available anymore, changes to
+    /// the `World` have to be done through `LazyUpdate`.
+    fn complete(&mut self, item: <Self::Future as Future>::Item, lazy: &LazyUpdate);
+}
+
+/// A system running an `AsyncSystem`, which polls all of its pending
+/// futures on every dispatch.
+///
+/// In case a future fails, the error will be added to the `Errors`
+/// resource.
+pub struct AsyncRunner<S, F> {
+    system: S,
+    pending: Vec<Spawn<F>>,
+}
+
+impl<S, F> AsyncRunner<S, F>
+where
+    S: for<'a> AsyncSystem<'a, Future = F>,
+{
+    /// Creates a new system running `system`.
+    pub fn new(system: S) -> Self {
+        AsyncRunner {
+            system,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the number of futures which haven't completed yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<'a, S, F> System<'a> for AsyncRunner<S, F>
+where
+    S: AsyncSystem<'a, Future = F>,
+    F: Future<Error = BoxedErr> + Send + Sync + 'static,
+{
+    type SystemData = (Read<'a, LazyUpdate>, Read<'a, Errors>, S::SystemData);
+
+    fn run(&mut self, (lazy, errors, data): Self::SystemData) {
+        if let Some(future) = self.system.run(data) {
+            self.pending.push(spawn(future));
+        }
+
+        let system = &mut self.system;
+        retain_mut(&mut self.pending, |spawn| {
+            match spawn.poll_future_notify(NOTIFY_IGNORE, 0) {
+                Ok(Async::NotReady) => true,
+                Ok(Async::Ready(item)) => {
+                    system.complete(item, &lazy);
+                    false
+                }
+                Err(err) => {
+                    errors.add(err);
+                    false
+                }
+            }
+        });
+    }
+
+    fn running_time(&self) -> RunningTime {
+        RunningTime::Short
+    }
+}
+
 struct NotifyIgnore;
 
 impl Notify for NotifyIgnore {
@@ -265,6 +415,62 @@ mod test {
     use storage::{NullStorage, VecStorage};
     use world::{Builder, Component, World};
 
+    #[test]
+    fn async_runner() {
+        use futures::sync::oneshot;
+
+        use common::{AsyncRunner, AsyncSystem, BoxedFuture};
+        use world::LazyUpdate;
+
+        struct Loaded(u32);
+
+        struct Load {
+            receivers: Vec<oneshot::Receiver<u32>>,
+        }
+
+        impl<'a> AsyncSystem<'a> for Load {
+            type SystemData = ();
+            type Future = BoxedFuture<u32>;
+
+            fn run(&mut self, _: ()) -> Option<Self::Future> {
+                self.receivers
+                    .pop()
+                    .map(|r| Box::new(r.map_err(BoxedErr::new)) as BoxedFuture<u32>)
+            }
+
+            fn complete(&mut self, value: u32, lazy: &LazyUpdate) {
+                lazy.exec_mut(move |world| world.add_resource(Loaded(value)));
+            }
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let (dropped, failing) = oneshot::channel::<u32>();
+
+        let mut world = World::new();
+        world.add_resource(Errors::new());
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(
+                AsyncRunner::new(Load {
+                    receivers: vec![failing, receiver],
+                }),
+                "load",
+                &[],
+            )
+            .build();
+
+        // The first future doesn't block the dispatch.
+        dispatcher.dispatch_seq(&mut world.res);
+        world.maintain();
+        assert!(!world.res.has_value::<Loaded>());
+
+        sender.send(5).unwrap();
+        drop(dropped);
+        dispatcher.dispatch_seq(&mut world.res);
+        world.maintain();
+        assert_eq!(world.read_resource::<Loaded>().0, 5);
+        assert!(world.read_resource::<Errors>().errors.try_pop().is_some());
+    }
+
     #[test]
     fn test_merge() {
         #[derive(Default)]
@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+use shred::{ReadExpect, Resources, RunningTime, System, SystemData};
+
+/// A resource measuring the time spent in the current frame against a
+/// budget, used by `BestEffort` systems.
+///
+/// `start_frame` has to be called at the start of every frame, before
+/// dispatching.
+pub struct FrameBudget {
+    budget: Duration,
+    clock: Box<Fn() -> Duration + Send + Sync>,
+    frame_start: Duration,
+}
+
+impl FrameBudget {
+    /// Creates a budget of `budget` per frame, measured with the system
+    /// clock.
+    pub fn new(budget: Duration) -> Self {
+        let start = Instant::now();
+
+        FrameBudget::with_clock(budget, move || start.elapsed())
+    }
+
+    /// Creates a budget of `budget` per frame, measured with `clock`, which
+    /// returns the time passed since some fixed point in time.
+    ///
+    /// This allows using a custom time source, e.g. a fake clock in tests.
+    pub fn with_clock<F>(budget: Duration, clock: F) -> Self
+    where
+        F: Fn() -> Duration + Send + Sync + 'static,
+    {
+        let frame_start = clock();
+
+        FrameBudget {
+            budget,
+            clock: Box::new(clock),
+            frame_start,
+        }
+    }
+
+    /// Returns the budget per frame.
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Sets the budget per frame.
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+
+    /// Starts measuring a new frame.
+    pub fn start_frame(&mut self) {
+        self.frame_start = (self.clock)();
+    }
+
+    /// Returns the time passed since `start_frame`.
+    pub fn elapsed(&self) -> Duration {
+        let now = (self.clock)();
+        if now > self.frame_start {
+            now - self.frame_start
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// Returns `true` if the time spent in this frame exceeds the budget.
+    pub fn exceeded(&self) -> bool {
+        self.elapsed() > self.budget
+    }
+}
+
+/// A system wrapper which skips the inner system once the `FrameBudget`
+/// of the current frame is exceeded.
+///
+/// The time spent is measured when the system is run, so everything that
+/// ran before it in the frame counts; to run it after certain systems, add
+/// them as its dependencies. A skipped system is tried again next frame.
+/// Like with `Conditional`, the system data is fetched even if the system is
+/// skipped.
+///
+/// ## Panics
+///
+/// Panics on dispatch if there's no `FrameBudget` in the `Resources`.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use std::time::Duration;
+/// use specs::dispatch::{BestEffort, FrameBudget};
+///
+/// struct Pathfinding;
+///
+/// impl<'a> System<'a> for Pathfinding {
+///     type SystemData = ();
+///
+///     fn run(&mut self, _: ()) {}
+/// }
+///
+/// let mut world = World::new();
+/// world.add_resource(FrameBudget::new(Duration::from_millis(16)));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(BestEffort::new(Pathfinding), "pathfinding", &[])
+///     .build();
+///
+/// world.write_resource::<FrameBudget>().start_frame();
+/// dispatcher.dispatch(&world.res);
+/// ```
+pub struct BestEffort<S> {
+    system: S,
+}
+
+impl<S> BestEffort<S> {
+    /// Wraps `system`, so it's only run while the frame budget hasn't been
+    /// exceeded.
+    pub fn new(system: S) -> Self {
+        BestEffort { system }
+    }
+}
+
+impl<'a, S> System<'a> for BestEffort<S>
+where
+    S: System<'a>,
+    S::SystemData: SystemData<'a>,
+{
+    type SystemData = (ReadExpect<'a, FrameBudget>, S::SystemData);
+
+    fn run(&mut self, (budget, data): Self::SystemData) {
+        if !budget.exceeded() {
+            self.system.run(data);
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+    }
+}
@@ -0,0 +1,139 @@
+use shred::{Dispatcher, DispatcherBuilder, ResourceId, Resources, RunNow, System, SystemData};
+
+use error::{BuildError, MissingResource};
+
+/// A `DispatcherBuilder` which records the resources read and written by
+/// its systems, so they can be checked against the `Resources` when
+/// building the dispatcher.
+///
+/// A missing resource would otherwise only be found by a panic when the
+/// dispatcher first runs the system, while `build` reports all missing
+/// resources at once, by the name of the system accessing them. Component
+/// storages are resources too, so systems using unregistered components
+/// are found as well.
+///
+/// Note that `Dispatcher::setup` adds the storages and resources which
+/// have a default, so this is mostly useful for worlds which are set up
+/// by hand, or for resources fetched with `ReadExpect` and `WriteExpect`.
+/// Thread-local systems and barriers are passed through unchecked, since
+/// thread-local systems don't declare their resources.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::CheckedDispatcherBuilder;
+///
+/// struct Score(u32);
+///
+/// struct AddPoint;
+///
+/// impl<'a> System<'a> for AddPoint {
+///     type SystemData = WriteExpect<'a, Score>;
+///
+///     fn run(&mut self, mut score: Self::SystemData) {
+///         score.0 += 1;
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// let builder = CheckedDispatcherBuilder::new().with(AddPoint, "add_point", &[]);
+/// let err = builder.build(&world.res).err().unwrap();
+/// assert_eq!(err.missing[0].system, "add_point");
+/// assert!(err.missing[0].write);
+///
+/// world.add_resource(Score(0));
+/// let builder = CheckedDispatcherBuilder::new().with(AddPoint, "add_point", &[]);
+/// let mut dispatcher = builder.build(&world.res).unwrap();
+/// dispatcher.dispatch(&world.res);
+/// ```
+pub struct CheckedDispatcherBuilder<'a, 'b> {
+    builder: DispatcherBuilder<'a, 'b>,
+    accesses: Vec<(String, Vec<ResourceId>, Vec<ResourceId>)>,
+}
+
+impl<'a, 'b> CheckedDispatcherBuilder<'a, 'b> {
+    /// Creates a new builder without any systems.
+    pub fn new() -> Self {
+        CheckedDispatcherBuilder {
+            builder: DispatcherBuilder::new(),
+            accesses: Vec::new(),
+        }
+    }
+
+    /// Adds a system, recording the resources it reads and writes.
+    ///
+    /// See `DispatcherBuilder::with` for `name` and `dep`.
+    pub fn with<S>(mut self, system: S, name: &str, dep: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'a,
+        for<'c> <S as System<'c>>::SystemData: SystemData<'c>,
+    {
+        let (reads, writes) = accesses::<S>();
+        self.accesses.push((name.to_owned(), reads, writes));
+        self.builder = self.builder.with(system, name, dep);
+
+        self
+    }
+
+    /// Adds a thread-local system, which isn't checked.
+    ///
+    /// See `DispatcherBuilder::with_thread_local`.
+    pub fn with_thread_local<T>(mut self, system: T) -> Self
+    where
+        T: for<'c> RunNow<'c> + 'b,
+    {
+        self.builder = self.builder.with_thread_local(system);
+
+        self
+    }
+
+    /// Adds a barrier, see `DispatcherBuilder::with_barrier`.
+    pub fn with_barrier(mut self) -> Self {
+        self.builder = self.builder.with_barrier();
+
+        self
+    }
+
+    /// Checks that every resource read or written by the systems exists in
+    /// `res`, and builds the dispatcher if so.
+    ///
+    /// Otherwise, returns a `BuildError` listing every missing resource
+    /// together with the systems accessing it.
+    pub fn build(self, res: &Resources) -> Result<Dispatcher<'a, 'b>, BuildError> {
+        let mut missing = Vec::new();
+        for (system, reads, writes) in self.accesses {
+            let reads = reads.into_iter().map(|id| (id, false));
+            let writes = writes.into_iter().map(|id| (id, true));
+            for (resource, write) in reads.chain(writes) {
+                if !res.has_value_raw(resource.clone()) {
+                    missing.push(MissingResource {
+                        system: system.clone(),
+                        resource,
+                        write,
+                    });
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(self.builder.build())
+        } else {
+            Err(BuildError { missing })
+        }
+    }
+}
+
+impl<'a, 'b> Default for CheckedDispatcherBuilder<'a, 'b> {
+    fn default() -> Self {
+        CheckedDispatcherBuilder::new()
+    }
+}
+
+fn accesses<'c, S>() -> (Vec<ResourceId>, Vec<ResourceId>)
+where
+    S: System<'c>,
+    S::SystemData: SystemData<'c>,
+{
+    (S::SystemData::reads(), S::SystemData::writes())
+}
@@ -0,0 +1,89 @@
+use std::marker::PhantomData;
+
+use shred::{ReadExpect, Resource, Resources, RunningTime, System, SystemData};
+
+/// A system wrapper which only runs the inner system if a predicate over
+/// the resource `R` returns `true`.
+///
+/// The predicate is evaluated on every dispatch. When it returns `false`,
+/// the inner system is skipped, but its system data is still fetched; so
+/// for scheduling, a skipped system always reserves its reads and writes.
+///
+/// ## Panics
+///
+/// Panics on dispatch if `R` hasn't been added to the `Resources`.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::Conditional;
+///
+/// struct Paused(bool);
+///
+/// struct Physics;
+///
+/// impl<'a> System<'a> for Physics {
+///     type SystemData = ();
+///
+///     fn run(&mut self, _: ()) {}
+/// }
+///
+/// let mut world = World::new();
+/// world.add_resource(Paused(false));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(
+///         Conditional::new(Physics, |paused: &Paused| !paused.0),
+///         "physics",
+///         &[],
+///     )
+///     .build();
+///
+/// dispatcher.dispatch(&world.res);
+/// ```
+pub struct Conditional<S, R, F> {
+    system: S,
+    predicate: F,
+    phantom: PhantomData<R>,
+}
+
+impl<S, R, F> Conditional<S, R, F>
+where
+    R: Resource,
+    F: FnMut(&R) -> bool,
+{
+    /// Creates a new conditional system, running `system` only if
+    /// `predicate` returns `true`.
+    pub fn new(system: S, predicate: F) -> Self {
+        Conditional {
+            system,
+            predicate,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, R, F> System<'a> for Conditional<S, R, F>
+where
+    S: System<'a>,
+    S::SystemData: SystemData<'a>,
+    R: Resource,
+    F: FnMut(&R) -> bool,
+{
+    type SystemData = (ReadExpect<'a, R>, S::SystemData);
+
+    fn run(&mut self, (condition, data): Self::SystemData) {
+        if (self.predicate)(&condition) {
+            self.system.run(data);
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+    }
+}
@@ -0,0 +1,52 @@
+use shred::{ResourceId, SystemData};
+
+/// Returns the resources which keep two systems fetching `A` and `B` from
+/// running in parallel: every resource written by one of them and read or
+/// written by the other.
+///
+/// The `Dispatcher` only orders two systems if they conflict (or one of
+/// them is a dependency of the other), so this helps finding out why
+/// systems expected to run in parallel don't. An empty result means they
+/// can.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::conflicts;
+///
+/// struct Pos;
+/// impl Component for Pos { type Storage = VecStorage<Self>; }
+/// struct Vel;
+/// impl Component for Vel { type Storage = VecStorage<Self>; }
+///
+/// // Both only read `Vel`.
+/// assert!(conflicts::<ReadStorage<Vel>, ReadStorage<Vel>>().is_empty());
+/// // Writing `Pos` conflicts with reading it.
+/// assert_eq!(
+///     conflicts::<(WriteStorage<Pos>, ReadStorage<Vel>), ReadStorage<Pos>>().len(),
+///     1,
+/// );
+/// ```
+pub fn conflicts<'a, A, B>() -> Vec<ResourceId>
+where
+    A: SystemData<'a>,
+    B: SystemData<'a>,
+{
+    let (a_reads, a_writes) = (A::reads(), A::writes());
+    let (b_reads, b_writes) = (B::reads(), B::writes());
+
+    let candidates = a_writes
+        .iter()
+        .filter(|id| b_reads.contains(id) || b_writes.contains(id))
+        .chain(b_writes.iter().filter(|id| a_reads.contains(id)));
+
+    let mut conflicts = Vec::new();
+    for id in candidates {
+        if !conflicts.contains(id) {
+            conflicts.push(id.clone());
+        }
+    }
+
+    conflicts
+}
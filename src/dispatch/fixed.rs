@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use shred::{Dispatcher, Resources, RunNow};
+
+/// A resource accumulating frame time into fixed steps, used by
+/// `FixedStep` to decide how often to run its systems.
+///
+/// `advance` has to be called once per frame with the time passed since
+/// the last frame, before dispatching.
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+    steps: u32,
+}
+
+impl FixedTimestep {
+    /// Creates a timestep of `step`, running at most 8 steps per frame.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(step: Duration) -> Self {
+        assert!(step > Duration::from_secs(0), "the step has to be positive");
+
+        FixedTimestep {
+            step,
+            max_steps: 8,
+            accumulator: Duration::from_secs(0),
+            steps: 0,
+        }
+    }
+
+    /// Sets the maximum number of steps per frame.
+    ///
+    /// If a frame took so long that more steps would be needed, the time
+    /// which can't be caught up with is dropped. This keeps a slow frame
+    /// from making the next one even slower by running more steps.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+
+        self
+    }
+
+    /// Returns the duration of a step.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Adds the time passed since the last frame, determining the number of
+    /// steps to run this frame.
+    pub fn advance(&mut self, delta: Duration) {
+        self.accumulator += delta;
+        self.steps = 0;
+        while self.accumulator >= self.step && self.steps < self.max_steps {
+            self.accumulator -= self.step;
+            self.steps += 1;
+        }
+        if self.accumulator >= self.step {
+            self.accumulator = Duration::from_secs(0);
+        }
+    }
+
+    /// Returns the number of steps to run this frame.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Returns the fraction of a step which has been accumulated, but not
+    /// run yet; e.g. to interpolate between the last two steps when
+    /// rendering.
+    pub fn alpha(&self) -> f64 {
+        secs(self.accumulator) / secs(self.step)
+    }
+}
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1e9
+}
+
+/// A group of systems which is run a number of times per dispatch given by
+/// the `FixedTimestep` resource, e.g. for physics running at a fixed rate.
+///
+/// The systems are dispatched by their own `Dispatcher`, once per step.
+/// The group is added to the main dispatcher as a thread-local system with
+/// `DispatcherBuilder::with_thread_local`, so it runs after all other
+/// systems of a dispatch.
+///
+/// ## Panics
+///
+/// Panics on dispatch if there's no `FixedTimestep` in the `Resources`.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use std::time::Duration;
+/// use specs::dispatch::{FixedStep, FixedTimestep};
+///
+/// # struct Integrate; impl<'a> System<'a> for Integrate { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// # struct Collide; impl<'a> System<'a> for Collide { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// let mut world = World::new();
+/// world.add_resource(FixedTimestep::new(Duration::from_millis(10)));
+///
+/// let physics = DispatcherBuilder::new()
+///     .with(Integrate, "integrate", &[])
+///     .with(Collide, "collide", &["integrate"])
+///     .build();
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(FixedStep::new(physics))
+///     .build();
+/// dispatcher.setup(&mut world.res);
+///
+/// // runs both systems twice
+/// world
+///     .write_resource::<FixedTimestep>()
+///     .advance(Duration::from_millis(25));
+/// dispatcher.dispatch(&world.res);
+/// ```
+pub struct FixedStep<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+}
+
+impl<'a, 'b> FixedStep<'a, 'b> {
+    /// Wraps `dispatcher`, so it's dispatched once per fixed step.
+    pub fn new(dispatcher: Dispatcher<'a, 'b>) -> Self {
+        FixedStep { dispatcher }
+    }
+}
+
+impl<'a, 'b, 'c> RunNow<'c> for FixedStep<'a, 'b> {
+    fn run_now(&mut self, res: &'c Resources) {
+        let steps = res.fetch::<FixedTimestep>().steps();
+        for _ in 0..steps {
+            self.dispatcher.dispatch(res);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.dispatcher.setup(res);
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use shred::{Dispatcher, DispatcherBuilder, Resources, RunningTime, System};
+
+/// The value of the active group if all systems run.
+const ALL: usize = !0;
+
+/// Builder of a `GroupDispatcher`, which allows tagging systems with the
+/// names of groups.
+///
+/// Systems are added to a regular `DispatcherBuilder`, so dependencies
+/// are declared the same way and tagging systems doesn't change how they
+/// are scheduled.
+pub struct GroupDispatcherBuilder<'a, 'b> {
+    builder: DispatcherBuilder<'a, 'b>,
+    groups: Vec<String>,
+    active: Arc<AtomicUsize>,
+}
+
+impl<'a, 'b> GroupDispatcherBuilder<'a, 'b> {
+    /// Creates a new builder without any systems.
+    pub fn new() -> Self {
+        GroupDispatcherBuilder {
+            builder: DispatcherBuilder::new(),
+            groups: Vec::new(),
+            active: Arc::new(AtomicUsize::new(ALL)),
+        }
+    }
+
+    /// Adds a system which doesn't belong to any group, so it only runs
+    /// with `GroupDispatcher::dispatch`.
+    ///
+    /// See `DispatcherBuilder::with` for `name` and `dep`.
+    pub fn with<S>(self, system: S, name: &str, dep: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.with_groups(system, name, dep, &[])
+    }
+
+    /// Adds a system belonging to `groups`, so it also runs when one of
+    /// them is dispatched with `GroupDispatcher::dispatch_group`.
+    ///
+    /// See `DispatcherBuilder::with` for `name` and `dep`.
+    pub fn with_groups<S>(mut self, system: S, name: &str, dep: &[&str], groups: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        let mut ids = Vec::with_capacity(groups.len());
+        for &group in groups {
+            let id = match self.groups.iter().position(|g| g == group) {
+                Some(id) => id,
+                None => {
+                    self.groups.push(group.to_owned());
+                    self.groups.len() - 1
+                }
+            };
+            ids.push(id);
+        }
+
+        let grouped = Grouped {
+            system,
+            groups: ids,
+            active: self.active.clone(),
+        };
+        self.builder = self.builder.with(grouped, name, dep);
+
+        self
+    }
+
+    /// Builds the dispatcher.
+    pub fn build(self) -> GroupDispatcher<'a, 'b> {
+        GroupDispatcher {
+            dispatcher: self.builder.build(),
+            groups: self.groups,
+            active: self.active,
+        }
+    }
+}
+
+impl<'a, 'b> Default for GroupDispatcherBuilder<'a, 'b> {
+    fn default() -> Self {
+        GroupDispatcherBuilder::new()
+    }
+}
+
+/// A dispatcher which can either run all of its systems or only the
+/// systems of one group, built with `GroupDispatcherBuilder`.
+///
+/// Dispatching a group runs the whole schedule, but skips all systems not
+/// in the group, so the order between the systems which are run is still
+/// given by their dependencies. Like with `Conditional`, skipped systems
+/// still fetch their system data.
+///
+/// This is meant for tooling, e.g. for stepping through the AI systems in
+/// an editor.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::GroupDispatcherBuilder;
+///
+/// # struct Plan; impl<'a> System<'a> for Plan { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// # struct Steer; impl<'a> System<'a> for Steer { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// # struct Physics; impl<'a> System<'a> for Physics { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// let mut world = World::new();
+/// let mut dispatcher = GroupDispatcherBuilder::new()
+///     .with_groups(Plan, "plan", &[], &["ai"])
+///     .with_groups(Steer, "steer", &["plan"], &["ai"])
+///     .with(Physics, "physics", &["steer"])
+///     .build();
+/// dispatcher.setup(&mut world.res);
+///
+/// // Runs everything.
+/// dispatcher.dispatch(&world.res);
+/// // Only runs `plan` and `steer`.
+/// dispatcher.dispatch_group(&world.res, "ai");
+/// ```
+pub struct GroupDispatcher<'a, 'b> {
+    dispatcher: Dispatcher<'a, 'b>,
+    groups: Vec<String>,
+    active: Arc<AtomicUsize>,
+}
+
+impl<'a, 'b> GroupDispatcher<'a, 'b> {
+    /// Sets up all the systems, see `Dispatcher::setup`.
+    pub fn setup(&mut self, res: &mut Resources) {
+        self.dispatcher.setup(res);
+    }
+
+    /// Runs all systems.
+    pub fn dispatch(&mut self, res: &Resources) {
+        self.dispatcher.dispatch(res);
+    }
+
+    /// Runs only the systems belonging to `group`.
+    ///
+    /// Does nothing if no system has been added to the group.
+    pub fn dispatch_group(&mut self, res: &Resources, group: &str) {
+        let id = match self.groups.iter().position(|g| g == group) {
+            Some(id) => id,
+            None => return,
+        };
+
+        self.active.store(id, Ordering::SeqCst);
+        self.dispatcher.dispatch(res);
+        self.active.store(ALL, Ordering::SeqCst);
+    }
+
+    /// Returns the names of all groups.
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+}
+
+/// A system only run if all systems or one of its groups are dispatched.
+struct Grouped<S> {
+    system: S,
+    groups: Vec<usize>,
+    active: Arc<AtomicUsize>,
+}
+
+impl<'a, S> System<'a> for Grouped<S>
+where
+    S: System<'a>,
+{
+    type SystemData = S::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        let active = self.active.load(Ordering::SeqCst);
+        if active == ALL || self.groups.contains(&active) {
+            self.system.run(data);
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+    }
+}
@@ -0,0 +1,23 @@
+//! Helpers for scheduling systems with a `Dispatcher`.
+//!
+//! The dispatcher itself is provided by `shred`; the types in this
+//! module wrap systems to change when and how they're run, and help
+//! understanding how they're scheduled.
+
+pub use self::budget::{BestEffort, FrameBudget};
+pub use self::checked::CheckedDispatcherBuilder;
+pub use self::conditional::Conditional;
+pub use self::conflicts::conflicts;
+pub use self::fixed::{FixedStep, FixedTimestep};
+pub use self::group::{GroupDispatcher, GroupDispatcherBuilder};
+pub use self::reload::{HotReload, HotReloadData, HotReloadHandle};
+pub use self::thread_local::ThreadLocalDispatcherBuilder;
+
+mod budget;
+mod checked;
+mod conditional;
+mod conflicts;
+mod fixed;
+mod group;
+mod reload;
+mod thread_local;
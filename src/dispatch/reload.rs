@@ -0,0 +1,223 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use shred::{ResourceId, Resources, RunNow, System, SystemData};
+
+use error::IncompatibleAccess;
+
+/// A system whose implementation can be replaced at runtime, e.g. by one
+/// loaded from a reloaded dynamic library.
+///
+/// The slot keeps the reads and writes of the system it has been created
+/// with, so its position in the schedule of the `Dispatcher` stays valid
+/// when the system is replaced through a `HotReloadHandle`. A replacement
+/// has to access a subset of these resources: it may read what the
+/// original system read or wrote, and write what it wrote.
+///
+/// The replacement is run from the next dispatch on. It isn't set up, so
+/// the resources it accesses have to exist already.
+///
+/// A built `Dispatcher` doesn't give access to its systems, so they can't
+/// be looked up and swapped by name there; instead, a system which should
+/// be replaceable is added wrapped in a `HotReload`.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::HotReload;
+///
+/// #[derive(Default)]
+/// struct Score(u32);
+///
+/// struct AddOne;
+///
+/// impl<'a> System<'a> for AddOne {
+///     type SystemData = Write<'a, Score>;
+///
+///     fn run(&mut self, mut score: Self::SystemData) {
+///         score.0 += 1;
+///     }
+/// }
+///
+/// struct AddTen;
+///
+/// impl<'a> System<'a> for AddTen {
+///     type SystemData = Write<'a, Score>;
+///
+///     fn run(&mut self, mut score: Self::SystemData) {
+///         score.0 += 10;
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// let scoring = HotReload::new(AddOne);
+/// let handle = scoring.handle();
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(scoring, "scoring", &[])
+///     .build();
+/// dispatcher.setup(&mut world.res);
+///
+/// dispatcher.dispatch(&world.res);
+/// handle.replace(AddTen).unwrap();
+/// dispatcher.dispatch(&world.res);
+/// assert_eq!(world.read_resource::<Score>().0, 11);
+/// ```
+pub struct HotReload<S> {
+    current: Arc<Mutex<Box<DynSystem>>>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<S> HotReload<S>
+where
+    S: for<'c> System<'c> + Send + 'static,
+{
+    /// Creates a slot running `system`, declaring its reads and writes.
+    pub fn new(system: S) -> Self {
+        HotReload {
+            current: Arc::new(Mutex::new(Box::new(system))),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns a handle for replacing the system.
+    pub fn handle(&self) -> HotReloadHandle<S> {
+        HotReloadHandle {
+            current: self.current.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, S> System<'a> for HotReload<S>
+where
+    S: System<'a>,
+    S::SystemData: SystemData<'a>,
+{
+    type SystemData = HotReloadData<'a, S>;
+
+    fn run(&mut self, data: Self::SystemData) {
+        self.current.lock().unwrap().run(data.res);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.current.lock().unwrap().setup(res);
+    }
+}
+
+/// Replaces the system of a `HotReload` slot, returned by
+/// `HotReload::handle`.
+pub struct HotReloadHandle<S> {
+    current: Arc<Mutex<Box<DynSystem>>>,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<S> HotReloadHandle<S>
+where
+    S: for<'c> System<'c> + Send + 'static,
+    for<'c> <S as System<'c>>::SystemData: SystemData<'c>,
+{
+    /// Replaces the system of the slot, which runs `system` from the next
+    /// dispatch on.
+    ///
+    /// Returns an error and keeps the current system if `system` accesses
+    /// resources the slot doesn't declare.
+    pub fn replace<N>(&self, system: N) -> Result<(), IncompatibleAccess>
+    where
+        N: for<'c> System<'c> + Send + 'static,
+        for<'c> <N as System<'c>>::SystemData: SystemData<'c>,
+    {
+        let (reads, writes) = accesses::<S>();
+        let (new_reads, new_writes) = accesses::<N>();
+
+        let undeclared = IncompatibleAccess {
+            reads: new_reads
+                .into_iter()
+                .filter(|id| !reads.contains(id) && !writes.contains(id))
+                .collect(),
+            writes: new_writes
+                .into_iter()
+                .filter(|id| !writes.contains(id))
+                .collect(),
+        };
+        if !undeclared.reads.is_empty() || !undeclared.writes.is_empty() {
+            return Err(undeclared);
+        }
+
+        *self.current.lock().unwrap() = Box::new(system);
+
+        Ok(())
+    }
+}
+
+impl<S> Clone for HotReloadHandle<S> {
+    fn clone(&self) -> Self {
+        HotReloadHandle {
+            current: self.current.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// The system data of a `HotReload` slot, declaring the reads and writes
+/// of the system it has been created with.
+///
+/// The current system fetches its own data when it's run.
+pub struct HotReloadData<'a, S> {
+    res: &'a Resources,
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<'a, S> SystemData<'a> for HotReloadData<'a, S>
+where
+    S: System<'a>,
+    S::SystemData: SystemData<'a>,
+{
+    fn setup(res: &mut Resources) {
+        S::SystemData::setup(res);
+    }
+
+    fn fetch(res: &'a Resources) -> Self {
+        HotReloadData {
+            res,
+            phantom: PhantomData,
+        }
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        S::SystemData::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        S::SystemData::writes()
+    }
+}
+
+/// A system with its system data erased, so systems with different system
+/// data can be stored in the same slot.
+trait DynSystem: Send {
+    fn run(&mut self, res: &Resources);
+
+    fn setup(&mut self, res: &mut Resources);
+}
+
+impl<T> DynSystem for T
+where
+    T: for<'c> System<'c> + Send,
+{
+    fn run(&mut self, res: &Resources) {
+        RunNow::run_now(self, res);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        System::setup(self, res);
+    }
+}
+
+fn accesses<'c, S>() -> (Vec<ResourceId>, Vec<ResourceId>)
+where
+    S: System<'c>,
+    S::SystemData: SystemData<'c>,
+{
+    (S::SystemData::reads(), S::SystemData::writes())
+}
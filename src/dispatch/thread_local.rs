@@ -0,0 +1,179 @@
+use shred::{Dispatcher, DispatcherBuilder, Resources, RunNow, System};
+
+use error::DependencyError;
+
+type ThreadLocal<'b> = Box<for<'c> RunNow<'c> + 'b>;
+
+/// A `DispatcherBuilder` whose thread-local systems can depend on each
+/// other, by name, like other systems.
+///
+/// The `Dispatcher` runs thread-local systems in the order they were added,
+/// after all other systems. Thread-local systems added with
+/// `with_thread_local_with_deps` are run in the order given by their
+/// dependencies instead; unlike with `DispatcherBuilder::with`, a
+/// dependency may also be added after the system depending on it. Systems
+/// without dependencies between them keep the order they were added in.
+///
+/// The dependencies are checked by `build`, which returns an error if one
+/// of them doesn't exist or if they are cyclic.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::dispatch::ThreadLocalDispatcherBuilder;
+///
+/// # struct Setup; impl<'a> System<'a> for Setup { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// # struct Submit; impl<'a> System<'a> for Submit { type SystemData = (); fn run(&mut self, _: ()) {} }
+/// let mut world = World::new();
+/// let mut dispatcher = ThreadLocalDispatcherBuilder::new()
+///     .with_thread_local_with_deps(Submit, "render_submit", &["render_setup"])
+///     .with_thread_local_with_deps(Setup, "render_setup", &[])
+///     .build()
+///     .unwrap();
+/// dispatcher.setup(&mut world.res);
+/// dispatcher.dispatch(&world.res);
+/// ```
+pub struct ThreadLocalDispatcherBuilder<'a, 'b> {
+    builder: DispatcherBuilder<'a, 'b>,
+    locals: Vec<(String, Vec<String>, ThreadLocal<'b>)>,
+}
+
+impl<'a, 'b> ThreadLocalDispatcherBuilder<'a, 'b> {
+    /// Creates a new builder without any systems.
+    pub fn new() -> Self {
+        ThreadLocalDispatcherBuilder {
+            builder: DispatcherBuilder::new(),
+            locals: Vec::new(),
+        }
+    }
+
+    /// Adds a system, see `DispatcherBuilder::with`.
+    pub fn with<S>(mut self, system: S, name: &str, dep: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.add(system, name, dep);
+
+        self
+    }
+
+    /// Adds a system, see `DispatcherBuilder::add`.
+    pub fn add<S>(&mut self, system: S, name: &str, dep: &[&str])
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.builder.add(system, name, dep);
+    }
+
+    /// Adds a barrier, see `DispatcherBuilder::with_barrier`.
+    pub fn with_barrier(mut self) -> Self {
+        self.add_barrier();
+
+        self
+    }
+
+    /// Adds a barrier, see `DispatcherBuilder::add_barrier`.
+    pub fn add_barrier(&mut self) {
+        self.builder.add_barrier();
+    }
+
+    /// Adds a thread-local system named `name`, which runs after all
+    /// thread-local systems named in `dep`.
+    pub fn with_thread_local_with_deps<T>(mut self, system: T, name: &str, dep: &[&str]) -> Self
+    where
+        T: for<'c> RunNow<'c> + 'b,
+    {
+        self.add_thread_local_with_deps(system, name, dep);
+
+        self
+    }
+
+    /// Adds a thread-local system named `name`, which runs after all
+    /// thread-local systems named in `dep`.
+    pub fn add_thread_local_with_deps<T>(&mut self, system: T, name: &str, dep: &[&str])
+    where
+        T: for<'c> RunNow<'c> + 'b,
+    {
+        let dep = dep.iter().map(|&d| d.to_owned()).collect();
+        self.locals.push((name.to_owned(), dep, Box::new(system)));
+    }
+
+    /// Orders the thread-local systems by their dependencies and builds
+    /// the dispatcher.
+    ///
+    /// Returns an error if two thread-local systems have the same name, if a
+    /// dependency doesn't exist or if the dependencies are cyclic.
+    pub fn build(self) -> Result<Dispatcher<'a, 'b>, DependencyError> {
+        let ThreadLocalDispatcherBuilder { mut builder, locals } = self;
+        let locals = sort(locals)?;
+        if !locals.is_empty() {
+            builder.add_thread_local(ThreadLocalSystems(locals));
+        }
+
+        Ok(builder.build())
+    }
+}
+
+impl<'a, 'b> Default for ThreadLocalDispatcherBuilder<'a, 'b> {
+    fn default() -> Self {
+        ThreadLocalDispatcherBuilder::new()
+    }
+}
+
+fn sort<'b>(
+    mut pending: Vec<(String, Vec<String>, ThreadLocal<'b>)>,
+) -> Result<Vec<ThreadLocal<'b>>, DependencyError> {
+    for (i, &(ref name, ref dep, _)) in pending.iter().enumerate() {
+        if pending[..i].iter().any(|&(ref other, _, _)| other == name) {
+            return Err(DependencyError::Duplicate(name.clone()));
+        }
+        for d in dep {
+            if pending.iter().all(|&(ref other, _, _)| other != d) {
+                return Err(DependencyError::Missing {
+                    system: name.clone(),
+                    dependency: d.clone(),
+                });
+            }
+        }
+    }
+
+    let mut done: Vec<String> = Vec::with_capacity(pending.len());
+    let mut sorted = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let next = pending
+            .iter()
+            .position(|&(_, ref dep, _)| dep.iter().all(|d| done.contains(d)));
+        match next {
+            Some(i) => {
+                let (name, _, system) = pending.remove(i);
+                done.push(name);
+                sorted.push(system);
+            }
+            None => {
+                let names = pending.into_iter().map(|(name, _, _)| name).collect();
+
+                return Err(DependencyError::Cyclic(names));
+            }
+        }
+    }
+
+    Ok(sorted)
+}
+
+/// Thread-local systems in the order they're run.
+struct ThreadLocalSystems<'b>(Vec<ThreadLocal<'b>>);
+
+impl<'a, 'b> RunNow<'a> for ThreadLocalSystems<'b> {
+    fn run_now(&mut self, res: &'a Resources) {
+        for system in &mut self.0 {
+            system.run_now(res);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        for system in &mut self.0 {
+            system.setup(res);
+        }
+    }
+}
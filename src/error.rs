@@ -4,9 +4,12 @@
 //! and additionally one `Error` type that can represent them all.
 //! Each error in this module has an `Into<Error>` implementation.
 
+use std::any::TypeId;
 use std::error::Error as StdError;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
+use shred::ResourceId;
+
 use world::{Entity, Generation};
 
 /// A boxed error implementing `Debug`, `Display` and `Error`.
@@ -59,6 +62,22 @@ pub enum Error {
     Custom(BoxedErr),
     /// Wrong generation error.
     WrongGeneration(WrongGeneration),
+    /// Stable id already in use.
+    IdInUse(IdInUse),
+    /// Component type not registered.
+    NotRegistered(NotRegistered),
+    /// Type-erased component of the wrong type.
+    WrongType(WrongType),
+    /// Events dropped before they have been read.
+    EventsLost(EventsLost),
+    /// Dependencies of thread-local systems can't be satisfied.
+    Dependency(DependencyError),
+    /// Component type couldn't be registered.
+    Register(RegisterError),
+    /// Systems access resources which don't exist.
+    Build(BuildError),
+    /// A system accesses resources its replacement slot doesn't declare.
+    IncompatibleAccess(IncompatibleAccess),
 
     #[doc(hidden)]
     __NonExhaustive,
@@ -69,6 +88,14 @@ impl Display for Error {
         match *self {
             Error::Custom(ref e) => write!(f, "Custom: {}", e),
             Error::WrongGeneration(ref e) => write!(f, "Wrong generation: {}", e),
+            Error::IdInUse(ref e) => write!(f, "Id in use: {}", e),
+            Error::NotRegistered(ref e) => write!(f, "Not registered: {}", e),
+            Error::WrongType(ref e) => write!(f, "Wrong type: {}", e),
+            Error::EventsLost(ref e) => write!(f, "Events lost: {}", e),
+            Error::Dependency(ref e) => write!(f, "Dependency: {}", e),
+            Error::Register(ref e) => write!(f, "Register: {}", e),
+            Error::Build(ref e) => write!(f, "Build: {}", e),
+            Error::IncompatibleAccess(ref e) => write!(f, "Incompatible access: {}", e),
 
             Error::__NonExhaustive => unimplemented!(),
         }
@@ -87,6 +114,54 @@ impl From<WrongGeneration> for Error {
     }
 }
 
+impl From<IdInUse> for Error {
+    fn from(e: IdInUse) -> Self {
+        Error::IdInUse(e)
+    }
+}
+
+impl From<NotRegistered> for Error {
+    fn from(e: NotRegistered) -> Self {
+        Error::NotRegistered(e)
+    }
+}
+
+impl From<WrongType> for Error {
+    fn from(e: WrongType) -> Self {
+        Error::WrongType(e)
+    }
+}
+
+impl From<EventsLost> for Error {
+    fn from(e: EventsLost) -> Self {
+        Error::EventsLost(e)
+    }
+}
+
+impl From<DependencyError> for Error {
+    fn from(e: DependencyError) -> Self {
+        Error::Dependency(e)
+    }
+}
+
+impl From<RegisterError> for Error {
+    fn from(e: RegisterError) -> Self {
+        Error::Register(e)
+    }
+}
+
+impl From<BuildError> for Error {
+    fn from(e: BuildError) -> Self {
+        Error::Build(e)
+    }
+}
+
+impl From<IncompatibleAccess> for Error {
+    fn from(e: IncompatibleAccess) -> Self {
+        Error::IncompatibleAccess(e)
+    }
+}
+
 impl StdError for Error {
     fn description(&self) -> &str {
         "A Specs error"
@@ -96,6 +171,14 @@ impl StdError for Error {
         let e = match *self {
             Error::Custom(ref e) => e.as_ref(),
             Error::WrongGeneration(ref e) => e,
+            Error::IdInUse(ref e) => e,
+            Error::NotRegistered(ref e) => e,
+            Error::WrongType(ref e) => e,
+            Error::EventsLost(ref e) => e,
+            Error::Dependency(ref e) => e,
+            Error::Register(ref e) => e,
+            Error::Build(ref e) => e,
+            Error::IncompatibleAccess(ref e) => e,
 
             Error::__NonExhaustive => unimplemented!(),
         };
@@ -105,6 +188,9 @@ impl StdError for Error {
 }
 
 /// Wrong generation error.
+///
+/// Carries the index of the entity, the generation that was
+/// supplied and the one that is currently stored for that index.
 #[derive(Debug, PartialEq, Eq)]
 pub struct WrongGeneration {
     /// The action that failed because of the wrong generation.
@@ -116,12 +202,27 @@ pub struct WrongGeneration {
     pub entity: Entity,
 }
 
+impl WrongGeneration {
+    /// The generation that was supplied with the entity.
+    pub fn supplied_gen(&self) -> Generation {
+        self.entity.gen()
+    }
+}
+
 impl Display for WrongGeneration {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         write!(
             f,
-            "Tried to {} entity {:?}, but the generation is wrong; it should be {:?}",
-            self.action, self.entity, self.actual_gen
+            "Tried to {} entity {} with generation {}, but the current generation is {}{}",
+            self.action,
+            self.entity.id(),
+            self.entity.gen().id(),
+            self.actual_gen.id().abs(),
+            if self.actual_gen.is_alive() {
+                ""
+            } else {
+                " (dead)"
+            }
         )
     }
 }
@@ -133,6 +234,258 @@ impl StdError for WrongGeneration {
     }
 }
 
+/// A stable id (see `StableIds`) is already used by an entity which is
+/// still alive.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IdInUse {
+    /// The id that has been requested.
+    pub id: u64,
+    /// The entity already having that id.
+    pub entity: Entity,
+}
+
+impl Display for IdInUse {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "Tried to use id {}, but it is already used by entity {:?}",
+            self.id, self.entity
+        )
+    }
+}
+
+impl StdError for IdInUse {
+    fn description(&self) -> &str {
+        "Used a stable id which already belongs to an alive entity"
+    }
+}
+
+/// A component type has been accessed by its `TypeId` (e.g. with
+/// `World::insert_dyn`), but it hasn't been registered.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotRegistered {
+    /// The `TypeId` of the component type.
+    pub component: TypeId,
+}
+
+impl Display for NotRegistered {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "No component with {:?} has been registered", self.component)
+    }
+}
+
+impl StdError for NotRegistered {
+    fn description(&self) -> &str {
+        "Accessed a component type which hasn't been registered"
+    }
+}
+
+/// A type-erased component (e.g. passed to `World::insert_dyn`) doesn't
+/// have the type it has been inserted as.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WrongType {
+    /// The `TypeId` of the expected component type.
+    pub expected: TypeId,
+}
+
+impl Display for WrongType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "Expected a component with {:?}", self.expected)
+    }
+}
+
+impl StdError for WrongType {
+    fn description(&self) -> &str {
+        "Type-erased component doesn't have the expected type"
+    }
+}
+
+/// A reader of a `BoundedChannel` fell behind, so events have been
+/// dropped before it could read them.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EventsLost {
+    /// The number of events which have been dropped.
+    pub lost: u64,
+}
+
+impl Display for EventsLost {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{} events have been dropped before being read", self.lost)
+    }
+}
+
+impl StdError for EventsLost {
+    fn description(&self) -> &str {
+        "Events have been dropped before being read"
+    }
+}
+
+/// The dependencies of the thread-local systems added to a
+/// `ThreadLocalDispatcherBuilder` can't be satisfied.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DependencyError {
+    /// Two thread-local systems have the same name.
+    Duplicate(String),
+    /// A thread-local system depends on one which hasn't been added.
+    Missing {
+        /// The name of the system.
+        system: String,
+        /// The name of the missing dependency.
+        dependency: String,
+    },
+    /// The dependencies of these thread-local systems are cyclic.
+    Cyclic(Vec<String>),
+}
+
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            DependencyError::Duplicate(ref name) => {
+                write!(f, "A thread-local system named `{}` already exists", name)
+            }
+            DependencyError::Missing {
+                ref system,
+                ref dependency,
+            } => write!(
+                f,
+                "Dependency `{}` of thread-local system `{}` doesn't exist",
+                dependency, system
+            ),
+            DependencyError::Cyclic(ref names) => write!(
+                f,
+                "Thread-local systems `{}` have cyclic dependencies",
+                names.join("`, `")
+            ),
+        }
+    }
+}
+
+impl StdError for DependencyError {
+    fn description(&self) -> &str {
+        "Dependencies of thread-local systems can't be satisfied"
+    }
+}
+
+/// A component type couldn't be registered with `World::try_register`.
+///
+/// Please note that you should not use `__NonExhaustive`,
+/// which is a variant specifically added for extensibility
+/// without breakage.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RegisterError {
+    /// The component type has already been registered; contains its
+    /// `TypeId`.
+    AlreadyRegistered(TypeId),
+
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            RegisterError::AlreadyRegistered(component) => write!(
+                f,
+                "The component with {:?} has already been registered",
+                component
+            ),
+
+            RegisterError::__NonExhaustive => unimplemented!(),
+        }
+    }
+}
+
+impl StdError for RegisterError {
+    fn description(&self) -> &str {
+        "Failed to register a component type"
+    }
+}
+
+/// Systems added to a `CheckedDispatcherBuilder` access resources (or
+/// component storages) which don't exist.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BuildError {
+    /// Every missing resource, for each system accessing it.
+    pub missing: Vec<MissingResource>,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        for (i, missing) in self.missing.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", missing)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl StdError for BuildError {
+    fn description(&self) -> &str {
+        "Systems access resources which don't exist"
+    }
+}
+
+/// A resource accessed by a system which doesn't exist, see `BuildError`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MissingResource {
+    /// The name of the system.
+    pub system: String,
+    /// The missing resource; for component storages, this is the id of
+    /// the `MaskedStorage` of the component.
+    pub resource: ResourceId,
+    /// `true` if the system writes the resource, `false` if it only reads
+    /// it.
+    pub write: bool,
+}
+
+impl Display for MissingResource {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "System `{}` {} {:?}, which doesn't exist",
+            self.system,
+            if self.write { "writes" } else { "reads" },
+            self.resource
+        )
+    }
+}
+
+impl StdError for MissingResource {
+    fn description(&self) -> &str {
+        "A system accesses a resource which doesn't exist"
+    }
+}
+
+/// A system couldn't replace the one of a `HotReload` slot, because it
+/// accesses resources the slot doesn't declare.
+#[derive(Debug, PartialEq, Eq)]
+pub struct IncompatibleAccess {
+    /// Resources read by the system, but neither read nor written by the
+    /// slot.
+    pub reads: Vec<ResourceId>,
+    /// Resources written by the system, but not written by the slot.
+    pub writes: Vec<ResourceId>,
+}
+
+impl Display for IncompatibleAccess {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "The system reads {:?} and writes {:?}, which the slot doesn't declare",
+            self.reads, self.writes
+        )
+    }
+}
+
+impl StdError for IncompatibleAccess {
+    fn description(&self) -> &str {
+        "A system accesses resources its replacement slot doesn't declare"
+    }
+}
+
 /// An error type which cannot be instantiated.
 /// Used as a placeholder for associated error types if
 /// something cannot fail.
@@ -2,8 +2,12 @@
 
 use std;
 use std::cell::UnsafeCell;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
 
-use hibitset::{BitIter, BitProducer, BitSetAll, BitSetAnd, BitSetLike};
+use fnv::FnvHashSet;
+use hibitset::{BitIter, BitProducer, BitSet, BitSetAll, BitSetAnd, BitSetLike, BitSetNot,
+               BitSetOr};
 use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
 use rayon::iter::ParallelIterator;
 use tuple_utils::Split;
@@ -216,6 +220,191 @@ pub trait Join {
         MaybeJoin(self)
     }
 
+    /// Returns a `Join`-able structure that yields the raw index of every
+    /// element together with it.
+    ///
+    /// This is cheaper than joining over `Entities` if only the index is
+    /// needed, e.g. to look up index-keyed external data, because the
+    /// generation doesn't have to be looked up.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// for i in 0..3 {
+    ///     world.create_entity().with(Pos(i * 10)).build();
+    /// }
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let indexed: Vec<_> = (&pos).indexed().join().map(|(i, p)| (i, p.0)).collect();
+    /// assert_eq!(indexed, vec![(0, 0), (1, 10), (2, 20)]);
+    /// ```
+    fn indexed(self) -> JoinIndexed<Self>
+    where
+        Self: Sized,
+    {
+        JoinIndexed(self)
+    }
+
+    /// Returns a `Join`-able structure that skips the first `n` elements
+    /// of this join.
+    ///
+    /// Joins always yield elements in ascending index order, so as long as
+    /// the joined set doesn't change, `skip` and `take` can be combined to
+    /// process a join in stable pages, e.g. across several frames. The
+    /// skipped elements are only counted, their components are never fetched.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// for i in 0..10 {
+    ///     world.create_entity().with(Pos(i)).build();
+    /// }
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let page: Vec<_> = (&pos).skip(4).take(3).join().map(|p| p.0).collect();
+    /// assert_eq!(page, vec![4, 5, 6]);
+    /// ```
+    fn skip(self, n: usize) -> JoinSkip<Self>
+    where
+        Self: Sized,
+    {
+        JoinSkip { join: self, n }
+    }
+
+    /// Returns a `Join`-able structure that yields at most the first `n`
+    /// elements of this join.
+    ///
+    /// See `Join::skip` for paging over a join.
+    fn take(self, n: usize) -> JoinTake<Self>
+    where
+        Self: Sized,
+    {
+        JoinTake { join: self, n }
+    }
+
+    /// Returns a `Join`-able structure that yields the first element of
+    /// this join and then every `n`th one, e.g. to only process part of
+    /// the entities for debug visualizations.
+    ///
+    /// The stride is over the position in the iteration, not over entity
+    /// indices: `step_by(2)` yields the first, third, fifth, ... element,
+    /// no matter which indices they have. Since joins iterate in ascending
+    /// index order, the same set of entities yields the same elements each
+    /// time. The indices of the selected elements are collected into a
+    /// bitset when the join is opened; skipped elements are never fetched.
+    /// This means it shouldn't be used on joins which are unconstrained,
+    /// like `!&storage` on its own.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// for i in 0..5 {
+    ///     world.create_entity().with(Pos(i)).build();
+    /// }
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let sampled: Vec<_> = (&pos).step_by(2).join().map(|p| p.0).collect();
+    /// assert_eq!(sampled, vec![0, 2, 4]);
+    /// ```
+    fn step_by(self, n: usize) -> JoinStepBy<Self>
+    where
+        Self: Sized,
+    {
+        assert!(n != 0, "the step has to be greater than zero");
+
+        JoinStepBy { join: self, n }
+    }
+
+    /// Returns a `Join`-able structure that only yields the elements of
+    /// this join whose indices are not in `mask`.
+    ///
+    /// This is a shorthand for joining with `!mask`, and can be used
+    /// as one term of a bigger join.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// for i in 0..4 {
+    ///     world.create_entity().with(Pos(i)).build();
+    /// }
+    ///
+    /// let mut processed = BitSet::new();
+    /// processed.add(1);
+    /// processed.add(2);
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let rest: Vec<_> = (&pos).exclude(&processed).join().map(|p| p.0).collect();
+    /// assert_eq!(rest, vec![0, 3]);
+    /// ```
+    fn exclude<B>(self, mask: B) -> JoinExclude<Self, B>
+    where
+        Self: Sized,
+        B: BitSetLike,
+    {
+        JoinExclude { join: self, mask }
+    }
+
+    /// Returns a `Join`-able structure that applies `f` to each element of
+    /// this join when it's yielded.
+    ///
+    /// Unlike `Iterator::map`, this is still a join, so it can be combined
+    /// with other joins or used with `par_join`. This allows building
+    /// borrowed views over several components:
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(f32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// # struct Vel(f32); impl Component for Vel { type Storage = VecStorage<Self>; }
+    /// struct Body<'a> {
+    ///     pos: &'a Pos,
+    ///     vel: &'a Vel,
+    /// }
+    ///
+    /// impl<'a> Body<'a> {
+    ///     fn next_pos(&self) -> f32 {
+    ///         self.pos.0 + self.vel.0
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Vel>();
+    /// world.create_entity().with(Pos(1.0)).with(Vel(2.0)).build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let vel = world.read_storage::<Vel>();
+    /// let bodies = (&pos, &vel).map_join(|(pos, vel)| Body { pos, vel });
+    ///
+    /// let next: Vec<_> = bodies.join().map(|body| body.next_pos()).collect();
+    /// assert_eq!(next, vec![3.0]);
+    /// ```
+    fn map_join<F, R>(self, f: F) -> JoinMap<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Type) -> R,
+    {
+        JoinMap { join: self, f }
+    }
+
     /// Open this join by returning the mask and the storages.
     ///
     /// This is unsafe because implementations of this trait can permit
@@ -253,6 +442,61 @@ pub unsafe trait ParJoin: Join {
     }
 }
 
+/// A `Join` over storages which are `DenseStorage`s, which can be iterated
+/// in fixed-size chunks of matched elements.
+///
+/// This is implemented for storages whose `UnprotectedStorage` is a
+/// `DenseStorage`, and for tuples of such storages only.
+///
+/// Chunks are formed in iteration (bitset) order; the elements of a
+/// chunk don't need to belong to adjacent entities.
+///
+/// ## Example
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::join::ChunkedJoin;
+///
+/// # #[derive(Debug, PartialEq)]
+/// # struct Pos(f32); impl Component for Pos { type Storage = VecStorage<Self>; }
+/// let mut world = World::new();
+/// world.register::<Pos>();
+///
+/// for i in 0..10 {
+///     world.create_entity().with(Pos(i as f32)).build();
+/// }
+///
+/// let pos = world.read_storage::<Pos>();
+/// let mut chunks = (&pos).chunked_join(4);
+/// let mut sums = Vec::new();
+/// while let Some(chunk) = chunks.next_chunk() {
+///     sums.push(chunk.iter().map(|p| p.0).sum::<f32>());
+/// }
+/// assert_eq!(sums, vec![6.0, 22.0, 17.0]);
+/// ```
+pub trait ChunkedJoin: Join {
+    /// Create a joined iterator yielding `size` elements at a time.
+    ///
+    /// Like `slice::chunks`, the last chunk holds the remaining elements
+    /// if there are fewer than `size` of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is `0`.
+    fn chunked_join(self, size: usize) -> JoinChunks<Self>
+    where
+        Self: Sized,
+    {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        JoinChunks {
+            iter: JoinIter::new(self),
+            buffer: Vec::with_capacity(size),
+            size,
+        }
+    }
+}
+
 /// A `Join`-able structure that yields all indices, returning `None` for all
 /// missing elements and `Some(T)` for found elements.
 ///
@@ -291,11 +535,399 @@ where
     }
 }
 
+/// A `Join`-able structure that yields the raw index of every element
+/// together with it.
+///
+/// For usage see [`Join::indexed()`].
+///
+/// [`Join::indexed()`]: ../join/trait.Join.html#method.indexed
+pub struct JoinIndexed<J: Join>(pub J);
+
+impl<J: Join> Join for JoinIndexed<J> {
+    type Type = (Index, J::Type);
+    type Value = J::Value;
+    type Mask = J::Mask;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        self.0.open()
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        (id, J::get(value, id))
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+unsafe impl<J: ParJoin> ParJoin for JoinIndexed<J> {}
+
+/// A `Join`-able structure that skips the first elements of another join.
+///
+/// For usage see [`Join::skip()`].
+///
+/// [`Join::skip()`]: ../join/trait.Join.html#method.skip
+pub struct JoinSkip<J: Join> {
+    join: J,
+    n: usize,
+}
+
+impl<J: Join> Join for JoinSkip<J> {
+    type Type = J::Type;
+    type Value = J::Value;
+    type Mask = BitSetRange<J::Mask>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+        let start = nth_index(&mask, self.n).map_or(RANGE_END, u64::from);
+
+        (BitSetRange::new(mask, start, RANGE_END), value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        J::get(value, id)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+unsafe impl<J: ParJoin> ParJoin for JoinSkip<J> {}
+
+/// A `Join`-able structure that yields only the first elements of
+/// another join.
+///
+/// For usage see [`Join::take()`].
+///
+/// [`Join::take()`]: ../join/trait.Join.html#method.take
+pub struct JoinTake<J: Join> {
+    join: J,
+    n: usize,
+}
+
+impl<J: Join> Join for JoinTake<J> {
+    type Type = J::Type;
+    type Value = J::Value;
+    type Mask = BitSetRange<J::Mask>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+        let end = nth_index(&mask, self.n).map_or(RANGE_END, u64::from);
+
+        (BitSetRange::new(mask, 0, end), value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        J::get(value, id)
+    }
+}
+
+unsafe impl<J: ParJoin> ParJoin for JoinTake<J> {}
+
+/// A `Join`-able structure that yields every `n`th element of another
+/// join.
+///
+/// For usage see [`Join::step_by()`].
+///
+/// [`Join::step_by()`]: ../join/trait.Join.html#method.step_by
+pub struct JoinStepBy<J: Join> {
+    join: J,
+    n: usize,
+}
+
+impl<J: Join> Join for JoinStepBy<J> {
+    type Type = J::Type;
+    type Value = J::Value;
+    type Mask = BitSet;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+        let n = self.n;
+        let mut sampled = BitSet::new();
+        sampled.extend(
+            mask.iter()
+                .enumerate()
+                .filter(|&(i, _)| i % n == 0)
+                .map(|(_, id)| id),
+        );
+
+        (sampled, value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        J::get(value, id)
+    }
+}
+
+unsafe impl<J: ParJoin> ParJoin for JoinStepBy<J> {}
+
+/// A `Join`-able structure that leaves out the indices of a bitset.
+///
+/// For usage see [`Join::exclude()`].
+///
+/// [`Join::exclude()`]: ../join/trait.Join.html#method.exclude
+pub struct JoinExclude<J: Join, B> {
+    join: J,
+    mask: B,
+}
+
+impl<J: Join, B: BitSetLike> Join for JoinExclude<J, B> {
+    type Type = J::Type;
+    type Value = J::Value;
+    type Mask = BitSetAnd<J::Mask, BitSetNot<B>>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+
+        (BitSetAnd(mask, BitSetNot(self.mask)), value)
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        J::get(value, id)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+unsafe impl<J: ParJoin, B: BitSetLike> ParJoin for JoinExclude<J, B> {}
+
+/// A `Join`-able structure that applies a function to each element of
+/// another join.
+///
+/// For usage see [`Join::map_join()`].
+///
+/// [`Join::map_join()`]: ../join/trait.Join.html#method.map_join
+pub struct JoinMap<J: Join, F> {
+    join: J,
+    f: F,
+}
+
+impl<J, F, R> Join for JoinMap<J, F>
+where
+    J: Join,
+    F: Fn(J::Type) -> R,
+{
+    type Type = R;
+    type Value = (J::Value, F);
+    type Mask = J::Mask;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+
+        (mask, (value, self.f))
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        (value.1)(J::get(&mut value.0, id))
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+// `f` is only called through a shared reference, so it has to be `Sync`.
+unsafe impl<J, F, R> ParJoin for JoinMap<J, F>
+where
+    J: ParJoin,
+    F: Fn(J::Type) -> R + Sync,
+{
+}
+
+/// A `Join`-able structure over the union of two joins, yielding the
+/// element of each join if it contains the index.
+///
+/// For usage see [`union_join()`].
+///
+/// [`union_join()`]: ../join/fn.union_join.html
+pub struct JoinUnion<A: Join, B: Join> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> Join for JoinUnion<A, B>
+where
+    A: Join,
+    B: Join,
+    A::Mask: Clone,
+    B::Mask: Clone,
+{
+    type Type = (Option<A::Type>, Option<B::Type>);
+    type Value = (A::Mask, A::Value, B::Mask, B::Value);
+    type Mask = BitSetOr<A::Mask, B::Mask>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (a_mask, a_value) = self.a.open();
+        let (b_mask, b_value) = self.b.open();
+
+        (
+            BitSetOr(a_mask.clone(), b_mask.clone()),
+            (a_mask, a_value, b_mask, b_value),
+        )
+    }
+
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        let a = if value.0.contains(id) {
+            Some(A::get(&mut value.1, id))
+        } else {
+            None
+        };
+        let b = if value.2.contains(id) {
+            Some(B::get(&mut value.3, id))
+        } else {
+            None
+        };
+
+        (a, b)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        A::is_unconstrained() || B::is_unconstrained()
+    }
+}
+
+unsafe impl<A, B> ParJoin for JoinUnion<A, B>
+where
+    A: ParJoin,
+    B: ParJoin,
+    A::Mask: Clone,
+    B::Mask: Clone,
+{
+}
+
+/// Number of bits per layer of a hierarchical bitset (`log2` of the bits
+/// in a `usize`).
+const LAYER_BITS: u32 = (std::mem::size_of::<usize>() * 8).trailing_zeros();
+
+/// One past the highest index a hierarchical bitset can hold.
+const RANGE_END: u64 = 1 << (4 * LAYER_BITS);
+
+/// Returns the index of the `n`th (starting at zero) element of `mask`.
+///
+/// Only the set words of the lowest layer are visited, each of them is
+/// skipped as a whole as long as it doesn't contain the element.
+fn nth_index<M: BitSetLike>(mask: &M, mut n: usize) -> Option<Index> {
+    for i2 in SetBits(mask.layer3()) {
+        for i1 in SetBits(mask.layer2(i2)).map(|b| (i2 << LAYER_BITS) | b) {
+            for i0 in SetBits(mask.layer1(i1)).map(|b| (i1 << LAYER_BITS) | b) {
+                let word = mask.layer0(i0);
+                let count = word.count_ones() as usize;
+                if n < count {
+                    let bit = SetBits(word).nth(n).unwrap();
+                    return Some(((i0 << LAYER_BITS) | bit) as Index);
+                }
+                n -= count;
+            }
+        }
+    }
+
+    None
+}
+
+/// Iterator over the positions of the set bits of a word.
+struct SetBits(usize);
+
+impl Iterator for SetBits {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            let bit = self.0.trailing_zeros() as usize;
+            self.0 &= self.0 - 1;
+
+            Some(bit)
+        }
+    }
+}
+
+/// A bitset restricting another bitset to the indices within
+/// `start..end`, used as mask by `JoinSkip` and `JoinTake`.
+///
+/// Words of all layers are masked, so iteration fast-forwards to `start`
+/// instead of visiting every element before it.
+pub struct BitSetRange<M> {
+    mask: M,
+    start: u64,
+    end: u64,
+}
+
+impl<M: BitSetLike> BitSetRange<M> {
+    fn new(mask: M, start: u64, end: u64) -> Self {
+        BitSetRange { mask, start, end }
+    }
+
+    /// Returns the bits of word `i` of layer `layer` (`0` being the lowest)
+    /// which cover at least one index in the range.
+    fn range_word(&self, layer: u32, i: usize) -> usize {
+        if self.start >= self.end {
+            return 0;
+        }
+
+        let shift = layer * LAYER_BITS;
+        let word_bits = 1 << LAYER_BITS;
+        let first = (i as u64) << LAYER_BITS;
+        let last = first + word_bits - 1;
+        let low = self.start >> shift;
+        let high = (self.end - 1) >> shift;
+
+        if low > last || high < first {
+            return 0;
+        }
+
+        let from = low.max(first) - first;
+        let to = high.min(last) - first;
+
+        (!0usize << from) & (!0usize >> (word_bits - 1 - to))
+    }
+}
+
+impl<M: BitSetLike> BitSetLike for BitSetRange<M> {
+    fn layer3(&self) -> usize {
+        self.mask.layer3() & self.range_word(3, 0)
+    }
+
+    fn layer2(&self, i: usize) -> usize {
+        self.mask.layer2(i) & self.range_word(2, i)
+    }
+
+    fn layer1(&self, i: usize) -> usize {
+        self.mask.layer1(i) & self.range_word(1, i)
+    }
+
+    fn layer0(&self, i: usize) -> usize {
+        self.mask.layer0(i) & self.range_word(0, i)
+    }
+
+    fn contains(&self, i: Index) -> bool {
+        let i = u64::from(i);
+
+        self.start <= i && i < self.end && self.mask.contains(i as Index)
+    }
+}
+
 /// `JoinIter` is an `Iterator` over a group of `Storages`.
+///
+/// It also implements `DoubleEndedIterator`, so `.rev()` can be used to
+/// iterate from the highest index to the lowest. Because bitsets can only
+/// be iterated in ascending order, the first call to `next_back` collects
+/// all remaining indices into a buffer, which costs `O(n)` memory and makes
+/// reverse iteration slower than forward iteration.
 #[must_use]
 pub struct JoinIter<J: Join> {
     keys: BitIter<J::Mask>,
     values: J::Value,
+    buffered: Option<VecDeque<Index>>,
 }
 
 impl<J: Join> JoinIter<J> {
@@ -309,6 +941,7 @@ impl<J: Join> JoinIter<J> {
         JoinIter {
             keys: keys.iter(),
             values,
+            buffered: None,
         }
     }
 }
@@ -386,16 +1019,404 @@ impl<J: Join> JoinIter<J> {
     }
 }
 
+impl<J: Join> JoinIter<J> {
+    /// Consumes the iterator, returning the indices it would have visited
+    /// as a `BitSet`, e.g. to use them as a constraint in a later join.
+    ///
+    /// Components aren't fetched, so this is cheaper than collecting the
+    /// entities into a `Vec` and building a `BitSet` from it. Indices
+    /// already returned by `next` or `next_back` aren't included.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos; impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// # struct Frozen; impl Component for Frozen { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Frozen>();
+    ///
+    /// let a = world.create_entity().with(Pos).build();
+    /// let b = world.create_entity().with(Pos).with(Frozen).build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let frozen = world.read_storage::<Frozen>();
+    ///
+    /// let moving = (&pos).exclude(frozen.mask()).join().collect_bitset();
+    /// assert!(moving.contains(a.id()));
+    /// assert!(!moving.contains(b.id()));
+    /// ```
+    pub fn collect_bitset(self) -> BitSet {
+        let mut set = BitSet::new();
+        match self.buffered {
+            Some(buffered) => for idx in buffered {
+                set.add(idx);
+            },
+            None => for idx in self.keys {
+                set.add(idx);
+            },
+        }
+
+        set
+    }
+
+    /// Returns the entity of the first element for which `pred` returns
+    /// `true`, stopping right there.
+    ///
+    /// This is like `Iterator::find`, but returns the entity instead of the
+    /// element, so the entities don't have to be part of the join. Calling
+    /// it again continues after the found element.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(i32, i32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// world.create_entity().with(Pos(0, 0)).build();
+    /// let e = world.create_entity().with(Pos(3, 4)).build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let found = (&pos).join().find_entity(&world.entities(), |p| p.0 == 3 && p.1 == 4);
+    /// assert_eq!(found, Some(e));
+    /// ```
+    pub fn find_entity<P>(&mut self, entities: &Entities, mut pred: P) -> Option<Entity>
+    where
+        P: FnMut(J::Type) -> bool,
+    {
+        while let Some(idx) = self.next_index() {
+            if pred(unsafe { J::get(&mut self.values, idx) }) {
+                return Some(entities.entity(idx));
+            }
+        }
+
+        None
+    }
+
+    fn next_index(&mut self) -> Option<Index> {
+        match self.buffered {
+            Some(ref mut buffered) => buffered.pop_front(),
+            None => self.keys.next(),
+        }
+    }
+
+    /// Returns an iterator which only yields the first element for every
+    /// distinct key returned by `key`, in iteration order.
+    ///
+    /// This is useful to process one representative of a group of entities,
+    /// e.g. one entity per grid cell. All keys seen so far are kept in a
+    /// set, so memory use grows with the number of distinct keys.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Cell(u32); impl Component for Cell { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Cell>();
+    ///
+    /// let a = world.create_entity().with(Cell(3)).build();
+    /// world.create_entity().with(Cell(3)).build();
+    /// let b = world.create_entity().with(Cell(5)).build();
+    ///
+    /// let entities = world.entities();
+    /// let cells = world.read_storage::<Cell>();
+    ///
+    /// let representatives: Vec<_> = (&*entities, &cells)
+    ///     .join()
+    ///     .unique_by(|&(_, cell)| cell.0)
+    ///     .map(|(e, _)| e)
+    ///     .collect();
+    /// assert_eq!(representatives, vec![a, b]);
+    /// ```
+    pub fn unique_by<F, K>(self, key: F) -> JoinUnique<J, F, K>
+    where
+        F: FnMut(&J::Type) -> K,
+        K: Eq + Hash,
+    {
+        JoinUnique {
+            iter: self,
+            key,
+            seen: FnvHashSet::default(),
+        }
+    }
+
+    /// Returns an iterator over all windows of `n` consecutive elements of
+    /// the join, like `slice::windows`; e.g. to compare every element with
+    /// its neighbors.
+    ///
+    /// "Consecutive" refers to the iteration order, which is ascending by
+    /// entity index; elements are neighbors if no other element of the join
+    /// lies between them, regardless of any spatial meaning of the
+    /// components. The windows overlap, so the elements have to be `Clone`,
+    /// which is the case for immutable joins. The last `n - 1` elements are
+    /// buffered. If the join has less than `n` elements, no window is
+    /// returned.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Height(u32); impl Component for Height { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Height>();
+    /// for &h in &[1, 4, 2, 8] {
+    ///     world.create_entity().with(Height(h)).build();
+    /// }
+    ///
+    /// let heights = world.read_storage::<Height>();
+    /// let steps: Vec<_> = heights
+    ///     .join()
+    ///     .windows(2)
+    ///     .map(|w| w[1].0 as i32 - w[0].0 as i32)
+    ///     .collect();
+    /// assert_eq!(steps, vec![3, -2, 6]);
+    /// ```
+    pub fn windows(self, n: usize) -> JoinWindows<J> {
+        assert!(n != 0, "window size must be non-zero");
+
+        JoinWindows {
+            iter: self,
+            size: n,
+            window: VecDeque::with_capacity(n),
+        }
+    }
+
+    /// Consumes the iterator of a join including the entities, returning a
+    /// map from each entity to the rest of its element.
+    ///
+    /// The join has to be a pair with the entities first; more parts can be
+    /// nested, as in `(&entities, (&a, &b))`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let a = world.create_entity().with(Pos(3)).build();
+    /// let b = world.create_entity().build();
+    ///
+    /// let entities = world.entities();
+    /// let pos = world.read_storage::<Pos>();
+    /// let map = (&*entities, &pos).join().collect_map();
+    /// assert_eq!(map.get(&a), Some(&&Pos(3)));
+    /// assert_eq!(map.get(&b), None);
+    /// ```
+    pub fn collect_map<V>(self) -> HashMap<Entity, V>
+    where
+        J: Join<Type = (Entity, V)>,
+    {
+        self.collect()
+    }
+}
+
 impl<J: Join> std::iter::Iterator for JoinIter<J> {
     type Item = J::Type;
 
     fn next(&mut self) -> Option<J::Type> {
-        self.keys
-            .next()
+        self.next_index()
             .map(|idx| unsafe { J::get(&mut self.values, idx) })
     }
 }
 
+impl<J: Join> std::iter::DoubleEndedIterator for JoinIter<J> {
+    fn next_back(&mut self) -> Option<J::Type> {
+        let idx = match self.buffered {
+            Some(ref mut buffered) => buffered.pop_back(),
+            None => {
+                let mut buffered: VecDeque<_> = self.keys.by_ref().collect();
+                let idx = buffered.pop_back();
+                self.buffered = Some(buffered);
+
+                idx
+            }
+        };
+
+        idx.map(|idx| unsafe { J::get(&mut self.values, idx) })
+    }
+}
+
+/// Iterates over two joins in lockstep, pairing the `n`th element of `a`
+/// with the `n`th element of `b`, until either of them ends.
+///
+/// Unlike joining a tuple `(a, b)`, which only yields indices both joins
+/// have, this pairs the elements by their position; the indices of paired
+/// elements usually differ. This is meant for matching up two pools which
+/// have the same number of elements, e.g. assigning workers to jobs; if one
+/// is larger, its remaining elements are left out.
+///
+/// ## Example
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::join::zip_joins;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct Worker(u32);
+/// # impl Component for Worker { type Storage = VecStorage<Self>; }
+/// # #[derive(Debug, PartialEq)]
+/// struct Job(&'static str);
+/// # impl Component for Job { type Storage = VecStorage<Self>; }
+/// let mut world = World::new();
+/// world.register::<Worker>();
+/// world.register::<Job>();
+///
+/// world.create_entity().with(Worker(1)).build();
+/// world.create_entity().with(Job("mine")).build();
+/// world.create_entity().with(Worker(2)).build();
+/// world.create_entity().with(Job("build")).build();
+///
+/// let workers = world.read_storage::<Worker>();
+/// let jobs = world.read_storage::<Job>();
+/// let pairs: Vec<_> = zip_joins(&workers, &jobs).map(|(w, j)| (w.0, j.0)).collect();
+/// assert_eq!(pairs, vec![(1, "mine"), (2, "build")]);
+/// ```
+pub fn zip_joins<A, B>(a: A, b: B) -> std::iter::Zip<JoinIter<A>, JoinIter<B>>
+where
+    A: Join,
+    B: Join,
+{
+    a.join().zip(b.join())
+}
+
+/// Returns a `Join`-able structure over the union of two joins: every index
+/// contained in at least one of them is yielded, along with the element of
+/// each join which contains it, or `None`.
+///
+/// This is the counterpart of joining a tuple, which only yields the
+/// indices contained in all joins.
+///
+/// ## Example
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::join::union_join;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct Light(u32);
+/// # impl Component for Light { type Storage = VecStorage<Self>; }
+/// # #[derive(Debug, PartialEq)]
+/// struct Heat(u32);
+/// # impl Component for Heat { type Storage = VecStorage<Self>; }
+/// let mut world = World::new();
+/// world.register::<Light>();
+/// world.register::<Heat>();
+///
+/// world.create_entity().with(Light(1)).build();
+/// world.create_entity().build();
+/// world.create_entity().with(Heat(2)).build();
+///
+/// let light = world.read_storage::<Light>();
+/// let heat = world.read_storage::<Heat>();
+/// let sources: Vec<_> = union_join(&light, &heat).join().collect();
+/// assert_eq!(sources, vec![(Some(&Light(1)), None), (None, Some(&Heat(2)))]);
+/// ```
+pub fn union_join<A, B>(a: A, b: B) -> JoinUnion<A, B>
+where
+    A: Join,
+    B: Join,
+{
+    JoinUnion { a, b }
+}
+
+/// `JoinChunks` yields chunks of joined components, created by
+/// `ChunkedJoin::chunked_join`.
+///
+/// The elements of a chunk are gathered in a buffer which is reused for
+/// every chunk, so iterating doesn't allocate. Because a chunk borrows that
+/// buffer, this is not an `Iterator`; chunks are taken with
+/// `JoinChunks::next_chunk` instead.
+#[must_use]
+pub struct JoinChunks<J: Join> {
+    iter: JoinIter<J>,
+    buffer: Vec<J::Type>,
+    size: usize,
+}
+
+impl<J: Join> JoinChunks<J> {
+    /// Returns the next chunk, or `None` once all elements have been
+    /// yielded.
+    ///
+    /// Every chunk has the size given to `chunked_join`, except for the
+    /// last one, which holds the remaining elements if there are fewer.
+    pub fn next_chunk(&mut self) -> Option<&[J::Type]> {
+        self.buffer.clear();
+        self.buffer.extend(self.iter.by_ref().take(self.size));
+
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(&self.buffer)
+        }
+    }
+}
+
+/// `JoinUnique` is an `Iterator` yielding the first element of a join for
+/// every distinct key, created by `JoinIter::unique_by`.
+#[must_use]
+pub struct JoinUnique<J: Join, F, K> {
+    iter: JoinIter<J>,
+    key: F,
+    seen: FnvHashSet<K>,
+}
+
+impl<J, F, K> std::iter::Iterator for JoinUnique<J, F, K>
+where
+    J: Join,
+    F: FnMut(&J::Type) -> K,
+    K: Eq + Hash,
+{
+    type Item = J::Type;
+
+    fn next(&mut self) -> Option<J::Type> {
+        while let Some(item) = self.iter.next() {
+            if self.seen.insert((self.key)(&item)) {
+                return Some(item);
+            }
+        }
+
+        None
+    }
+}
+
+/// `JoinWindows` is an `Iterator` over overlapping windows of consecutive
+/// elements of a join, created by `JoinIter::windows`.
+#[must_use]
+pub struct JoinWindows<J: Join> {
+    iter: JoinIter<J>,
+    size: usize,
+    window: VecDeque<J::Type>,
+}
+
+impl<J> std::iter::Iterator for JoinWindows<J>
+where
+    J: Join,
+    J::Type: Clone,
+{
+    type Item = Vec<J::Type>;
+
+    fn next(&mut self) -> Option<Vec<J::Type>> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
 /// `JoinParIter` is a `ParallelIterator` over a group of `Storages`.
 #[must_use]
 pub struct JoinParIter<J>(J);
@@ -525,6 +1546,10 @@ macro_rules! define_open {
             where $($from: ParJoin),*,
                   ($(<$from as Join>::Mask,)*): BitAnd,
         {}
+        impl<$($from,)*> ChunkedJoin for ($($from),*,)
+            where $($from: ChunkedJoin),*,
+                  ($(<$from as Join>::Mask,)*): BitAnd,
+        {}
 
     }
 }
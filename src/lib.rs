@@ -214,6 +214,8 @@ pub mod saveload;
 
 mod bitset;
 pub mod changeset;
+pub mod channel;
+pub mod dispatch;
 pub mod error;
 pub mod join;
 pub mod prelude;
@@ -0,0 +1,244 @@
+use std::fmt::Display;
+use std::ops::{Deref, DerefMut};
+
+use hibitset::{BitSetLike, BitSetOr};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{self, Serialize, Serializer};
+
+use saveload::{FromDeserialize, IntoSerialize, Marker, MarkerAllocator};
+use storage::{MaskedStorage, ReadStorage, Storage, TrackReaders, Tracked, WriteStorage};
+use world::{Component, EntitiesRes, Entity};
+
+/// A change of a single component, written by `Storage::serialize_changes`
+/// and read by `Storage::apply_changes`.
+///
+/// Entities are identified by their marker, so changes can be applied to
+/// another world.
+#[derive(Serialize, Deserialize)]
+pub enum ComponentChange<M, D> {
+    /// The component has been inserted; contains its current value.
+    Inserted(M, D),
+    /// The component has been modified; contains its current value.
+    Modified(M, D),
+    /// The component has been removed.
+    Removed(M),
+}
+
+/// The changes of a tracked storage grouped by kind, returned by
+/// `Storage::changed_pairs`.
+///
+/// Entities are identified by their marker, and components are converted
+/// with `IntoSerialize`, so the changes can be sent to another world as
+/// they are.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChangedPairs<M, D> {
+    /// The inserted components with their current value.
+    pub inserted: Vec<(M, D)>,
+    /// The modified components with their current value.
+    pub modified: Vec<(M, D)>,
+    /// The entities whose component has been removed.
+    pub removed: Vec<M>,
+}
+
+impl<M, D> ChangedPairs<M, D> {
+    /// Returns `true` if there are no changes at all.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    T::Storage: Tracked,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Reads all pending events of a tracked storage and returns the
+    /// resulting changes of marked entities grouped by kind, in a single
+    /// pass over the changed components.
+    ///
+    /// This is like `serialize_changes`, but leaves serializing to the
+    /// caller, e.g. to send each group in its own message. The same rules
+    /// apply: changes are collapsed to their net effect and entities
+    /// without a marker, including deleted ones, are skipped. Each group is
+    /// ordered by entity index.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # extern crate specs;
+    /// # #[macro_use] extern crate serde;
+    /// # use specs::prelude::*;
+    /// # use specs::saveload::{MarkedBuilder, U64Marker, U64MarkerAllocator};
+    /// #[derive(Clone, Debug, PartialEq, Serialize)]
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut world = World::new();
+    /// world.add_resource(U64MarkerAllocator::new());
+    /// world.register::<Health>();
+    /// world.register::<U64Marker>();
+    /// let mut readers = world.write_storage::<Health>().track_all();
+    ///
+    /// let e = world
+    ///     .create_entity()
+    ///     .with(Health(10))
+    ///     .marked::<U64Marker>()
+    ///     .build();
+    ///
+    /// let health = world.read_storage::<Health>();
+    /// let markers = world.read_storage::<U64Marker>();
+    /// let changes = health
+    ///     .changed_pairs(&mut readers, &world.entities(), &markers)
+    ///     .unwrap();
+    /// assert_eq!(changes.inserted, vec![(*markers.get(e).unwrap(), Health(10))]);
+    /// # }
+    /// ```
+    pub fn changed_pairs<M>(
+        &self,
+        readers: &mut TrackReaders,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+    ) -> Result<ChangedPairs<M, <T as IntoSerialize<M>>::Data>, <T as IntoSerialize<M>>::Error>
+    where
+        M: Marker,
+        T: IntoSerialize<M>,
+    {
+        let net = self.net_changes(readers);
+        let changed = BitSetOr(BitSetOr(&net.inserted, &net.modified), &net.removed);
+
+        let ids = |entity: Entity| -> Option<M> { markers.get(entity).cloned() };
+        let mut changes = ChangedPairs {
+            inserted: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+        };
+        for id in changed.iter() {
+            let entity = entities.entity(id);
+            let marker = match ids(entity) {
+                Some(marker) => marker,
+                None => continue,
+            };
+
+            match self.get(entity) {
+                Some(comp) => {
+                    let data = comp.into(&ids)?;
+                    if net.inserted.contains(id) {
+                        changes.inserted.push((marker, data));
+                    } else {
+                        changes.modified.push((marker, data));
+                    }
+                }
+                None => changes.removed.push(marker),
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Reads all pending events of a tracked storage and serializes the
+    /// resulting changes as a sequence of `ComponentChange`s, one per
+    /// changed component of a marked entity.
+    ///
+    /// Changes are collapsed to their net effect, see `Storage::net_changes`;
+    /// inserted and modified components are written with their current
+    /// value. Entities without a marker are skipped, just like with
+    /// `SerializeComponents`. This includes entities which have been
+    /// deleted, because their marker is removed as well; deleted entities
+    /// have to be replicated separately.
+    pub fn serialize_changes<M, S>(
+        &self,
+        readers: &mut TrackReaders,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        M: Marker,
+        T: IntoSerialize<M>,
+        <T as IntoSerialize<M>>::Error: Display,
+        S: Serializer,
+    {
+        let net = self.net_changes(readers);
+        let changed = BitSetOr(BitSetOr(&net.inserted, &net.modified), &net.removed);
+
+        let ids = |entity: Entity| -> Option<M> { markers.get(entity).cloned() };
+        let mut changes = Vec::new();
+        for id in changed.iter() {
+            let entity = entities.entity(id);
+            let marker = match ids(entity) {
+                Some(marker) => marker,
+                None => continue,
+            };
+
+            let change = match self.get(entity) {
+                Some(comp) => {
+                    let data = comp.into(&ids).map_err(ser::Error::custom)?;
+                    if net.inserted.contains(id) {
+                        ComponentChange::Inserted(marker, data)
+                    } else {
+                        ComponentChange::Modified(marker, data)
+                    }
+                }
+                None => ComponentChange::Removed(marker),
+            };
+            changes.push(change);
+        }
+
+        changes.serialize(serializer)
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Deserializes a sequence of `ComponentChange`s written by
+    /// `serialize_changes` and applies them to this storage.
+    ///
+    /// Markers are mapped to entities using `alloc`; for inserted and
+    /// modified components, unknown markers get a new entity, while
+    /// removals of unknown markers are ignored.
+    pub fn apply_changes<'de, M, De>(
+        &mut self,
+        entities: &EntitiesRes,
+        markers: &mut WriteStorage<M>,
+        alloc: &mut M::Allocator,
+        deserializer: De,
+    ) -> Result<(), De::Error>
+    where
+        M: Marker,
+        T: FromDeserialize<M>,
+        <T as FromDeserialize<M>>::Error: Display,
+        De: Deserializer<'de>,
+    {
+        let changes =
+            Vec::<ComponentChange<M, <T as FromDeserialize<M>>::Data>>::deserialize(deserializer)?;
+        for change in changes {
+            match change {
+                ComponentChange::Inserted(marker, data)
+                | ComponentChange::Modified(marker, data) => {
+                    let entity = alloc.retrieve_entity(marker, markers, entities);
+                    let comp = {
+                        let ids =
+                            |marker: M| Some(alloc.retrieve_entity(marker, markers, entities));
+                        T::from(data, ids).map_err(de::Error::custom)?
+                    };
+                    self.insert(entity, comp).map_err(de::Error::custom)?;
+                }
+                ComponentChange::Removed(marker) => {
+                    if let Some(entity) = alloc.retrieve_entity_internal(marker.id()) {
+                        self.remove(entity);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -22,13 +22,23 @@
 //! of these ids is what `MarkerAllocator`s are responsible for. For an example,
 //! see the docs for the `Marker` trait.
 //!
+//! ## Changes
+//!
+//! For replicating a world incrementally, `Storage::serialize_changes`
+//! writes the changes of a tracked storage since the last call as
+//! `ComponentChange`s, which `Storage::apply_changes` applies to another
+//! world. `Storage::changed_pairs` returns the same changes grouped by
+//! kind, for sending them in a custom format.
+//!
 
+mod changes;
 mod de;
 mod marker;
 mod ser;
 #[cfg(test)]
 mod tests;
 
+pub use self::changes::{ChangedPairs, ComponentChange};
 pub use self::de::{DeserializeComponents, FromDeserialize};
 pub use self::marker::{MarkedBuilder, Marker, MarkerAllocator, U64Marker, U64MarkerAllocator};
 pub use self::ser::{IntoSerialize, SerializeComponents};
@@ -134,6 +134,98 @@ where
         serseq.end()
     }
 
+    /// Serialize components from specified storages
+    /// of the marked entities which are also in `filter`,
+    /// e.g. only the entities with a `Persistent` tag component.
+    /// The components to save are selected by the storages,
+    /// like with `serialize`.
+    /// References to entities which aren't selected are treated like
+    /// references to unmarked entities, i.e. the closure passed in `ids`
+    /// returns `None` for them, so loading the result
+    /// (with `DeserializeComponents::deserialize` and storages of the
+    /// same component types) doesn't create entities for them.
+    ///
+    /// ```
+    /// # extern crate ron;
+    /// # extern crate specs;
+    /// # #[macro_use] extern crate serde;
+    /// # use specs::prelude::*;
+    /// use specs::error::NoError;
+    /// use specs::saveload::{MarkedBuilder, SerializeComponents, U64Marker, U64MarkerAllocator};
+    ///
+    /// #[derive(Clone, Serialize)]
+    /// struct Pos(f32);
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Persistent;
+    /// impl Component for Persistent {
+    ///     type Storage = NullStorage<Self>;
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Persistent>();
+    /// world.register::<U64Marker>();
+    /// world.add_resource(U64MarkerAllocator::new());
+    ///
+    /// world.create_entity().with(Pos(1.5)).with(Persistent).marked::<U64Marker>().build();
+    /// world.create_entity().with(Pos(2.5)).marked::<U64Marker>().build();
+    ///
+    /// let mut ser = ron::ser::Serializer::new(None, false);
+    /// let (entities, pos, persistent, markers) = world.system_data::<(
+    ///     Entities,
+    ///     ReadStorage<Pos>,
+    ///     ReadStorage<Persistent>,
+    ///     ReadStorage<U64Marker>,
+    /// )>();
+    /// SerializeComponents::<NoError, U64Marker>::serialize_selected(
+    ///     &(&pos,),
+    ///     &entities,
+    ///     &markers,
+    ///     &persistent,
+    ///     &mut ser,
+    /// ).unwrap();
+    /// let saved = ser.into_output_string();
+    /// assert!(saved.contains("1.5"));
+    /// assert!(!saved.contains("2.5"));
+    /// # }
+    /// ```
+    fn serialize_selected<J, S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        filter: J,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Display,
+        J: Join,
+        S: Serializer,
+    {
+        let selected = (&*markers, filter).join().collect_bitset();
+
+        let mut serseq = serializer.serialize_seq(None)?;
+        let ids = |entity: Entity| -> Option<M> {
+            if selected.contains(entity.id()) {
+                markers.get(entity).cloned()
+            } else {
+                None
+            }
+        };
+        for (entity, marker, _) in (&*entities, &*markers, &selected).join() {
+            serseq.serialize_element(&EntityData::<M, Self::Data> {
+                marker: marker.clone(),
+                components: self.serialize_entity(entity, &ids)
+                    .map_err(ser::Error::custom)?,
+            })?;
+        }
+        serseq.end()
+    }
+
     /// Serialize components from specified storages
     /// of all marked entities with provided serializer.
     /// When the component gets serialized the closure passed
@@ -161,4 +161,255 @@ mod marker_test {
             assert_eq!(marker_ids.len(), marker_id_set.len());
         });
     }
+
+    #[derive(Clone, Debug, Default)]
+    struct Persistent;
+
+    impl Component for Persistent {
+        type Storage = NullStorage<Self>;
+    }
+
+    #[test]
+    fn serialize_selected() {
+        let mut world = World::new();
+
+        world.add_resource(U64MarkerAllocator::new());
+        world.register::<A>();
+        world.register::<B>();
+        world.register::<Persistent>();
+        world.register::<U64Marker>();
+
+        world
+            .create_entity()
+            .with(A(1))
+            .with(B(true))
+            .with(Persistent)
+            .marked::<U64Marker>()
+            .build();
+        world
+            .create_entity()
+            .with(A(2))
+            .with(Persistent)
+            .marked::<U64Marker>()
+            .build();
+        world
+            .create_entity()
+            .with(A(3))
+            .with(B(false))
+            .marked::<U64Marker>()
+            .build();
+
+        // Only save `A` of persistent entities
+        let mut ser = ron::ser::Serializer::new(Some(Default::default()), true);
+
+        world.exec(
+            |(ents, comp_a, persistent, markers): (
+                Entities,
+                ReadStorage<A>,
+                ReadStorage<Persistent>,
+                ReadStorage<U64Marker>,
+            )| {
+                SerializeComponents::<NoError, U64Marker>::serialize_selected(
+                    &(&comp_a,),
+                    &ents,
+                    &markers,
+                    &persistent,
+                    &mut ser,
+                ).unwrap();
+            },
+        );
+
+        let serial = ser.into_output_string();
+
+        let mut de = ron::de::Deserializer::from_str(&serial).unwrap();
+
+        let mut world = World::new();
+
+        world.add_resource(U64MarkerAllocator::new());
+        world.register::<A>();
+        world.register::<B>();
+        world.register::<U64Marker>();
+
+        world.exec(
+            |(ents, comp_a, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<A>,
+                WriteStorage<U64Marker>,
+                Write<U64MarkerAllocator>,
+            )| {
+                DeserializeComponents::<Error, _>::deserialize(
+                    &mut (comp_a,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &mut de,
+                ).unwrap();
+            },
+        );
+
+        assert_marked_entity_count(&mut world, 2);
+        let comp_a = world.read_storage::<A>();
+        let comp_b = world.read_storage::<B>();
+        let mut loaded: Vec<_> = (&comp_a).join().cloned().collect();
+        loaded.sort_by_key(|a| a.0);
+        assert_eq!(loaded, vec![A(1), A(2)]);
+        assert_eq!((&comp_b).join().count(), 0);
+    }
+}
+
+mod changes_test {
+    use super::*;
+    use serde::Deserialize;
+    use storage::TrackReaders;
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    fn create_world() -> World {
+        let mut world = World::new();
+        world.add_resource(U64MarkerAllocator::new());
+        world.register::<Health>();
+        world.register::<U64Marker>();
+
+        world
+    }
+
+    fn serialize(world: &mut World, readers: &mut TrackReaders) -> String {
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, health, markers): (Entities, ReadStorage<Health>, ReadStorage<U64Marker>)| {
+                health
+                    .serialize_changes(readers, &ents, &markers, &mut ser)
+                    .unwrap();
+            },
+        );
+
+        ser.into_output_string()
+    }
+
+    fn apply(world: &mut World, serial: &str) {
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        world.exec(
+            |(ents, mut health, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Health>,
+                WriteStorage<U64Marker>,
+                Write<U64MarkerAllocator>,
+            )| {
+                health
+                    .apply_changes(&ents, &mut markers, &mut alloc, &mut de)
+                    .unwrap();
+            },
+        );
+        world.maintain();
+    }
+
+    fn healths(world: &World) -> Vec<(u64, u32)> {
+        let markers = world.read_storage::<U64Marker>();
+        let health = world.read_storage::<Health>();
+        let mut healths: Vec<_> = (&markers, &health)
+            .join()
+            .map(|(m, h)| (m.id(), h.0))
+            .collect();
+        healths.sort();
+
+        healths
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut source = create_world();
+        let mut target = create_world();
+        let mut readers = source.write_storage::<Health>().track_all();
+
+        let a = source
+            .create_entity()
+            .with(Health(10))
+            .marked::<U64Marker>()
+            .build();
+        let b = source
+            .create_entity()
+            .with(Health(20))
+            .marked::<U64Marker>()
+            .build();
+        // not marked, so it's not replicated
+        source.create_entity().with(Health(30)).build();
+
+        let serial = serialize(&mut source, &mut readers);
+        apply(&mut target, &serial);
+        assert_eq!(healths(&target), healths(&source));
+        assert_eq!(healths(&target).len(), 2);
+
+        source.write_storage::<Health>().get_mut(a).unwrap().0 = 5;
+        source.write_storage::<Health>().remove(b);
+
+        let serial = serialize(&mut source, &mut readers);
+        apply(&mut target, &serial);
+        assert_eq!(healths(&target), healths(&source));
+        assert_eq!(healths(&target).len(), 1);
+
+        // Nothing changed since the last call.
+        let serial = serialize(&mut source, &mut readers);
+        let mut de = ron::de::Deserializer::from_str(&serial).unwrap();
+        let changes: Vec<ComponentChange<U64Marker, Health>> =
+            Deserialize::deserialize(&mut de).unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn changed_pairs() {
+        let mut world = create_world();
+        let mut readers = world.write_storage::<Health>().track_all();
+
+        let marked = |world: &mut World, health| {
+            world
+                .create_entity()
+                .with(Health(health))
+                .marked::<U64Marker>()
+                .build()
+        };
+        let a = marked(&mut world, 1);
+        let b = marked(&mut world, 2);
+        let c = marked(&mut world, 3);
+        world.create_entity().with(Health(4)).build();
+        world.exec(
+            |(ents, health, markers): (Entities, ReadStorage<Health>, ReadStorage<U64Marker>)| {
+                health.changed_pairs(&mut readers, &ents, &markers).unwrap();
+            },
+        );
+
+        let d = marked(&mut world, 5);
+        {
+            let mut health = world.write_storage::<Health>();
+            health.get_mut(a).unwrap().0 = 10;
+            health.get_mut(b).unwrap().0 = 20;
+            health.remove(b);
+            health.remove(c);
+            health.get_mut(d).unwrap().0 = 50;
+        }
+        let e = marked(&mut world, 6);
+        world.write_storage::<Health>().remove(e);
+
+        let (a, b, c, d) = {
+            let markers = world.read_storage::<U64Marker>();
+            let marker = |e| *markers.get(e).unwrap();
+
+            (marker(a), marker(b), marker(c), marker(d))
+        };
+        world.exec(
+            |(ents, health, markers): (Entities, ReadStorage<Health>, ReadStorage<U64Marker>)| {
+                let changes = health.changed_pairs(&mut readers, &ents, &markers).unwrap();
+                assert_eq!(changes.inserted, vec![(d, Health(50))]);
+                assert_eq!(changes.modified, vec![(a, Health(10))]);
+                assert_eq!(changes.removed, vec![b, c]);
+
+                let changes = health.changed_pairs(&mut readers, &ents, &markers).unwrap();
+                assert!(changes.is_empty());
+            },
+        );
+    }
 }
@@ -0,0 +1,96 @@
+use rayon::prelude::*;
+
+use error::Error;
+use storage::{EnsureCapacity, WriteStorage};
+use world::{Component, Entity};
+
+type InsertJob<'a> = Box<FnMut() -> Result<(), Error> + Send + 'a>;
+
+/// Inserts batches of components into several storages in parallel, one
+/// storage per task on the global `rayon` thread pool.
+///
+/// This speeds up bulk loading, where many component types are filled for
+/// the same block of entities at once. Each batch is inserted with
+/// `Storage::insert_batch`, so tracked storages report the insertions as
+/// usual.
+///
+/// Only available with the `parallel` feature.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::ParInsert;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Pos(u32);
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Vel(u32);
+/// impl Component for Vel {
+///     type Storage = DenseVecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Vel>();
+/// let entities: Vec<_> = world.create_iter().take(100).collect();
+///
+/// let mut pos = world.write_storage::<Pos>();
+/// let mut vel = world.write_storage::<Vel>();
+/// ParInsert::new()
+///     .with(&mut pos, entities.iter().map(|&e| (e, Pos(e.id()))).collect())
+///     .with(&mut vel, entities.iter().map(|&e| (e, Vel(1))).collect())
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(pos.get(entities[42]), Some(&Pos(42)));
+/// assert_eq!(vel.get(entities[42]), Some(&Vel(1)));
+/// ```
+#[derive(Default)]
+pub struct ParInsert<'a> {
+    jobs: Vec<InsertJob<'a>>,
+}
+
+impl<'a> ParInsert<'a> {
+    /// Creates an empty `ParInsert`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a batch of components to be inserted into `storage`.
+    ///
+    /// Since every storage is borrowed mutably, no two batches can target
+    /// the same one.
+    pub fn with<'b: 'a, T>(
+        mut self,
+        storage: &'a mut WriteStorage<'b, T>,
+        batch: Vec<(Entity, T)>,
+    ) -> Self
+    where
+        T: Component + Send,
+        T::Storage: EnsureCapacity,
+    {
+        let mut batch = Some(batch);
+        self.jobs.push(Box::new(move || {
+            storage.insert_batch(batch.take().unwrap_or_default())
+        }));
+
+        self
+    }
+
+    /// Inserts all batches, blocking until they're done.
+    ///
+    /// Like with `Storage::insert_batch`, a batch containing a dead entity
+    /// isn't inserted at all; the other batches are inserted regardless,
+    /// and one of the errors is returned.
+    pub fn run(self) -> Result<(), Error> {
+        self.jobs
+            .into_par_iter()
+            .map(|mut job| job())
+            .reduce(|| Ok(()), |a, b| a.and(b))
+    }
+}
@@ -0,0 +1,147 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use hibitset::BitSetLike;
+
+use storage::{DenseVecStorage, DistinctStorage, MaskedStorage, Storage, TryDefault,
+              UnprotectedStorage};
+use world::{Component, Entity, Index};
+
+/// Wrapper storage which keeps a version per component, incremented on
+/// every insertion and every mutable access, so caches keyed on the entity
+/// and its version can cheaply tell whether they're stale.
+///
+/// Unlike `FlaggedStorage`, this doesn't record events which have to be
+/// read; the current version is simply asked for with `Storage::version`.
+/// And unlike `VersionedStorage`, the version is maintained by the storage
+/// instead of the component.
+///
+/// Versions only ever grow for an index: they are kept when a component is
+/// removed, so a component inserted later at the same index continues
+/// from the old version. Like with `FlaggedStorage`, any mutable access
+/// bumps the version, even if the component isn't changed; use
+/// `Storage::get_mut_silent` to avoid that.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::VersionCountStorage;
+///
+/// struct Mesh(Vec<[f32; 3]>);
+///
+/// impl Component for Mesh {
+///     type Storage = VersionCountStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Mesh>();
+/// let e = world.create_entity().with(Mesh(vec![])).build();
+///
+/// let mut meshes = world.write_storage::<Mesh>();
+/// let cached = meshes.version(e).unwrap();
+/// meshes.get_mut(e).unwrap().0.push([0.0; 3]);
+/// assert!(meshes.version(e).unwrap() > cached);
+/// ```
+pub struct VersionCountStorage<C, T = DenseVecStorage<C>> {
+    versions: Vec<u64>,
+    storage: T,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> VersionCountStorage<C, T> {
+    /// Returns the version of the component at `id`.
+    ///
+    /// The result is meaningless if there is no component at `id`.
+    pub fn version(&self, id: Index) -> u64 {
+        self.versions.get(id as usize).cloned().unwrap_or(0)
+    }
+
+    fn bump(&mut self, id: Index) {
+        let id = id as usize;
+        if self.versions.len() <= id {
+            self.versions.resize(id + 1, 0);
+        }
+        self.versions[id] += 1;
+    }
+}
+
+impl<C, T> Default for VersionCountStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        VersionCountStorage {
+            versions: Vec::new(),
+            storage: T::unwrap_default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T: UnprotectedStorage<C>> UnprotectedStorage<C> for VersionCountStorage<C, T> {
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.storage.get(id)
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        self.bump(id);
+        self.storage.get_mut(id)
+    }
+
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut C {
+        self.storage.get_mut_silent(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.bump(id);
+        self.storage.insert(id, comp);
+    }
+
+    unsafe fn insert_entity(&mut self, e: Entity, comp: C) {
+        self.bump(e.id());
+        self.storage.insert_entity(e, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.storage.remove(id)
+    }
+
+    fn tracks_modified(&self) -> bool {
+        true
+    }
+
+    fn grows(&self) -> usize {
+        self.storage.grows()
+    }
+}
+
+unsafe impl<C, T: DistinctStorage> DistinctStorage for VersionCountStorage<C, T> {}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = VersionCountStorage<T, S>>,
+    S: UnprotectedStorage<T>,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the version of the component of `e`, or `None` if `e` is
+    /// dead or doesn't have the component.
+    ///
+    /// The version is incremented whenever the component is inserted or
+    /// accessed mutably, so it differs from any version returned before
+    /// if the component may have changed since.
+    pub fn version(&self, e: Entity) -> Option<u64> {
+        if self.contains(e) {
+            Some(self.data.inner.version(e.id()))
+        } else {
+            None
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use shred::{Fetch, FetchMut, MetaTable, ResourceId, Resources, SystemData};
 
 use storage::{AnyStorage, MaskedStorage, Storage, TryDefault};
-use world::{Component, EntitiesRes};
+use world::{Component, DynComponents, EntitiesRes};
 
 /// A storage with read access.
 ///
@@ -119,6 +119,7 @@ where
             .or_insert_with(|| MaskedStorage::new(<T::Storage as TryDefault>::unwrap_default()));
         res.fetch_mut::<MetaTable<AnyStorage>>()
             .register(&*res.fetch::<MaskedStorage<T>>());
+        res.fetch_mut::<DynComponents>().register::<T>();
     }
 
     fn fetch(res: &'a Resources) -> Self {
@@ -210,6 +211,7 @@ where
             .or_insert_with(|| MaskedStorage::new(<T::Storage as TryDefault>::unwrap_default()));
         res.fetch_mut::<MetaTable<AnyStorage>>()
             .register(&*res.fetch::<MaskedStorage<T>>());
+        res.fetch_mut::<DynComponents>().register::<T>();
     }
 
     fn fetch(res: &'a Resources) -> Self {
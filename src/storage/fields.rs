@@ -0,0 +1,409 @@
+use std::marker::PhantomData;
+use std::ops::{BitOr, Deref, DerefMut};
+use std::vec;
+
+use fnv::FnvHashMap;
+use hibitset::{BitSet, BitSetLike};
+
+use storage::{ChangeKind, DenseVecStorage, EnsureCapacity, FlaggedStorage, MaskedStorage, Storage,
+              TrackChannels, TrackReaders, Tracked, TryDefault, UnprotectedStorage, WithCapacity};
+use world::{Component, EntitiesRes, Entity, Index};
+
+/// Computes which fields of a component differ between two values.
+///
+/// `Fields` is usually a bitflags-like value with one bit per field.
+pub trait FieldDiff {
+    /// The set of changed fields.
+    type Fields: BitOr<Output = Self::Fields> + Copy + Default + PartialEq;
+
+    /// Returns the fields which differ between `old` and `new`.
+    fn field_diff(old: &Self, new: &Self) -> Self::Fields;
+}
+
+/// Wrapper storage that works like `FlaggedStorage`, but additionally
+/// records which fields of a component changed.
+///
+/// The first time a component is accessed mutably the old value is cloned.
+/// `Storage::maintain_fields` then compares it with the current value using
+/// `FieldDiff` and accumulates the changed fields, which can be read with
+/// `Storage::field_changes` until `Storage::clear_field_changes` is called.
+///
+/// Modification events are still sent on every mutable access, exactly like
+/// `FlaggedStorage` does.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::{FieldDiff, FieldFlaggedStorage};
+///
+/// #[derive(Clone)]
+/// struct Body {
+///     pos: f32,
+///     mass: f32,
+/// }
+///
+/// impl FieldDiff for Body {
+///     type Fields = u8;
+///
+///     fn field_diff(old: &Self, new: &Self) -> u8 {
+///         (old.pos != new.pos) as u8 | ((old.mass != new.mass) as u8) << 1
+///     }
+/// }
+///
+/// impl Component for Body {
+///     type Storage = FieldFlaggedStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Body>();
+/// let e = world.create_entity().with(Body { pos: 0.0, mass: 1.0 }).build();
+///
+/// let mut bodies = world.write_storage::<Body>();
+/// bodies.get_mut(e).unwrap().pos = 3.0;
+/// bodies.maintain_fields();
+///
+/// assert_eq!(bodies.field_changes(e), 0b01);
+/// ```
+pub struct FieldFlaggedStorage<C: FieldDiff, T = DenseVecStorage<C>> {
+    flagged: FlaggedStorage<C, T>,
+    old: FnvHashMap<Index, C>,
+    fields: FnvHashMap<Index, C::Fields>,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> Default for FieldFlaggedStorage<C, T>
+where
+    C: FieldDiff,
+    T: TryDefault,
+{
+    fn default() -> Self {
+        FieldFlaggedStorage {
+            flagged: FlaggedStorage::default(),
+            old: FnvHashMap::default(),
+            fields: FnvHashMap::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T> EnsureCapacity for FieldFlaggedStorage<C, T>
+where
+    C: FieldDiff,
+    T: EnsureCapacity,
+{
+    fn ensure_capacity_for(&mut self, id: Index) {
+        self.flagged.ensure_capacity_for(id);
+    }
+}
+
+impl<C, T> WithCapacity for FieldFlaggedStorage<C, T>
+where
+    C: FieldDiff,
+    T: WithCapacity,
+{
+    fn with_capacity(capacity: usize) -> Self {
+        FieldFlaggedStorage {
+            flagged: FlaggedStorage::with_capacity(capacity),
+            old: FnvHashMap::default(),
+            fields: FnvHashMap::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T> FieldFlaggedStorage<C, T>
+where
+    C: Component + FieldDiff,
+    T: UnprotectedStorage<C>,
+{
+    /// Compares the old values of mutably accessed components with the
+    /// current ones and accumulates the changed fields.
+    pub fn maintain(&mut self) {
+        for (id, old) in self.old.drain() {
+            let diff = C::field_diff(&old, unsafe { self.flagged.get(id) });
+            let fields = self.fields.entry(id).or_default();
+            *fields = *fields | diff;
+        }
+    }
+
+    /// Returns the fields which changed for an index since the last
+    /// call to `clear_changes`.
+    pub fn field_changes(&self, id: Index) -> C::Fields {
+        self.fields.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Forgets all recorded field changes.
+    pub fn clear_changes(&mut self) {
+        self.fields.clear();
+    }
+
+    /// Returns the value an index had before it was first accessed
+    /// mutably, if that hasn't been processed by `maintain` yet.
+    pub fn old(&self, id: Index) -> Option<&C> {
+        self.old.get(&id)
+    }
+}
+
+impl<C, T> UnprotectedStorage<C> for FieldFlaggedStorage<C, T>
+where
+    C: Component + FieldDiff + Clone,
+    T: UnprotectedStorage<C>,
+{
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.old.clear();
+        self.fields.clear();
+        self.flagged.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.flagged.get(id)
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        if !self.old.contains_key(&id) {
+            let old = self.flagged.get(id).clone();
+            self.old.insert(id, old);
+        }
+
+        self.flagged.get_mut(id)
+    }
+
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut C {
+        self.flagged.get_mut_silent(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.flagged.insert(id, comp);
+    }
+
+    unsafe fn insert_entity(&mut self, e: Entity, comp: C) {
+        self.flagged.insert_entity(e, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.old.remove(&id);
+        self.fields.remove(&id);
+        self.flagged.remove(id)
+    }
+
+    fn tracks_modified(&self) -> bool {
+        true
+    }
+
+    fn grows(&self) -> usize {
+        self.flagged.grows()
+    }
+}
+
+impl<C: FieldDiff, T> Tracked for FieldFlaggedStorage<C, T> {
+    fn channels(&self) -> &TrackChannels {
+        self.flagged.channels()
+    }
+
+    fn channels_mut(&mut self) -> &mut TrackChannels {
+        self.flagged.channels_mut()
+    }
+
+    fn reset(&mut self) {
+        self.old.clear();
+        self.fields.clear();
+    }
+
+    fn compact(&mut self, mask: &BitSet) -> usize {
+        let before = self.old.capacity() + self.fields.capacity();
+        self.old.retain(|id, _| mask.contains(*id));
+        self.fields.retain(|id, _| mask.contains(*id));
+        self.old.shrink_to_fit();
+        self.fields.shrink_to_fit();
+
+        before - (self.old.capacity() + self.fields.capacity())
+    }
+}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = FieldFlaggedStorage<T, S>> + FieldDiff,
+    S: UnprotectedStorage<T>,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the fields of the entity's component which changed since
+    /// the last call to `clear_field_changes`.
+    ///
+    /// Only includes changes which have been processed by `maintain_fields`.
+    pub fn field_changes(&self, e: Entity) -> T::Fields {
+        if self.contains(e) {
+            self.data.inner.field_changes(e.id())
+        } else {
+            Default::default()
+        }
+    }
+
+    /// Reads all pending events and returns a `ChangeRecord` for each of
+    /// them, containing both the old and the current value of the component.
+    ///
+    /// Records are returned by kind (insertions, then modifications, then
+    /// removals), each in the order the events were sent. An index which
+    /// was changed several times gets a record for every event. Use `rev`
+    /// to process them from the back.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # use specs::storage::{ChangeKind, FieldDiff, FieldFlaggedStorage};
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Pos(u32);
+    ///
+    /// impl FieldDiff for Pos {
+    ///     type Fields = bool;
+    ///
+    ///     fn field_diff(old: &Self, new: &Self) -> bool {
+    ///         old != new
+    ///     }
+    /// }
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = FieldFlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let e = world.create_entity().with(Pos(0)).build();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// let mut readers = pos.track_all();
+    /// pos.get_mut(e).unwrap().0 = 5;
+    ///
+    /// let record = pos.change_records(&mut readers).next().unwrap();
+    /// assert_eq!(record.entity, e);
+    /// assert_eq!(record.kind, ChangeKind::Modified);
+    /// assert_eq!(record.old, Some(&Pos(0)));
+    /// assert_eq!(record.current, Some(&Pos(5)));
+    /// ```
+    pub fn change_records<'a>(&'a self, readers: &mut TrackReaders) -> ChangeRecords<'a, T, S> {
+        let channels = self.data.inner.channels();
+        let changes: Vec<_> = channels
+            .insert
+            .read(&mut readers.insert)
+            .map(|flag| (**flag, ChangeKind::Inserted))
+            .chain(
+                channels
+                    .modify
+                    .read(&mut readers.modify)
+                    .map(|flag| (**flag, ChangeKind::Modified)),
+            )
+            .chain(
+                channels
+                    .remove
+                    .read(&mut readers.remove)
+                    .map(|flag| (**flag, ChangeKind::Removed)),
+            )
+            .collect();
+
+        ChangeRecords {
+            changes: changes.into_iter(),
+            mask: &self.data.mask,
+            storage: &self.data.inner,
+            entities: &self.entities,
+        }
+    }
+}
+
+/// A change to a component of a `FieldFlaggedStorage`, returned by
+/// `Storage::change_records`.
+#[derive(Debug)]
+pub struct ChangeRecord<'a, T: 'a> {
+    /// The entity the changed component belongs to.
+    ///
+    /// For removals, the entity might not be alive anymore.
+    pub entity: Entity,
+    /// The kind of change.
+    pub kind: ChangeKind,
+    /// The value of the component before it has been modified.
+    ///
+    /// Only available for `Modified` records, until `Storage::maintain_fields`
+    /// is called.
+    pub old: Option<&'a T>,
+    /// The current value of the component, if it still exists.
+    ///
+    /// Always `None` for `Removed` records.
+    pub current: Option<&'a T>,
+}
+
+/// Iterator over `ChangeRecord`s, returned by `Storage::change_records`.
+pub struct ChangeRecords<'a, T: 'a + FieldDiff, S: 'a> {
+    changes: vec::IntoIter<(Index, ChangeKind)>,
+    mask: &'a BitSet,
+    storage: &'a FieldFlaggedStorage<T, S>,
+    entities: &'a EntitiesRes,
+}
+
+impl<'a, T, S> Iterator for ChangeRecords<'a, T, S>
+where
+    T: Component + FieldDiff,
+    S: UnprotectedStorage<T>,
+{
+    type Item = ChangeRecord<'a, T>;
+
+    fn next(&mut self) -> Option<ChangeRecord<'a, T>> {
+        self.changes.next().map(|(id, kind)| self.record(id, kind))
+    }
+}
+
+impl<'a, T, S> DoubleEndedIterator for ChangeRecords<'a, T, S>
+where
+    T: Component + FieldDiff,
+    S: UnprotectedStorage<T>,
+{
+    fn next_back(&mut self) -> Option<ChangeRecord<'a, T>> {
+        self.changes.next_back().map(|(id, kind)| self.record(id, kind))
+    }
+}
+
+impl<'a, T, S> ChangeRecords<'a, T, S>
+where
+    T: Component + FieldDiff,
+    S: UnprotectedStorage<T>,
+{
+    fn record(&self, id: Index, kind: ChangeKind) -> ChangeRecord<'a, T> {
+        let current = if kind != ChangeKind::Removed && self.mask.contains(id) {
+            Some(unsafe { self.storage.flagged.get(id) })
+        } else {
+            None
+        };
+        let old = if kind == ChangeKind::Modified {
+            self.storage.old(id)
+        } else {
+            None
+        };
+
+        ChangeRecord {
+            entity: self.entities.entity(id),
+            kind,
+            old,
+            current,
+        }
+    }
+}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = FieldFlaggedStorage<T, S>> + FieldDiff,
+    S: UnprotectedStorage<T>,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Diffs all components which have been accessed mutably since the last
+    /// call against their old values, recording the changed fields.
+    pub fn maintain_fields(&mut self) {
+        self.data.inner.maintain();
+    }
+
+    /// Forgets all recorded field changes.
+    pub fn clear_field_changes(&mut self) {
+        self.data.inner.clear_changes();
+    }
+}
@@ -1,10 +1,15 @@
 use std::marker::PhantomData;
 
+#[cfg(feature = "parallel")]
+use hibitset::AtomicBitSet;
 use hibitset::BitSetLike;
 
+#[cfg(feature = "parallel")]
+use storage::ParMutStorage;
 use storage::TryDefault;
-use storage::{DenseVecStorage, TrackChannels, Tracked, UnprotectedStorage};
-use world::{Component, Index};
+use storage::{AppendStorage, DenseStorage, DenseVecStorage, EnsureCapacity, SliceAccess,
+              TrackChannels, Tracked, UnprotectedStorage, WithCapacity};
+use world::{Component, Entity, Index};
 
 /// Wrapper storage that tracks modifications, insertions, and removals of components
 /// through an `EventChannel`.
@@ -129,6 +134,8 @@ use world::{Component, Index};
 pub struct FlaggedStorage<C, T = DenseVecStorage<C>> {
     trackers: TrackChannels,
     storage: T,
+    #[cfg(feature = "parallel")]
+    par_modified: AtomicBitSet,
     phantom: PhantomData<C>,
 }
 
@@ -140,6 +147,8 @@ where
         FlaggedStorage {
             trackers: TrackChannels::default(),
             storage: T::unwrap_default(),
+            #[cfg(feature = "parallel")]
+            par_modified: AtomicBitSet::new(),
             phantom: PhantomData,
         }
     }
@@ -150,6 +159,9 @@ impl<C: Component, T: UnprotectedStorage<C>> UnprotectedStorage<C> for FlaggedSt
     where
         B: BitSetLike,
     {
+        for id in (&has).iter() {
+            self.trackers.flag_removed(id);
+        }
         self.storage.clean(has);
     }
 
@@ -159,19 +171,105 @@ impl<C: Component, T: UnprotectedStorage<C>> UnprotectedStorage<C> for FlaggedSt
 
     unsafe fn get_mut(&mut self, id: Index) -> &mut C {
         // calling `.iter()` on an unconstrained mutable storage will flag everything
-        self.trackers.modify.single_write(id.into());
+        self.trackers.flag_modified(id);
         self.storage.get_mut(id)
     }
 
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut C {
+        self.storage.get_mut_silent(id)
+    }
+
     unsafe fn insert(&mut self, id: Index, comp: C) {
-        self.trackers.insert.single_write(id.into());
+        self.trackers.flag_inserted(id);
         self.storage.insert(id, comp);
     }
 
+    unsafe fn insert_entity(&mut self, e: Entity, comp: C) {
+        self.trackers.flag_inserted(e.id());
+        self.storage.insert_entity(e, comp);
+    }
+
     unsafe fn remove(&mut self, id: Index) -> C {
-        self.trackers.remove.single_write(id.into());
+        self.trackers.flag_removed(id);
         self.storage.remove(id)
     }
+
+    fn tracks_modified(&self) -> bool {
+        true
+    }
+
+    fn grows(&self) -> usize {
+        self.storage.grows()
+    }
+}
+
+#[cfg(feature = "parallel")]
+unsafe impl<C, T> ParMutStorage<C> for FlaggedStorage<C, T>
+where
+    C: Component,
+    T: ParMutStorage<C>,
+{
+    unsafe fn get_mut_par(&mut self, id: Index) -> &mut C {
+        self.par_modified.add_atomic(id);
+        self.storage.get_mut_par(id)
+    }
+
+    fn finish_par(&mut self) {
+        for id in (&self.par_modified).iter() {
+            self.trackers.flag_modified(id);
+        }
+        self.par_modified.clear();
+        self.storage.finish_par();
+    }
+}
+
+impl<C: Component, T: AppendStorage<C>> AppendStorage<C> for FlaggedStorage<C, T> {
+    unsafe fn append(&mut self, id: Index, comp: C) {
+        self.trackers.flag_inserted(id);
+        self.storage.append(id, comp);
+    }
+}
+
+impl<C, T: DenseStorage> DenseStorage for FlaggedStorage<C, T> {}
+
+impl<C, T: EnsureCapacity> EnsureCapacity for FlaggedStorage<C, T> {
+    fn ensure_capacity_for(&mut self, id: Index) {
+        self.storage.ensure_capacity_for(id);
+    }
+
+    fn reserve_batch(&mut self, max_id: Index, count: usize) {
+        self.storage.reserve_batch(max_id, count);
+    }
+}
+
+impl<C, T: WithCapacity> WithCapacity for FlaggedStorage<C, T> {
+    fn with_capacity(capacity: usize) -> Self {
+        FlaggedStorage {
+            trackers: TrackChannels::default(),
+            storage: T::with_capacity(capacity),
+            #[cfg(feature = "parallel")]
+            par_modified: AtomicBitSet::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T: SliceAccess<C>> SliceAccess<C> for FlaggedStorage<C, T> {
+    fn as_slice(&self) -> &[C] {
+        self.storage.as_slice()
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [C] {
+        if !self.trackers.is_suppressed() {
+            let flags = self.storage.slice_ids().iter().map(|&id| id.into());
+            self.trackers.modify.iter_write(flags);
+        }
+        self.storage.as_mut_slice()
+    }
+
+    fn slice_ids(&self) -> &[Index] {
+        self.storage.slice_ids()
+    }
 }
 
 impl<C, T> Tracked for FlaggedStorage<C, T> {
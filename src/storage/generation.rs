@@ -0,0 +1,192 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use hibitset::BitSetLike;
+
+use storage::{DenseVecStorage, DistinctStorage, MaskedStorage, Storage, TryDefault,
+              UnprotectedStorage};
+use world::{Component, Entity, Generation, Index};
+
+/// Wrapper storage which stores the generation of the entity along with
+/// each component, so stale `Entity` handles can be detected without
+/// asking `EntitiesRes`.
+///
+/// `Storage::get_checked` and `Storage::get_mut_checked` only return the
+/// component if the generation of the handle matches the one it has been
+/// inserted for. This keeps handles from returning the component of
+/// another entity reusing the same index, even for storages which aren't
+/// kept in sync with the entities of the world, like copies made by tools.
+///
+/// The generation is recorded by `Storage::insert` and the other methods
+/// inserting a component for an `Entity`; components inserted by index
+/// only, e.g. when `World::compact` moves them, have no generation and are
+/// never returned by the checked accessors.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::GenerationStorage;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Name(&'static str);
+///
+/// impl Component for Name {
+///     type Storage = GenerationStorage<Self, VecStorage<Self>>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Name>();
+/// let e = world.create_entity().with(Name("a")).build();
+///
+/// let names = world.read_storage::<Name>();
+/// assert_eq!(names.get_checked(e), Some(&Name("a")));
+/// ```
+pub struct GenerationStorage<C, T = DenseVecStorage<C>> {
+    generations: Vec<Generation>,
+    storage: T,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> GenerationStorage<C, T> {
+    /// Returns the generation of the entity the component at `id` has
+    /// been inserted for, if it's known.
+    ///
+    /// The result is meaningless if there is no component at `id`.
+    pub fn generation(&self, id: Index) -> Option<Generation> {
+        match self.generations.get(id as usize) {
+            Some(&gen) if gen.is_alive() => Some(gen),
+            _ => None,
+        }
+    }
+
+    fn set_generation(&mut self, id: Index, gen: Generation) {
+        let id = id as usize;
+        if self.generations.len() <= id {
+            self.generations.resize(id + 1, Generation(0));
+        }
+        self.generations[id] = gen;
+    }
+}
+
+impl<C, T> Default for GenerationStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        GenerationStorage {
+            generations: Vec::new(),
+            storage: T::unwrap_default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T: UnprotectedStorage<C>> UnprotectedStorage<C> for GenerationStorage<C, T> {
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.generations.clear();
+        self.storage.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.storage.get(id)
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        self.storage.get_mut(id)
+    }
+
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut C {
+        self.storage.get_mut_silent(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.set_generation(id, Generation(0));
+        self.storage.insert(id, comp);
+    }
+
+    unsafe fn insert_entity(&mut self, e: Entity, comp: C) {
+        self.set_generation(e.id(), e.gen());
+        self.storage.insert_entity(e, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.set_generation(id, Generation(0));
+        self.storage.remove(id)
+    }
+
+    fn tracks_modified(&self) -> bool {
+        self.storage.tracks_modified()
+    }
+
+    fn grows(&self) -> usize {
+        self.storage.grows()
+    }
+}
+
+unsafe impl<C, T: DistinctStorage> DistinctStorage for GenerationStorage<C, T> {}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = GenerationStorage<T, S>>,
+    S: UnprotectedStorage<T>,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the component of `e` if it has been inserted for exactly
+    /// this entity, comparing the generation of the handle with the one
+    /// stored along with the component instead of asking `EntitiesRes`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # use specs::storage::GenerationStorage;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Name(&'static str);
+    /// # impl Component for Name { type Storage = GenerationStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Name>();
+    /// let old = world.create_entity().with(Name("old")).build();
+    /// world.delete_entity(old).unwrap();
+    /// let new = world.create_entity().with(Name("new")).build();
+    ///
+    /// assert_eq!(old.id(), new.id());
+    /// let names = world.read_storage::<Name>();
+    /// assert_eq!(names.get_checked(old), None);
+    /// assert_eq!(names.get_checked(new), Some(&Name("new")));
+    /// ```
+    pub fn get_checked<'a>(&'a self, e: Entity) -> Option<&'a T>
+    where
+        S: 'a,
+    {
+        if self.data.mask.contains(e.id()) && self.data.inner.generation(e.id()) == Some(e.gen())
+        {
+            Some(unsafe { self.data.inner.get(e.id()) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = GenerationStorage<T, S>>,
+    S: UnprotectedStorage<T>,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Like `get_checked`, but returns the component mutably.
+    pub fn get_mut_checked<'a>(&'a mut self, e: Entity) -> Option<&'a mut T>
+    where
+        S: 'a,
+    {
+        if self.data.mask.contains(e.id()) && self.data.inner.generation(e.id()) == Some(e.gen())
+        {
+            Some(unsafe { self.data.inner.get_mut(e.id()) })
+        } else {
+            None
+        }
+    }
+}
@@ -1,36 +1,65 @@
 //! Component storage types, implementations for component joins, etc.
 
+#[cfg(feature = "parallel")]
+pub use self::batch::ParInsert;
+pub use self::counted::VersionCountStorage;
 pub use self::data::{ReadStorage, WriteStorage};
+pub use self::fields::{ChangeRecord, ChangeRecords, FieldDiff, FieldFlaggedStorage};
 pub use self::flagged::FlaggedStorage;
+pub use self::generation::GenerationStorage;
 pub use self::generic::{GenericReadStorage, GenericWriteStorage};
+#[cfg(feature = "parallel")]
+pub use self::par_mut::{ParIterMut, ParMutStorage};
 pub use self::restrict::{ImmutableParallelRestriction, MutableParallelRestriction,
-                         RestrictedStorage, SequentialRestriction};
+                         RestrictedStorage, SequentialRestriction, Token, TokenMask,
+                         TokenStorage};
 #[cfg(feature = "rudy")]
 pub use self::storages::RudyStorage;
-pub use self::storages::{BTreeStorage, DenseVecStorage, HashMapStorage, NullStorage, VecStorage};
-pub use self::track::{InsertedFlag, ModifiedFlag, RemovedFlag, TrackChannels, Tracked};
+pub use self::snapshot::StorageSnapshot;
+pub use self::stats::StorageStats;
+pub use self::storages::{ArenaStorage, BTreeStorage, DenseVecStorage, HashMapStorage,
+                         InternedStorage, NullStorage, VecStorage};
+pub use self::throttle::ChangeThrottle;
+pub use self::track::{ChangeKind, ChangedFlags, ChangedJoin, ChangedMut, InsertedFlag,
+                      ModifiedFlag, NetChanges, RemovedFlag, TrackChannels, TrackReaders,
+                      Tracked, TrackedMut};
+pub use self::versioned::{Versioned, VersionedStorage};
+pub use self::view::{View, ViewOf};
 
 use std;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut, Not};
 
-use hibitset::{BitSet, BitSetLike, BitSetNot};
+use hibitset::{BitSet, BitSetAnd, BitSetLike, BitSetNot, BitSetXor};
 use shred::{CastFrom, Fetch};
 
 use self::drain::Drain;
+use self::stats::{Access, AccessCounters};
 use error::{Error, WrongGeneration};
-use join::{Join, ParJoin};
+use join::{ChunkedJoin, Join, ParJoin};
 use world::{Component, EntitiesRes, Entity, Generation, Index};
 
+#[cfg(feature = "parallel")]
+mod batch;
+mod counted;
 mod data;
 mod drain;
+mod fields;
 mod flagged;
+mod generation;
 mod generic;
+#[cfg(feature = "parallel")]
+mod par_mut;
 mod restrict;
+mod snapshot;
+mod stats;
 mod storages;
 #[cfg(test)]
 mod tests;
+mod throttle;
 mod track;
+mod versioned;
+mod view;
 
 /// An inverted storage type, only useful to iterate entities
 /// that do not have a particular component type.
@@ -58,6 +87,12 @@ unsafe impl<'a> ParJoin for AntiStorage<'a> {}
 pub trait AnyStorage {
     /// Drop components of given entities.
     fn drop(&mut self, entities: &[Entity]);
+
+    /// Moves the components of the given `(from, to)` index pairs.
+    ///
+    /// Used by `World::compact`; the target indices must not have a
+    /// component.
+    fn move_components(&mut self, moves: &[(Index, Index)]);
 }
 
 impl<T> CastFrom<T> for AnyStorage
@@ -82,6 +117,18 @@ where
             MaskedStorage::drop(self, entity.id());
         }
     }
+
+    fn move_components(&mut self, moves: &[(Index, Index)]) {
+        for &(from, to) in moves {
+            if self.mask.remove(from) {
+                unsafe {
+                    let component = self.inner.remove(from);
+                    self.inner.insert(to, component);
+                }
+                self.mask.add(to);
+            }
+        }
+    }
 }
 
 /// This is a marker trait which requires you to uphold the following guarantee:
@@ -106,6 +153,75 @@ where
 /// elements), thus allows `join_par()`.
 pub unsafe trait DistinctStorage {}
 
+/// Marker trait for storages which keep their components packed in memory
+/// (like `VecStorage` and `DenseVecStorage`).
+///
+/// Only joins over such storages can be chunked with `ChunkedJoin`.
+pub trait DenseStorage {}
+
+/// Storages which can preallocate memory for a number of components.
+///
+/// Used by `Registration::with_capacity`.
+pub trait WithCapacity {
+    /// Creates an empty storage with room for at least `capacity`
+    /// components (or, for storages indexed by entity id, for the
+    /// components of the first `capacity` indices).
+    fn with_capacity(capacity: usize) -> Self;
+}
+
+/// Storages which can allocate memory for components up to some index
+/// ahead of time.
+///
+/// Used by `Storage::ensure_capacity_for`.
+pub trait EnsureCapacity {
+    /// Grows the storage so that inserting components with indices up to
+    /// `id` doesn't need to allocate.
+    fn ensure_capacity_for(&mut self, id: Index);
+
+    /// Grows the storage so that inserting `count` components with indices
+    /// up to `max_id` doesn't need to allocate.
+    ///
+    /// Used by `Storage::insert_batch`; defaults to `ensure_capacity_for`,
+    /// storages keeping components separate from their indices (like
+    /// `DenseVecStorage`) only need room for `count` more of them.
+    fn reserve_batch(&mut self, max_id: Index, count: usize) {
+        let _ = count;
+        self.ensure_capacity_for(max_id);
+    }
+}
+
+/// Storages with a faster way of inserting components in ascending order
+/// of their indices, e.g. when loading a world.
+///
+/// Used by `Storage::append`.
+pub trait AppendStorage<T>: UnprotectedStorage<T> {
+    /// Inserts a component for an index greater than all indices which
+    /// currently have a component.
+    ///
+    /// Storages may check the order with a debug assertion.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `UnprotectedStorage::insert`.
+    unsafe fn append(&mut self, id: Index, value: T);
+}
+
+/// Storages which keep all of their components in one contiguous slice,
+/// without gaps.
+///
+/// The order of the slice is internal to the storage and changes when
+/// components are inserted or removed.
+pub trait SliceAccess<T> {
+    /// Returns all components of the storage.
+    fn as_slice(&self) -> &[T];
+
+    /// Returns all components of the storage mutably.
+    fn as_mut_slice(&mut self) -> &mut [T];
+
+    /// Returns the index each element of the slice belongs to.
+    fn slice_ids(&self) -> &[Index];
+}
+
 /// The status of an `insert()`ion into a storage.
 /// If the insertion was successful then the Ok value will
 /// contain the component that was replaced (if any).
@@ -118,6 +234,7 @@ pub type InsertResult<T> = Result<Option<T>, Error>;
 pub struct MaskedStorage<T: Component> {
     mask: BitSet,
     inner: T::Storage,
+    stats: AccessCounters,
 }
 
 impl<T: Component> MaskedStorage<T> {
@@ -127,6 +244,16 @@ impl<T: Component> MaskedStorage<T> {
         MaskedStorage {
             mask: BitSet::new(),
             inner,
+            stats: AccessCounters::default(),
+        }
+    }
+
+    /// Returns the element of a given index, if there is one.
+    pub(crate) fn get(&self, id: Index) -> Option<&T> {
+        if self.mask.contains(id) {
+            Some(unsafe { self.inner.get(id) })
+        } else {
+            None
         }
     }
 
@@ -135,26 +262,40 @@ impl<T: Component> MaskedStorage<T> {
     }
 
     /// Clear the contents of this storage.
+    ///
+    /// Calls `Component::on_remove` for every component first.
     pub fn clear(&mut self) {
         unsafe {
+            for id in (&self.mask).iter() {
+                self.inner.get_mut_silent(id).on_remove();
+            }
             self.inner.clean(&self.mask);
         }
         self.mask.clear();
     }
 
     /// Remove an element by a given index.
+    ///
+    /// Calls `Component::on_remove` before removing the element.
     pub fn remove(&mut self, id: Index) -> Option<T> {
         if self.mask.remove(id) {
-            Some(unsafe { self.inner.remove(id) })
+            unsafe {
+                self.inner.get_mut_silent(id).on_remove();
+
+                Some(self.inner.remove(id))
+            }
         } else {
             None
         }
     }
 
     /// Drop an element by a given index.
+    ///
+    /// Calls `Component::on_remove` before dropping the element.
     pub fn drop(&mut self, id: Index) {
         if self.mask.remove(id) {
             unsafe {
+                self.inner.get_mut_silent(id).on_remove();
                 self.inner.drop(id);
             }
         }
@@ -199,6 +340,7 @@ where
 
     /// Tries to read the data associated with an `Entity`.
     pub fn get(&self, e: Entity) -> Option<&T> {
+        self.data.stats.record(Access::Get);
         if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
             Some(unsafe { self.data.inner.get(e.id()) })
         } else {
@@ -211,11 +353,182 @@ where
         self.data.mask.contains(e.id()) && self.entities.is_alive(e)
     }
 
+    /// Like `get`, but also returns how the component changed according to
+    /// `changes` (usually the result of `net_changes` for this frame), or
+    /// `None` if it didn't change.
+    ///
+    /// Returns `None` (instead of the pair) only if the entity is dead or
+    /// doesn't have the component, so this replaces looking up the
+    /// component and the change separately in reactive systems.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// use specs::storage::ChangeKind;
+    ///
+    /// struct Health(u32);
+    /// impl Component for Health {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// let mut readers = world.write_storage::<Health>().track_all();
+    /// let e = world.create_entity().with(Health(10)).build();
+    ///
+    /// let health = world.read_storage::<Health>();
+    /// let changes = health.net_changes(&mut readers);
+    /// let (_, change) = health.get_with_change(e, &changes).unwrap();
+    /// assert_eq!(change, Some(ChangeKind::Inserted));
+    /// ```
+    pub fn get_with_change(
+        &self,
+        e: Entity,
+        changes: &NetChanges,
+    ) -> Option<(&T, Option<ChangeKind>)> {
+        self.get(e).map(|c| (c, changes.kind(e.id())))
+    }
+
+    /// Returns for each of the given entities whether it is alive and has a
+    /// component in this storage, just like `contains`.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos; impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// let a = world.create_entity().with(Pos).build();
+    /// let b = world.create_entity().build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// assert_eq!(pos.contains_entities(&[a, b]), vec![true, false]);
+    /// ```
+    pub fn contains_entities(&self, entities: &[Entity]) -> Vec<bool> {
+        entities.iter().map(|&e| self.contains(e)).collect()
+    }
+
+    /// Clears `out` and fills it with a clone of the component of each of the
+    /// given entities, or `None` if it doesn't have one, just like `get`.
+    ///
+    /// The capacity of `out` is reused, so gathering into the same buffer
+    /// every frame doesn't allocate.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Clone, Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// let a = world.create_entity().with(Pos(3)).build();
+    /// let b = world.create_entity().build();
+    ///
+    /// let mut out = Vec::new();
+    /// world.read_storage::<Pos>().gather(&[a, b], &mut out);
+    /// assert_eq!(out, vec![Some(Pos(3)), None]);
+    /// ```
+    pub fn gather(&self, entities: &[Entity], out: &mut Vec<Option<T>>)
+    where
+        T: Clone,
+    {
+        out.clear();
+        out.extend(entities.iter().map(|&e| self.get(e).cloned()));
+    }
+
+    /// Returns statistics about the usage of this storage.
+    ///
+    /// The number of `get`, `get_mut`, `insert` and `remove` calls, as
+    /// well as the number of inserts which grew the storage, is only
+    /// counted with the `stats` feature, which adds a little overhead to
+    /// each of them.
+    pub fn stats(&self) -> StorageStats {
+        StorageStats::new(&self.data.stats, &self.data.mask, self.data.inner.grows())
+    }
+
     /// Returns a reference to the bitset of this storage which allows filtering
     /// by the component type without actually getting the component.
     pub fn mask(&self) -> &BitSet {
         &self.data.mask
     }
+
+    /// Returns all components as a slice, for storages that keep them
+    /// contiguous (like `DenseVecStorage`).
+    ///
+    /// The order of the values is internal to the storage; use
+    /// `entities_slice` to find out which entity a value belongs to.
+    ///
+    /// Only storages implementing `SliceAccess` have this:
+    ///
+    /// ```compile_fail
+    /// # use specs::prelude::*;
+    /// struct Pos(f32);
+    /// impl Component for Pos {
+    ///     type Storage = HashMapStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.read_storage::<Pos>().values();
+    /// ```
+    pub fn values(&self) -> &[T]
+    where
+        T::Storage: SliceAccess<T>,
+    {
+        self.data.inner.as_slice()
+    }
+
+    /// Returns the indices of the entities the elements of `values` and
+    /// `values_mut` belong to, in the same order.
+    pub fn entities_slice(&self) -> &[Index]
+    where
+        T::Storage: SliceAccess<T>,
+    {
+        self.data.inner.slice_ids()
+    }
+
+    /// Returns `true` if both storages have equal components
+    /// for exactly the same indices.
+    ///
+    /// This is `O(n)` over the union of both masks, but returns
+    /// early if the masks differ.
+    pub fn eq_contents<D2>(&self, other: &Storage<T, D2>) -> bool
+    where
+        T: PartialEq,
+        D2: Deref<Target = MaskedStorage<T>>,
+    {
+        if BitSetXor(&self.data.mask, &other.data.mask)
+            .iter()
+            .next()
+            .is_some()
+        {
+            return false;
+        }
+
+        (&self.data.mask)
+            .iter()
+            .all(|id| unsafe { self.data.inner.get(id) == other.data.inner.get(id) })
+    }
+
+    /// Returns the indices for which the two storages differ, meaning only
+    /// one of them has a component for it or the components are not equal.
+    ///
+    /// This is `O(n)` over the union of both masks.
+    pub fn diff<D2>(&self, other: &Storage<T, D2>) -> BitSet
+    where
+        T: PartialEq,
+        D2: Deref<Target = MaskedStorage<T>>,
+    {
+        let mut diff: BitSet = BitSetXor(&self.data.mask, &other.data.mask)
+            .iter()
+            .collect();
+        diff.extend(
+            BitSetAnd(&self.data.mask, &other.data.mask)
+                .iter()
+                .filter(|&id| unsafe { self.data.inner.get(id) != other.data.inner.get(id) }),
+        );
+
+        diff
+    }
 }
 
 /// An entry to a storage which has a component associated to the entity.
@@ -279,7 +592,7 @@ where
         let id = self.entity.id();
         self.storage.data.mask.add(id);
         unsafe {
-            self.storage.data.inner.insert(id, component);
+            self.storage.data.inner.insert_entity(self.entity, component);
             self.storage.data.inner.get_mut(id)
         }
     }
@@ -332,6 +645,7 @@ where
 
     /// Tries to mutate the data associated with an `Entity`.
     pub fn get_mut(&mut self, e: Entity) -> Option<&mut T> {
+        self.data.stats.record(Access::GetMut);
         if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
             Some(unsafe { self.data.inner.get_mut(e.id()) })
         } else {
@@ -339,6 +653,148 @@ where
         }
     }
 
+    /// Applies `f` to the component associated with an `Entity`.
+    ///
+    /// Returns `true` if the mutation has been recorded as a modification
+    /// (which is the case for tracked storages like `FlaggedStorage`),
+    /// and `false` for untracked storages or if the entity is dead or doesn't
+    /// have the component, in which case `f` is not called.
+    pub fn apply<F>(&mut self, e: Entity, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        let tracked = self.data.inner.tracks_modified();
+        match self.get_mut(e) {
+            Some(component) => {
+                f(component);
+
+                tracked
+            }
+            None => false,
+        }
+    }
+
+    /// Returns mutable references to the components of two different
+    /// entities at once, e.g. to resolve a collision between them.
+    ///
+    /// Returns `None` if `a` and `b` are the same entity, or if either of
+    /// them is dead or doesn't have the component. Both components count as
+    /// modified for tracked storages like `FlaggedStorage`, just like with
+    /// `get_mut`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Vel(f32);
+    /// impl Component for Vel {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Vel>();
+    /// let a = world.create_entity().with(Vel(1.0)).build();
+    /// let b = world.create_entity().with(Vel(-2.0)).build();
+    ///
+    /// let mut vel = world.write_storage::<Vel>();
+    /// {
+    ///     let (va, vb) = vel.get_pair_mut(a, b).unwrap();
+    ///     std::mem::swap(&mut va.0, &mut vb.0);
+    /// }
+    /// assert_eq!(vel.get(a).unwrap().0, -2.0);
+    /// assert!(vel.get_pair_mut(a, a).is_none());
+    /// ```
+    pub fn get_pair_mut(&mut self, a: Entity, b: Entity) -> Option<(&mut T, &mut T)> {
+        self.data.stats.record(Access::GetMut);
+        if a.id() == b.id()
+            || !self.data.mask.contains(a.id())
+            || !self.data.mask.contains(b.id())
+            || !self.entities.is_alive(a)
+            || !self.entities.is_alive(b)
+        {
+            return None;
+        }
+
+        // The indices are distinct, so the references don't alias; like
+        // for joins over `&mut Storage`, getting another component doesn't
+        // move the ones handed out before.
+        unsafe {
+            let first = self.data.inner.get_mut(a.id()) as *mut T;
+            let second = self.data.inner.get_mut(b.id());
+
+            Some((&mut *first, second))
+        }
+    }
+
+    /// Calls `f` with every component of this storage and the entity it
+    /// belongs to, in ascending order of entity ids.
+    ///
+    /// Every component counts as modified for tracked storages like
+    /// `FlaggedStorage`, just like with `get_mut`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Health(u32);
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// let e = world.create_entity().with(Health(250)).build();
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// health.for_each_mut(|_, health| health.0 = health.0.min(100));
+    /// assert_eq!(health.get(e).unwrap().0, 100);
+    /// ```
+    pub fn for_each_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Entity, &mut T),
+    {
+        let data = &mut *self.data;
+        for id in (&data.mask).iter() {
+            f(self.entities.entity(id), unsafe { data.inner.get_mut(id) });
+        }
+    }
+
+    /// Returns a parallel iterator over all components of this storage,
+    /// mutably, along with their indices; e.g. to integrate the positions
+    /// of all entities.
+    ///
+    /// The mask is split into disjoint ranges of indices, so every thread
+    /// mutates different components. This works for all storages which
+    /// implement `ParMutStorage`, including `FlaggedStorage`, which writes
+    /// a modification event for every component once the iteration is done.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Pos(f32);
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let e = world.create_entity().with(Pos(1.0)).build();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// pos.par_iter_mut().for_each(|(_, pos)| pos.0 += 0.5);
+    /// assert_eq!(pos.get(e).unwrap().0, 1.5);
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn par_iter_mut(&mut self) -> ParIterMut<T>
+    where
+        T::Storage: ParMutStorage<T>,
+    {
+        ParIterMut::new(&mut self.data)
+    }
+
     /// Returns an entry to the component associated to the entity.
     ///
     /// Behaves somewhat similarly to `std::collections::HashMap`'s entry api.
@@ -398,6 +854,7 @@ where
     /// Inserts new data for a given `Entity`.
     /// Returns the result of the operation as a `InsertResult<T>`
     pub fn insert(&mut self, e: Entity, mut v: T) -> InsertResult<T> {
+        self.data.stats.record(Access::Insert);
         if self.entities.is_alive(e) {
             let id = e.id();
             if self.data.mask.contains(id) {
@@ -405,7 +862,7 @@ where
                 Ok(Some(v))
             } else {
                 self.data.mask.add(id);
-                unsafe { self.data.inner.insert(id, v) };
+                unsafe { self.data.inner.insert_entity(e, v) };
                 Ok(None)
             }
         } else {
@@ -417,8 +874,238 @@ where
         }
     }
 
-    /// Removes the data associated with an `Entity`.
+    /// Like `insert`, but drops the replaced component, if any, and returns
+    /// a mutable reference to the inserted one instead.
+    ///
+    /// Tracked storages record the insertion (or a modification if a
+    /// component was replaced) just like with `insert`; changes done through
+    /// the returned reference aren't flagged separately.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let e = world.create_entity().build();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// pos.insert_mut(e, Pos(1)).unwrap().0 += 1;
+    /// assert_eq!(pos.get(e), Some(&Pos(2)));
+    /// ```
+    pub fn insert_mut(&mut self, e: Entity, v: T) -> Result<&mut T, Error> {
+        self.insert(e, v)?;
+
+        Ok(unsafe { self.data.inner.get_mut_silent(e.id()) })
+    }
+
+    /// Inserts a component for an entity with a greater index than all
+    /// entities which already have one, e.g. when loading a large world
+    /// with the entities in order.
+    ///
+    /// This skips the check for a component which has to be replaced, and
+    /// storages implementing `AppendStorage` can use a faster path for
+    /// appending; `DenseVecStorage` pushes the component without looking
+    /// up an existing slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity already has a component. In debug builds, this
+    /// also panics if the components aren't appended in ascending order;
+    /// release builds fall back to a normal insertion.
+    pub fn append(&mut self, e: Entity, v: T) -> Result<(), Error>
+    where
+        T::Storage: AppendStorage<T>,
+    {
+        self.data.stats.record(Access::Insert);
+        if !self.entities.is_alive(e) {
+            return Err(Error::WrongGeneration(WrongGeneration {
+                action: "append component for entity",
+                actual_gen: self.entities.entity(e.id()).gen(),
+                entity: e,
+            }));
+        }
+
+        let id = e.id();
+        debug_assert!(
+            !has_greater(&self.data.mask, id),
+            "components have to be appended in ascending order"
+        );
+        assert!(
+            !self.data.mask.add(id),
+            "tried to append a component for an entity which already has one"
+        );
+        unsafe { self.data.inner.append(id, v) };
+
+        Ok(())
+    }
+
+    /// Returns all components as a mutable slice, for storages that keep
+    /// them contiguous (like `DenseVecStorage`).
+    ///
+    /// The order of the values is internal to the storage; use
+    /// `entities_slice` to find out which entity a value belongs to.
+    ///
+    /// For a `FlaggedStorage`, every component is flagged as modified,
+    /// since changes done through the slice can't be tracked individually.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Pos(f32);
+    /// impl Component for Pos {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.create_entity().with(Pos(1.0)).build();
+    /// world.create_entity().with(Pos(2.0)).build();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// for p in pos.values_mut() {
+    ///     p.0 *= 2.0;
+    /// }
+    /// ```
+    pub fn values_mut(&mut self) -> &mut [T]
+    where
+        T::Storage: SliceAccess<T>,
+    {
+        self.data.inner.as_mut_slice()
+    }
+
+    /// Grows the backing storage so that inserting components for entities
+    /// with indices up to `id` doesn't cause a reallocation, e.g. to avoid
+    /// a large reallocation in the middle of a latency-sensitive frame.
+    ///
+    /// This only allocates memory; no component is added, so the mask and
+    /// everything depending on it (`get`, joins, ...) is unchanged.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Pos(f32);
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.write_storage::<Pos>().ensure_capacity_for(1000);
+    ///
+    /// let e = world.create_entity().build();
+    /// assert!(world.read_storage::<Pos>().get(e).is_none());
+    /// ```
+    pub fn ensure_capacity_for(&mut self, id: Index)
+    where
+        T::Storage: EnsureCapacity,
+    {
+        self.data.inner.ensure_capacity_for(id);
+    }
+
+    /// Inserts a batch of components, e.g. when deserializing a storage.
+    ///
+    /// Unlike inserting them one by one, the backing storage is grown once
+    /// for the whole batch first (see `EnsureCapacity::reserve_batch`), so
+    /// inserting out of order doesn't reallocate over and over again.
+    /// Components already present are replaced, just like with `insert`.
+    ///
+    /// If one of the entities is dead, nothing is inserted and an error is
+    /// returned.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = DenseVecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let entities: Vec<_> = world.create_iter().take(100).collect();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// pos.insert_batch(entities.iter().rev().map(|&e| (e, Pos(e.id()))))
+    ///     .unwrap();
+    /// assert_eq!(pos.get(entities[42]), Some(&Pos(42)));
+    /// ```
+    pub fn insert_batch<I>(&mut self, batch: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (Entity, T)>,
+        T::Storage: EnsureCapacity,
+    {
+        let batch: Vec<_> = batch.into_iter().collect();
+        let mut max_id = None;
+        for &(e, _) in &batch {
+            if !self.entities.is_alive(e) {
+                return Err(Error::WrongGeneration(WrongGeneration {
+                    action: "insert component batch for entity",
+                    actual_gen: self.entities.entity(e.id()).gen(),
+                    entity: e,
+                }));
+            }
+            max_id = Some(max_id.map_or(e.id(), |max: Index| max.max(e.id())));
+        }
+
+        if let Some(max_id) = max_id {
+            self.data.inner.reserve_batch(max_id, batch.len());
+        }
+        for (e, v) in batch {
+            self.insert(e, v)?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts new data for a given `Entity` without checking whether
+    /// it is alive, returning the replaced component (if any).
+    ///
+    /// This is meant for bulk loading, where many entities are created
+    /// atomically with `EntitiesRes::create_iter` and all bookkeeping is
+    /// deferred to a single `World::maintain` afterwards:
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// {
+    ///     let entities = world.entities();
+    ///     let mut pos = world.write_storage::<Pos>();
+    ///     for (i, e) in entities.create_iter().take(1000).enumerate() {
+    ///         // The entities have just been created, so they're alive.
+    ///         unsafe {
+    ///             pos.insert_unchecked(e, Pos(i as u32));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// world.maintain();
+    /// ```
+    ///
+    /// ## Safety
+    ///
+    /// The caller has to make sure `e` is alive (which includes entities
+    /// created atomically since the last `World::maintain`) and is not
+    /// deleted before the next `World::maintain`. Otherwise the component
+    /// is never cleaned up and would be visible to a later entity reusing
+    /// the same index.
+    pub unsafe fn insert_unchecked(&mut self, e: Entity, mut v: T) -> Option<T> {
+        let id = e.id();
+        if self.data.mask.contains(id) {
+            std::mem::swap(&mut v, self.data.inner.get_mut(id));
+            Some(v)
+        } else {
+            self.data.mask.add(id);
+            self.data.inner.insert_entity(e, v);
+            None
+        }
+    }
+
+    /// Removes the data associated with an `Entity`, returning the removed
+    /// component if there was one.
+    ///
+    /// Tracked storages like `FlaggedStorage` also flag the component as
+    /// removed; the returned value is the only way to access it afterwards.
+    /// Components removed by deleting the entity are dropped instead.
     pub fn remove(&mut self, e: Entity) -> Option<T> {
+        self.data.stats.record(Access::Remove);
         if self.entities.is_alive(e) {
             self.data.remove(e.id())
         } else {
@@ -438,6 +1125,64 @@ where
             data: &mut self.data,
         }
     }
+
+    /// Moves the components of all entities in `mask` to `dest`, converting
+    /// them with `Into`, and returns the number of moved components.
+    ///
+    /// This allows keeping e.g. active and dormant components in separate
+    /// storages (`T` can also be a wrapper around `U` or vice versa). Each
+    /// component is removed from this storage and inserted into `dest` in
+    /// a single pass, without cloning. Tracked storages flag the components
+    /// as removed from this storage and inserted into `dest`.
+    ///
+    /// `Component::on_remove` isn't called for moved components. If `dest`
+    /// already has a component for one of the entities, that one is removed
+    /// first, just like with `remove`, so its `on_remove` is called.
+    ///
+    /// Both storages are expected to belong to the same `World`; entities
+    /// which are not alive for `dest` keep their component in this storage.
+    pub fn move_to<U, D2>(&mut self, dest: &mut Storage<U, D2>, mask: &BitSet) -> usize
+    where
+        T: Into<U>,
+        U: Component,
+        D2: DerefMut<Target = MaskedStorage<U>>,
+    {
+        let ids: BitSet = BitSetAnd(mask, &self.data.mask).iter().collect();
+        let mut moved = 0;
+        for id in ids.iter() {
+            let e = self.entities.entity(id);
+            if !dest.entities.is_alive(e) {
+                continue;
+            }
+
+            // Not `remove`, since the component isn't destroyed and
+            // `Component::on_remove` mustn't be called.
+            self.data.stats.record(Access::Remove);
+            self.data.mask.remove(id);
+            let comp = unsafe { self.data.inner.remove(id) };
+            dest.remove(e);
+            // The entity is alive, so this can't fail.
+            let _ = dest.insert(e, comp.into());
+            moved += 1;
+        }
+
+        moved
+    }
+}
+
+/// Returns whether `mask` contains an index greater than `id`, by looking
+/// at the word containing `id` on each layer of the bitset.
+fn has_greater(mask: &BitSet, id: Index) -> bool {
+    let word_bits = std::mem::size_of::<usize>() * 8;
+    let shift = word_bits.trailing_zeros() as usize;
+    // The bits of `word` above position `i` (modulo the word size).
+    let above = |word: usize, i: usize| word & ((!0 << (i & (word_bits - 1))) << 1) != 0;
+
+    let id = id as usize;
+    above(mask.layer0(id >> shift), id)
+        || above(mask.layer1(id >> (2 * shift)), id >> shift)
+        || above(mask.layer2(id >> (3 * shift)), id >> (2 * shift))
+        || above(mask.layer3(), id >> (3 * shift))
 }
 
 unsafe impl<'a, T: Component, D> DistinctStorage for Storage<'a, T, D>
@@ -514,6 +1259,22 @@ where
 {
 }
 
+impl<'a, 'e, T, D> ChunkedJoin for &'a Storage<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+    T::Storage: DenseStorage,
+{
+}
+
+impl<'a, 'e, T, D> ChunkedJoin for &'a mut Storage<'e, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+    T::Storage: DenseStorage,
+{
+}
+
 /// Tries to create a default value, returns an `Err` with the name of the storage and/or component
 /// if there's no default.
 pub trait TryDefault: Sized {
@@ -542,6 +1303,9 @@ where
 pub trait UnprotectedStorage<T>: TryDefault {
     /// Clean the storage given a bitset with bits set for valid indices.
     /// Allows us to safely drop the storage.
+    ///
+    /// This removes every component in `has`, so storages tracking
+    /// removals have to report them here, just like in `remove`.
     unsafe fn clean<B>(&mut self, has: B)
     where
         B: BitSetLike;
@@ -556,9 +1320,31 @@ pub trait UnprotectedStorage<T>: TryDefault {
     /// to protect this storage is absent.
     unsafe fn get_mut(&mut self, id: Index) -> &mut T;
 
+    /// Like `get_mut`, but doesn't record a modification for
+    /// storages that track them (see `tracks_modified`).
+    ///
+    /// Defaults to `get_mut`.
+    ///
+    /// # Safety
+    ///
+    /// Same as `get_mut`, the index has to be contained in the external
+    /// set protecting this storage.
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut T {
+        self.get_mut(id)
+    }
+
     /// Inserts new data for a given `Index`.
     unsafe fn insert(&mut self, id: Index, value: T);
 
+    /// Inserts new data for a given `Entity`, which is used by `Storage`
+    /// whenever the entity is known.
+    ///
+    /// Defaults to `insert` with the index of the entity; storages which
+    /// need the generation as well, like `GenerationStorage`, override it.
+    unsafe fn insert_entity(&mut self, e: Entity, value: T) {
+        self.insert(e.id(), value)
+    }
+
     /// Removes the data associated with an `Index`.
     unsafe fn remove(&mut self, id: Index) -> T;
 
@@ -566,6 +1352,21 @@ pub trait UnprotectedStorage<T>: TryDefault {
     unsafe fn drop(&mut self, id: Index) {
         self.remove(id);
     }
+
+    /// Returns `true` if mutable access through `get_mut` is recorded
+    /// as a modification, like `FlaggedStorage` does.
+    #[inline]
+    fn tracks_modified(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of inserts which had to grow the buffers of
+    /// this storage, see `StorageStats::grows`.
+    ///
+    /// Only counted with the `stats` feature; defaults to `0`.
+    fn grows(&self) -> usize {
+        0
+    }
 }
 
 #[cfg(test)]
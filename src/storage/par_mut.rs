@@ -0,0 +1,115 @@
+use hibitset::BitSet;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::ParallelIterator;
+
+use join::{Join, ParJoin};
+use storage::{DistinctStorage, MaskedStorage, UnprotectedStorage};
+use world::{Component, Index};
+
+/// Storages whose components can be mutated from several threads at once,
+/// each accessing different indices, as done by `Storage::par_iter_mut`.
+///
+/// This is implemented for all `DistinctStorage`s, and for
+/// `FlaggedStorage`, which records the modified indices in an
+/// `AtomicBitSet` and writes the modification events once the iteration
+/// is done.
+///
+/// # Safety
+///
+/// `get_mut_par` must be safe to call from several threads at once, as
+/// long as the indices are distinct.
+pub unsafe trait ParMutStorage<C>: UnprotectedStorage<C> {
+    /// Returns the component at `id` mutably, while other threads may call
+    /// this for other indices.
+    ///
+    /// # Safety
+    ///
+    /// Has the same requirements as `UnprotectedStorage::get_mut`.
+    unsafe fn get_mut_par(&mut self, id: Index) -> &mut C;
+
+    /// Called once a parallel iteration is done, e.g. to write the events
+    /// recorded in the meantime.
+    ///
+    /// Does nothing by default.
+    fn finish_par(&mut self) {}
+}
+
+unsafe impl<C, T> ParMutStorage<C> for T
+where
+    T: UnprotectedStorage<C> + DistinctStorage,
+{
+    unsafe fn get_mut_par(&mut self, id: Index) -> &mut C {
+        self.get_mut(id)
+    }
+}
+
+/// A parallel iterator over the components of a storage, mutably, along
+/// with their indices; returned by `Storage::par_iter_mut`.
+pub struct ParIterMut<'a, T>
+where
+    T: Component,
+{
+    data: &'a mut MaskedStorage<T>,
+}
+
+impl<'a, T> ParIterMut<'a, T>
+where
+    T: Component,
+{
+    pub(crate) fn new(data: &'a mut MaskedStorage<T>) -> Self {
+        ParIterMut { data }
+    }
+}
+
+impl<'a, T> ParallelIterator for ParIterMut<'a, T>
+where
+    T: Component + Send,
+    T::Storage: ParMutStorage<T> + Sync,
+{
+    type Item = (Index, &'a mut T);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let data: *mut MaskedStorage<T> = self.data;
+        // The components handed out stay borrowed for `'a`, but
+        // `finish_par` doesn't touch them.
+        let result = unsafe { ParMutJoin(&mut *data) }
+            .indexed()
+            .par_join()
+            .drive_unindexed(consumer);
+        unsafe { (*data).inner.finish_par() };
+
+        result
+    }
+}
+
+struct ParMutJoin<'a, T: Component + 'a>(&'a mut MaskedStorage<T>);
+
+impl<'a, T> Join for ParMutJoin<'a, T>
+where
+    T: Component,
+    T::Storage: ParMutStorage<T>,
+{
+    type Type = &'a mut T;
+    type Value = &'a mut T::Storage;
+    type Mask = &'a BitSet;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        self.0.open_mut()
+    }
+
+    unsafe fn get(v: &mut Self::Value, i: Index) -> &'a mut T {
+        // See the `Join` implementation of `&mut Storage`.
+        let value: *mut Self::Value = v as *mut Self::Value;
+        (*value).get_mut_par(i)
+    }
+}
+
+unsafe impl<'a, T> ParJoin for ParMutJoin<'a, T>
+where
+    T: Component,
+    T::Storage: ParMutStorage<T> + Sync,
+{
+}
@@ -207,6 +207,110 @@ where
     }
 }
 
+impl<'st, T, D> Storage<'st, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Splits the storage into a `TokenMask`, which can be joined over while
+    /// only borrowing the mask, and a `TokenStorage` which turns the joined
+    /// `Token`s into component references on demand.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use specs::prelude::*;
+    /// # struct Health(u32);
+    /// # impl Component for Health { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// world.create_entity().with(Health(0)).build();
+    /// world.create_entity().with(Health(10)).build();
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// let (tokens, mut writer) = health.tokens_mut();
+    /// for (entity, token) in (&*world.entities(), tokens).join() {
+    ///     // The join doesn't hold on to the components, so any of them
+    ///     // can be accessed here, not just the current one.
+    ///     if writer.get(token).0 == 0 {
+    ///         writer.get_mut(token).0 = 100;
+    ///     }
+    /// }
+    /// ```
+    pub fn tokens_mut<'rf>(&'rf mut self) -> (TokenMask<'rf>, TokenStorage<'rf, T>) {
+        let (mask, data) = self.data.open_mut();
+
+        (TokenMask(mask), TokenStorage { mask, data })
+    }
+}
+
+/// The mask half of `Storage::tokens_mut`, yielding a `Token` for every
+/// component in the storage when joined.
+#[derive(Clone, Copy)]
+pub struct TokenMask<'a>(&'a BitSet);
+
+impl<'a> Join for TokenMask<'a> {
+    type Type = Token;
+    type Value = ();
+    type Mask = &'a BitSet;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.0, ())
+    }
+
+    unsafe fn get(_: &mut Self::Value, id: Index) -> Token {
+        Token(id)
+    }
+}
+
+/// Refers to a component of the storage a `TokenMask` was created from.
+///
+/// A token is only a checked index, it doesn't borrow anything. Since the
+/// mask can't change while the `TokenMask` and `TokenStorage` are borrowed,
+/// a token refers to an existing component for as long as they live. Tokens
+/// must not be used with the `TokenStorage` of another storage; this is
+/// checked at runtime.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Token(Index);
+
+impl Token {
+    /// Returns the index of the entity this token refers to.
+    pub fn id(&self) -> Index {
+        self.0
+    }
+}
+
+/// The storage half of `Storage::tokens_mut`, giving access to components
+/// through `Token`s.
+pub struct TokenStorage<'a, T: Component> {
+    mask: &'a BitSet,
+    data: &'a mut T::Storage,
+}
+
+impl<'a, T: Component> TokenStorage<'a, T> {
+    /// Gets the component a token refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token wasn't created for this storage.
+    pub fn get(&self, token: Token) -> &T {
+        assert!(self.mask.contains(token.0), "token is not valid for this storage");
+
+        unsafe { self.data.get(token.0) }
+    }
+
+    /// Gets the component a token refers to mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token wasn't created for this storage.
+    pub fn get_mut(&mut self, token: Token) -> &mut T {
+        assert!(self.mask.contains(token.0), "token is not valid for this storage");
+
+        unsafe { self.data.get_mut(token.0) }
+    }
+}
+
 /// Pairs a storage with an index, meaning that the index is guaranteed to exist
 /// as long as the `PairedStorage<C, S>` exists.
 pub struct PairedStorage<'rf, 'st: 'rf, C, S, B, Restrict> {
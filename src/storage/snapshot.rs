@@ -0,0 +1,140 @@
+use std::ops::{Deref, DerefMut};
+
+use fnv::FnvHashMap;
+use hibitset::{BitSet, BitSetLike};
+
+use storage::{MaskedStorage, NetChanges, Storage, UnprotectedStorage};
+use world::{Component, Index};
+
+/// A copy of all components of a storage, returned by `Storage::snapshot`.
+///
+/// Diffing a storage against a snapshot with `Storage::diff_snapshot` finds
+/// the changes done since the snapshot has been taken, without tracking
+/// them continuously like `FlaggedStorage` does.
+#[derive(Clone, Debug)]
+pub struct StorageSnapshot<T> {
+    mask: BitSet,
+    components: FnvHashMap<Index, T>,
+}
+
+impl<T> StorageSnapshot<T> {
+    /// Returns the indices which had a component when the snapshot was
+    /// taken.
+    pub fn mask(&self) -> &BitSet {
+        &self.mask
+    }
+
+    /// Returns the component an index had when the snapshot was taken.
+    pub fn get(&self, id: Index) -> Option<&T> {
+        self.components.get(&id)
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Takes a snapshot of all components, cloning them.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # #[derive(Clone, PartialEq)]
+    /// # struct Pos(u32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let a = world.create_entity().with(Pos(0)).build();
+    /// let b = world.create_entity().build();
+    ///
+    /// let snapshot = world.read_storage::<Pos>().snapshot();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// pos.get_mut(a).unwrap().0 = 3;
+    /// pos.insert(b, Pos(1)).unwrap();
+    ///
+    /// let changes = pos.diff_snapshot(&snapshot);
+    /// assert!(changes.modified.contains(a.id()));
+    /// assert!(changes.inserted.contains(b.id()));
+    /// ```
+    pub fn snapshot(&self) -> StorageSnapshot<T>
+    where
+        T: Clone,
+    {
+        let components = (&self.data.mask)
+            .iter()
+            .map(|id| (id, unsafe { self.data.inner.get(id) }.clone()))
+            .collect();
+
+        StorageSnapshot {
+            mask: self.data.mask.clone(),
+            components,
+        }
+    }
+
+    /// Compares the storage with a snapshot taken earlier and returns the
+    /// components which have been inserted, modified (compared with
+    /// `PartialEq`) or removed since.
+    ///
+    /// This is `O(n)` over the union of the current mask and the one of the
+    /// snapshot. Components are compared by index, so a component of a
+    /// deleted entity whose index has been reused counts as modified.
+    pub fn diff_snapshot(&self, snapshot: &StorageSnapshot<T>) -> NetChanges
+    where
+        T: PartialEq,
+    {
+        let mut changes = NetChanges::default();
+        for id in (&self.data.mask).iter() {
+            match snapshot.components.get(&id) {
+                Some(old) => if unsafe { self.data.inner.get(id) } != old {
+                    changes.modified.add(id);
+                },
+                None => {
+                    changes.inserted.add(id);
+                }
+            }
+        }
+        for id in (&snapshot.mask).iter() {
+            if !self.data.mask.contains(id) {
+                changes.removed.add(id);
+            }
+        }
+
+        changes
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Replaces all components with the ones of a snapshot.
+    ///
+    /// The components are restored by index, no matter whether the
+    /// entities are alive. Tracked storages flag all components which
+    /// have been there as removed, and the restored ones as inserted.
+    ///
+    /// `Component::on_remove` isn't called for the replaced components,
+    /// since the snapshot restores them (or their state before the snapshot
+    /// has been taken) rather than removing them.
+    pub fn restore_snapshot(&mut self, snapshot: StorageSnapshot<T>) {
+        let data = &mut *self.data;
+        unsafe {
+            data.inner.clean(&data.mask);
+        }
+        data.mask.clear();
+        for (id, component) in snapshot.components {
+            data.mask.add(id);
+            let e = self.entities.entity(id);
+            unsafe {
+                if self.entities.is_alive(e) {
+                    data.inner.insert_entity(e, component);
+                } else {
+                    data.inner.insert(id, component);
+                }
+            }
+        }
+    }
+}
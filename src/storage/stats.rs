@@ -0,0 +1,153 @@
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use hibitset::{BitSet, BitSetLike};
+
+use world::Index;
+
+/// Statistics about how a storage is used, returned by `Storage::stats`.
+///
+/// This helps choosing a storage for a component: a dense component which
+/// is accessed a lot is best kept in a `VecStorage`, while a sparse one
+/// should rather use `DenseVecStorage` or `HashMapStorage`.
+///
+/// The access counters are only collected with the `stats` feature;
+/// without it, they're always zero.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StorageStats {
+    /// Number of `get` calls.
+    pub gets: usize,
+    /// Number of `get_mut` calls.
+    pub get_muts: usize,
+    /// Number of `insert` calls.
+    pub inserts: usize,
+    /// Number of `remove` calls.
+    pub removes: usize,
+    /// Number of inserts which had to grow the buffers of the storage,
+    /// i.e. which reallocated.
+    ///
+    /// Only counted by `VecStorage` and `DenseVecStorage` (also when
+    /// wrapped in another storage); calling `Storage::ensure_capacity_for`
+    /// ahead of time avoids these.
+    pub grows: usize,
+    /// Number of components currently in the storage.
+    pub count: usize,
+    /// Highest index with a component, if there is one.
+    pub max_index: Option<Index>,
+}
+
+impl StorageStats {
+    pub(crate) fn new(counters: &AccessCounters, mask: &BitSet, grows: usize) -> Self {
+        let mut stats = counters.snapshot();
+        stats.grows = grows;
+        for id in mask.iter() {
+            stats.count += 1;
+            stats.max_index = Some(id);
+        }
+
+        stats
+    }
+
+    /// Returns the ratio of components to the indices up to the highest
+    /// one with a component, between `0.0` (empty) and `1.0` (no gaps).
+    pub fn density(&self) -> f32 {
+        match self.max_index {
+            Some(max) => self.count as f32 / (max as f32 + 1.0),
+            None => 0.0,
+        }
+    }
+}
+
+/// The kinds of accesses counted by `AccessCounters`.
+#[derive(Clone, Copy)]
+pub(crate) enum Access {
+    Get,
+    GetMut,
+    Insert,
+    Remove,
+}
+
+/// Counts the accesses to a storage if the `stats` feature is enabled,
+/// and is a zero-sized no-op otherwise.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default)]
+pub(crate) struct AccessCounters {
+    gets: AtomicUsize,
+    get_muts: AtomicUsize,
+    inserts: AtomicUsize,
+    removes: AtomicUsize,
+}
+
+#[cfg(feature = "stats")]
+impl AccessCounters {
+    #[inline]
+    pub(crate) fn record(&self, access: Access) {
+        let counter = match access {
+            Access::Get => &self.gets,
+            Access::GetMut => &self.get_muts,
+            Access::Insert => &self.inserts,
+            Access::Remove => &self.removes,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> StorageStats {
+        StorageStats {
+            gets: self.gets.load(Ordering::Relaxed),
+            get_muts: self.get_muts.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            removes: self.removes.load(Ordering::Relaxed),
+            ..StorageStats::default()
+        }
+    }
+}
+
+/// Counts the accesses to a storage if the `stats` feature is enabled,
+/// and is a zero-sized no-op otherwise.
+#[cfg(not(feature = "stats"))]
+#[derive(Debug, Default)]
+pub(crate) struct AccessCounters;
+
+#[cfg(not(feature = "stats"))]
+impl AccessCounters {
+    #[inline]
+    pub(crate) fn record(&self, _: Access) {}
+
+    fn snapshot(&self) -> StorageStats {
+        StorageStats::default()
+    }
+}
+
+/// Counts the inserts which grew the buffers of a storage if the `stats`
+/// feature is enabled, and is a zero-sized no-op otherwise.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default)]
+pub(crate) struct GrowCounter(usize);
+
+#[cfg(feature = "stats")]
+impl GrowCounter {
+    #[inline]
+    pub(crate) fn record(&mut self) {
+        self.0 += 1;
+    }
+
+    pub(crate) fn get(&self) -> usize {
+        self.0
+    }
+}
+
+/// Counts the inserts which grew the buffers of a storage if the `stats`
+/// feature is enabled, and is a zero-sized no-op otherwise.
+#[cfg(not(feature = "stats"))]
+#[derive(Debug, Default)]
+pub(crate) struct GrowCounter;
+
+#[cfg(not(feature = "stats"))]
+impl GrowCounter {
+    #[inline]
+    pub(crate) fn record(&mut self) {}
+
+    pub(crate) fn get(&self) -> usize {
+        0
+    }
+}
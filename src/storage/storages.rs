@@ -1,11 +1,15 @@
 //! Different types of storages you can use for your components.
 
 use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::sync::Arc;
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use hibitset::BitSetLike;
 
-use storage::{DistinctStorage, UnprotectedStorage};
+use storage::stats::GrowCounter;
+use storage::{AppendStorage, DenseStorage, DistinctStorage, EnsureCapacity, SliceAccess,
+              UnprotectedStorage, WithCapacity};
 use world::Index;
 
 #[cfg(feature = "rudy")]
@@ -44,6 +48,11 @@ impl<T> UnprotectedStorage<T> for BTreeStorage<T> {
 unsafe impl<T> DistinctStorage for BTreeStorage<T> {}
 
 /// HashMap-based storage. Best suited for rare components.
+///
+/// The map uses the FNV hasher, which has no random state, so the storage
+/// behaves the same in every run; joins over it are in ascending order of
+/// entity ids anyway. This makes seeding it for reproducible tests or
+/// replays unnecessary.
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
 pub struct HashMapStorage<T>(FnvHashMap<Index, T>);
@@ -75,6 +84,81 @@ impl<T> UnprotectedStorage<T> for HashMapStorage<T> {
 
 unsafe impl<T> DistinctStorage for HashMapStorage<T> {}
 
+impl<T> WithCapacity for HashMapStorage<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        HashMapStorage(FnvHashMap::with_capacity_and_hasher(
+            capacity,
+            Default::default(),
+        ))
+    }
+}
+
+/// Arena-based storage. Component values are kept in a slab, with a map
+/// from entity ids to slots; freed slots are reused by later insertions.
+///
+/// Like `HashMapStorage`, this is suited for rare components, but the values
+/// themselves are stored next to each other, which makes it a better fit
+/// for large components that are inserted and removed often.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct ArenaStorage<T> {
+    slots: FnvHashMap<Index, usize>,
+    arena: Vec<Option<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> UnprotectedStorage<T> for ArenaStorage<T> {
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.slots.clear();
+        self.arena.clear();
+        self.free.clear();
+    }
+
+    unsafe fn get(&self, id: Index) -> &T {
+        self.arena[self.slots[&id]].as_ref().unwrap()
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut T {
+        let slot = self.slots[&id];
+        self.arena[slot].as_mut().unwrap()
+    }
+
+    unsafe fn insert(&mut self, id: Index, v: T) {
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.arena[slot] = Some(v);
+                slot
+            }
+            None => {
+                self.arena.push(Some(v));
+                self.arena.len() - 1
+            }
+        };
+        self.slots.insert(id, slot);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> T {
+        let slot = self.slots.remove(&id).unwrap();
+        self.free.push(slot);
+        self.arena[slot].take().unwrap()
+    }
+}
+
+unsafe impl<T> DistinctStorage for ArenaStorage<T> {}
+
+impl<T> WithCapacity for ArenaStorage<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        ArenaStorage {
+            slots: FnvHashMap::with_capacity_and_hasher(capacity, Default::default()),
+            arena: Vec::with_capacity(capacity),
+            free: Vec::new(),
+        }
+    }
+}
+
 /// Dense vector storage. Has a redirection 2-way table
 /// between entities and components, allowing to leave
 /// no gaps within the data.
@@ -84,6 +168,7 @@ pub struct DenseVecStorage<T> {
     data: Vec<T>,
     entity_id: Vec<Index>,
     data_id: Vec<Index>,
+    grows: GrowCounter,
 }
 
 impl<T> UnprotectedStorage<T> for DenseVecStorage<T> {
@@ -106,8 +191,11 @@ impl<T> UnprotectedStorage<T> for DenseVecStorage<T> {
 
     unsafe fn insert(&mut self, id: Index, v: T) {
         let id = id as usize;
-        if self.data_id.len() <= id {
-            let delta = id + 1 - self.data_id.len();
+        let delta = (id + 1).saturating_sub(self.data_id.len());
+        if grows(&self.data_id, delta) || grows(&self.entity_id, 1) || grows(&self.data, 1) {
+            self.grows.record();
+        }
+        if delta > 0 {
             self.data_id.reserve(delta);
             self.data_id.set_len(id + 1);
         }
@@ -123,10 +211,72 @@ impl<T> UnprotectedStorage<T> for DenseVecStorage<T> {
         self.entity_id.swap_remove(did as usize);
         self.data.swap_remove(did as usize)
     }
+
+    fn grows(&self) -> usize {
+        self.grows.get()
+    }
+}
+
+impl<T> AppendStorage<T> for DenseVecStorage<T> {
+    unsafe fn append(&mut self, id: Index, v: T) {
+        let id = id as usize;
+        if self.data_id.len() > id {
+            // There has been a component with a higher index before.
+            return self.insert(id as Index, v);
+        }
+
+        // The indices between the last one and `id` are just padding.
+        self.data_id.resize(id, 0);
+        self.data_id.push(self.data.len() as Index);
+        self.entity_id.push(id as Index);
+        self.data.push(v);
+    }
 }
 
 unsafe impl<T> DistinctStorage for DenseVecStorage<T> {}
 
+impl<T> DenseStorage for DenseVecStorage<T> {}
+
+impl<T> EnsureCapacity for DenseVecStorage<T> {
+    fn ensure_capacity_for(&mut self, id: Index) {
+        let needed = id as usize + 1;
+        reserve_total(&mut self.data_id, needed);
+        reserve_total(&mut self.entity_id, needed);
+        reserve_total(&mut self.data, needed);
+    }
+
+    fn reserve_batch(&mut self, max_id: Index, count: usize) {
+        reserve_total(&mut self.data_id, max_id as usize + 1);
+        self.entity_id.reserve(count);
+        self.data.reserve(count);
+    }
+}
+
+impl<T> WithCapacity for DenseVecStorage<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        DenseVecStorage {
+            data: Vec::with_capacity(capacity),
+            entity_id: Vec::with_capacity(capacity),
+            data_id: Vec::with_capacity(capacity),
+            grows: GrowCounter::default(),
+        }
+    }
+}
+
+impl<T> SliceAccess<T> for DenseVecStorage<T> {
+    fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    fn slice_ids(&self) -> &[Index] {
+        &self.entity_id
+    }
+}
+
 /// A null storage type, used for cases where the component
 /// doesn't contain any data and instead works as a simple flag.
 pub struct NullStorage<T>(T);
@@ -172,11 +322,90 @@ where
 /// This is safe because you cannot mutate ZSTs.
 unsafe impl<T> DistinctStorage for NullStorage<T> {}
 
+/// Interning storage. Equal components share a single allocation, which
+/// saves memory for components with only a few distinct values, like
+/// materials or configurations shared by many entities.
+///
+/// Mutable access clones a shared value first (copy-on-write), so changing
+/// the component of one entity never affects another. Values which have
+/// been accessed mutably aren't shared anymore until they're inserted
+/// again.
+#[derive(Derivative)]
+#[derivative(Default(bound = "T: Eq + Hash"))]
+pub struct InternedStorage<T> {
+    handles: Vec<Option<Arc<T>>>,
+    interned: FnvHashSet<Arc<T>>,
+}
+
+impl<T: Eq + Hash> InternedStorage<T> {
+    /// Returns the number of distinct values which are currently shared
+    /// through this storage.
+    pub fn interned_count(&self) -> usize {
+        self.interned.len()
+    }
+
+    /// Removes `value` from the intern table if `handle` is the last
+    /// reference to it besides the table itself.
+    fn release(interned: &mut FnvHashSet<Arc<T>>, handle: &Arc<T>) {
+        if Arc::strong_count(handle) == 2 {
+            interned.remove(&**handle);
+        }
+    }
+}
+
+impl<T> UnprotectedStorage<T> for InternedStorage<T>
+where
+    T: Clone + Eq + Hash,
+{
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.handles.clear();
+        self.interned.clear();
+    }
+
+    unsafe fn get(&self, id: Index) -> &T {
+        self.handles[id as usize].as_ref().unwrap()
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut T {
+        let handle = self.handles[id as usize].as_mut().unwrap();
+        Self::release(&mut self.interned, handle);
+
+        Arc::make_mut(handle)
+    }
+
+    unsafe fn insert(&mut self, id: Index, v: T) {
+        let handle = match self.interned.get(&v) {
+            Some(handle) => handle.clone(),
+            None => {
+                let handle = Arc::new(v);
+                self.interned.insert(handle.clone());
+                handle
+            }
+        };
+
+        let id = id as usize;
+        if self.handles.len() <= id {
+            self.handles.resize(id + 1, None);
+        }
+        self.handles[id] = Some(handle);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> T {
+        let handle = self.handles[id as usize].take().unwrap();
+        Self::release(&mut self.interned, &handle);
+
+        Arc::try_unwrap(handle).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
 /// Vector storage. Uses a simple `Vec`. Supposed to have maximum
 /// performance for the components mostly present in entities.
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
-pub struct VecStorage<T>(Vec<T>);
+pub struct VecStorage<T>(Vec<T>, GrowCounter);
 
 impl<T> UnprotectedStorage<T> for VecStorage<T> {
     unsafe fn clean<B>(&mut self, has: B)
@@ -206,6 +435,9 @@ impl<T> UnprotectedStorage<T> for VecStorage<T> {
         let id = id as usize;
         if self.0.len() <= id {
             let delta = id + 1 - self.0.len();
+            if grows(&self.0, delta) {
+                self.1.record();
+            }
             self.0.reserve(delta);
             self.0.set_len(id + 1);
         }
@@ -219,10 +451,48 @@ impl<T> UnprotectedStorage<T> for VecStorage<T> {
 
         ptr::read(self.get(id))
     }
+
+    fn grows(&self) -> usize {
+        self.1.get()
+    }
+}
+
+impl<T> AppendStorage<T> for VecStorage<T> {
+    unsafe fn append(&mut self, id: Index, v: T) {
+        self.insert(id, v);
+    }
 }
 
 unsafe impl<T> DistinctStorage for VecStorage<T> {}
 
+impl<T> DenseStorage for VecStorage<T> {}
+
+impl<T> EnsureCapacity for VecStorage<T> {
+    fn ensure_capacity_for(&mut self, id: Index) {
+        reserve_total(&mut self.0, id as usize + 1);
+    }
+}
+
+impl<T> WithCapacity for VecStorage<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        VecStorage(Vec::with_capacity(capacity), GrowCounter::default())
+    }
+}
+
+/// Returns `true` if pushing `additional` elements to `vec` reallocates.
+#[inline]
+fn grows<T>(vec: &Vec<T>, additional: usize) -> bool {
+    vec.capacity() - vec.len() < additional
+}
+
+/// Makes sure `vec` can hold `total` elements without reallocating.
+fn reserve_total<T>(vec: &mut Vec<T>, total: usize) {
+    if total > vec.len() {
+        let additional = total - vec.len();
+        vec.reserve(additional);
+    }
+}
+
 /// Rudy-based storage.
 #[cfg(feature = "rudy")]
 #[derive(Derivative)]
@@ -200,6 +200,38 @@ mod test {
         type Storage = BTreeStorage<Self>;
     }
 
+    #[derive(PartialEq, Eq, Debug)]
+    struct CArena(u32);
+    impl From<u32> for CArena {
+        fn from(v: u32) -> CArena {
+            CArena(v)
+        }
+    }
+    impl AsMut<u32> for CArena {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for CArena {
+        type Storage = ArenaStorage<Self>;
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Hash)]
+    struct CInterned(u32);
+    impl From<u32> for CInterned {
+        fn from(v: u32) -> CInterned {
+            CInterned(v)
+        }
+    }
+    impl AsMut<u32> for CInterned {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for CInterned {
+        type Storage = InternedStorage<Self>;
+    }
+
     #[derive(PartialEq, Eq, Debug)]
     #[cfg(feature = "rudy")]
     struct CRudy(u32);
@@ -484,6 +516,106 @@ mod test {
         test_clear::<CBtree>();
     }
 
+    #[test]
+    fn arena_test_add() {
+        test_add::<CArena>();
+    }
+    #[test]
+    fn arena_test_sub() {
+        test_sub::<CArena>();
+    }
+    #[test]
+    fn arena_test_get_mut() {
+        test_get_mut::<CArena>();
+    }
+    #[test]
+    fn arena_test_add_gen() {
+        test_add_gen::<CArena>();
+    }
+    #[test]
+    fn arena_test_sub_gen() {
+        test_sub_gen::<CArena>();
+    }
+    #[test]
+    fn arena_test_clear() {
+        test_clear::<CArena>();
+    }
+    #[test]
+    fn arena_test_reuse() {
+        let mut w = World::new();
+        w.register::<CArena>();
+        let entities: Vec<_> = (0..10)
+            .map(|i| w.create_entity().with(CArena(i)).build())
+            .collect();
+
+        let mut s = w.write_storage::<CArena>();
+        for e in &entities[..5] {
+            assert!(s.remove(*e).is_some());
+        }
+        for (i, e) in entities[..5].iter().enumerate() {
+            s.insert(*e, CArena(100 + i as u32)).unwrap();
+        }
+
+        for (i, e) in entities.iter().enumerate() {
+            let expected = if i < 5 { 100 + i as u32 } else { i as u32 };
+            assert_eq!(s.get(*e), Some(&CArena(expected)));
+        }
+    }
+
+    #[test]
+    fn interned_test_add() {
+        test_add::<CInterned>();
+    }
+    #[test]
+    fn interned_test_sub() {
+        test_sub::<CInterned>();
+    }
+    #[test]
+    fn interned_test_get_mut() {
+        test_get_mut::<CInterned>();
+    }
+    #[test]
+    fn interned_test_add_gen() {
+        test_add_gen::<CInterned>();
+    }
+    #[test]
+    fn interned_test_sub_gen() {
+        test_sub_gen::<CInterned>();
+    }
+    #[test]
+    fn interned_test_clear() {
+        test_clear::<CInterned>();
+    }
+    #[test]
+    fn interned_test_sharing() {
+        let mut w = World::new();
+        w.register::<CInterned>();
+        let entities: Vec<_> = (0..10)
+            .map(|i| w.create_entity().with(CInterned(i % 2)).build())
+            .collect();
+
+        let mut s = w.write_storage::<CInterned>();
+        assert_eq!(s.unprotected_storage().interned_count(), 2);
+
+        // Copy-on-write: only the mutated component changes.
+        s.get_mut(entities[0]).unwrap().0 = 7;
+        assert_eq!(s.get(entities[0]), Some(&CInterned(7)));
+        for e in &entities[1..] {
+            assert_eq!(s.get(*e), Some(&CInterned(e.id() % 2)));
+        }
+        assert_eq!(s.unprotected_storage().interned_count(), 2);
+
+        // Values are dropped from the table once nobody uses them anymore.
+        for e in entities.iter().filter(|e| e.id() % 2 == 1) {
+            assert_eq!(s.remove(*e), Some(CInterned(1)));
+        }
+        assert_eq!(s.unprotected_storage().interned_count(), 1);
+
+        s.insert(entities[1], CInterned(0)).unwrap();
+        assert_eq!(s.unprotected_storage().interned_count(), 1);
+        assert_eq!(s.get(entities[1]), Some(&CInterned(0)));
+    }
+
     #[cfg(feature = "rudy")]
     #[test]
     fn rudy_test_add() {
@@ -704,6 +836,229 @@ mod test {
         assert_eq!(s1.get(e6), None);
     }
 
+    #[test]
+    fn contains_entities() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+
+        let present = w.create_entity().with(Cvec(1)).build();
+        let absent = w.create_entity().build();
+        let stale = w.create_entity().with(Cvec(2)).build();
+        w.delete_entity(stale).unwrap();
+        // reuses the index of `stale`
+        let reused = w.create_entity().with(Cvec(3)).build();
+        assert_eq!(reused.id(), stale.id());
+
+        let s = w.read_storage::<Cvec>();
+        assert_eq!(
+            s.contains_entities(&[present, absent, stale, reused]),
+            vec![true, false, false, true]
+        );
+        assert!(s.contains_entities(&[]).is_empty());
+    }
+
+    #[test]
+    fn gather() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Value(u32);
+
+        impl Component for Value {
+            type Storage = DenseVecStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Value>();
+
+        let entities: Vec<_> = (0..6)
+            .map(|i| {
+                let mut builder = w.create_entity();
+                if i % 3 != 0 {
+                    builder = builder.with(Value(i));
+                }
+                builder.build()
+            })
+            .collect();
+        let dead = w.create_entity().with(Value(100)).build();
+        w.delete_entity(dead).unwrap();
+
+        let s = w.read_storage::<Value>();
+        let mut out = Vec::with_capacity(16);
+        let capacity = out.capacity();
+
+        s.gather(&entities, &mut out);
+        assert_eq!(
+            out,
+            vec![None, Some(Value(1)), Some(Value(2)), None, Some(Value(4)), Some(Value(5))]
+        );
+
+        // Previous contents are replaced.
+        s.gather(&[entities[4], dead, entities[0]], &mut out);
+        assert_eq!(out, vec![Some(Value(4)), None, None]);
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    #[test]
+    fn insert_mut() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let e = w.create_entity().build();
+        let dead = w.create_entity().build();
+        w.delete_entity(dead).unwrap();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut readers = s.track_all();
+
+        s.insert_mut(e, FlaggedCvec(1)).unwrap().0 += 10;
+        assert_eq!(s.get(e), Some(&FlaggedCvec(11)));
+        assert!(s.insert_mut(dead, FlaggedCvec(2)).is_err());
+        assert_eq!(s.get(dead), None);
+
+        let mut inserted = BitSet::new();
+        let mut modified = BitSet::new();
+        s.populate_inserted(&mut readers.insert, &mut inserted);
+        s.populate_modified(&mut readers.modify, &mut modified);
+        assert!(inserted.contains(e.id()));
+        assert!(!inserted.contains(dead.id()));
+        assert!(!modified.contains(e.id()));
+
+        // Replacing counts as a modification, just like `insert`.
+        *s.insert_mut(e, FlaggedCvec(3)).unwrap() = FlaggedCvec(4);
+        assert_eq!(s.get(e), Some(&FlaggedCvec(4)));
+        s.populate_modified(&mut readers.modify, &mut modified);
+        assert!(modified.contains(e.id()));
+    }
+
+    #[test]
+    fn stats() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        let entities: Vec<_> = (0..8).map(|_| w.create_entity().build()).collect();
+
+        let mut s = w.write_storage::<Cvec>();
+        assert_eq!(s.stats(), StorageStats::default());
+        assert_eq!(s.stats().density(), 0.0);
+
+        for e in &entities[..4] {
+            s.insert(*e, Cvec(0)).unwrap();
+        }
+        s.get(entities[0]);
+        s.get(entities[7]);
+        s.get_mut(entities[1]);
+        s.remove(entities[0]);
+
+        let stats = s.stats();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.max_index, Some(entities[3].id()));
+        assert_eq!(stats.density(), 0.75);
+
+        if cfg!(feature = "stats") {
+            assert_eq!(
+                (stats.gets, stats.get_muts, stats.inserts, stats.removes),
+                (2, 1, 4, 1)
+            );
+        } else {
+            assert_eq!(
+                (stats.gets, stats.get_muts, stats.inserts, stats.removes),
+                (0, 0, 0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn stats_grows() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<FlaggedCvec>();
+        let entities: Vec<_> = (0..100).map(|_| w.create_entity().build()).collect();
+
+        {
+            let mut s = w.write_storage::<Cvec>();
+            s.ensure_capacity_for(9);
+            for e in &entities[..10] {
+                s.insert(*e, Cvec(0)).unwrap();
+            }
+            assert_eq!(s.stats().grows, 0);
+
+            s.insert(entities[99], Cvec(99)).unwrap();
+            let expected = if cfg!(feature = "stats") { 1 } else { 0 };
+            assert_eq!(s.stats().grows, expected);
+        }
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        s.insert(entities[0], FlaggedCvec(0)).unwrap();
+        let expected = if cfg!(feature = "stats") { 1 } else { 0 };
+        assert_eq!(s.stats().grows, expected);
+    }
+
+    #[test]
+    fn ensure_capacity_for() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<FlaggedCvec>();
+        let entities: Vec<_> = (0..10).map(|_| w.create_entity().build()).collect();
+
+        {
+            let mut s = w.write_storage::<Cvec>();
+            s.ensure_capacity_for(100);
+            // nothing is present
+            assert!(entities.iter().all(|&e| s.get(e).is_none()));
+            assert_eq!((&s).join().count(), 0);
+
+            s.insert(entities[9], Cvec(9)).unwrap();
+            assert_eq!(s.get(entities[9]), Some(&Cvec(9)));
+            assert_eq!(s.get(entities[8]), None);
+        }
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        s.ensure_capacity_for(50);
+        assert!(entities.iter().all(|&e| s.get(e).is_none()));
+    }
+
+    #[test]
+    fn insert_batch() {
+        use join::Join;
+
+        #[derive(Debug, PartialEq)]
+        struct Dense(u32);
+        impl Component for Dense {
+            type Storage = DenseVecStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Dense>();
+        let entities: Vec<_> = w.create_iter().take(50).collect();
+        w.write_storage::<Dense>()
+            .insert(entities[11], Dense(0))
+            .unwrap();
+
+        {
+            let mut s = w.write_storage::<Dense>();
+            let batch = entities
+                .iter()
+                .rev()
+                .filter(|e| e.id() % 2 == 1)
+                .map(|&e| (e, Dense(e.id())));
+            s.insert_batch(batch).unwrap();
+
+            // replaced the existing component
+            assert_eq!(s.get(entities[11]), Some(&Dense(11)));
+            assert_eq!(s.get(entities[10]), None);
+            for &e in entities.iter().filter(|e| e.id() % 2 == 1) {
+                assert_eq!(s.get(e), Some(&Dense(e.id())));
+            }
+            assert_eq!((&s).join().count(), 25);
+            assert_eq!(s.values_mut().len(), 25);
+        }
+
+        // all or nothing
+        let dead = entities[0];
+        w.delete_entity(dead).unwrap();
+        let mut s = w.write_storage::<Dense>();
+        let batch = vec![(entities[2], Dense(2)), (dead, Dense(0))];
+        assert!(s.insert_batch(batch).is_err());
+        assert_eq!(s.get(entities[2]), None);
+    }
+
     #[test]
     fn storage_mask() {
         use join::Join;
@@ -825,4 +1180,1474 @@ mod test {
             assert!(removed.contains(entity.id()));
         }
     }
+
+    #[test]
+    fn for_each_mut() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<FlaggedCvec>();
+
+        let entities: Vec<_> = (0..10)
+            .map(|i| {
+                let mut builder = w.create_entity().with(FlaggedCvec(i));
+                if i % 2 == 0 {
+                    builder = builder.with(Cvec(i));
+                }
+                builder.build()
+            })
+            .collect();
+
+        {
+            let mut s = w.write_storage::<Cvec>();
+            let mut visited = Vec::new();
+            s.for_each_mut(|e, c| {
+                visited.push(e);
+                c.0 *= 10;
+            });
+
+            let expected: Vec<_> = entities
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i % 2 == 0)
+                .map(|(_, &e)| e)
+                .collect();
+            assert_eq!(visited, expected);
+            for (i, &e) in entities.iter().enumerate() {
+                let expected = if i % 2 == 0 { Some(&Cvec(i as u32 * 10)) } else { None };
+                assert_eq!(s.get(e), expected);
+            }
+        }
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut modified_id = s.track_modified();
+        s.for_each_mut(|_, c| c.0 += 1);
+
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut modified_id, &mut modified);
+        for (i, &e) in entities.iter().enumerate() {
+            assert_eq!(s.get(e), Some(&FlaggedCvec(i as u32 + 1)));
+            assert!(modified.contains(e.id()));
+        }
+    }
+
+    #[test]
+    fn flagged_removed_on_delete_and_clear() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+
+        let entities: Vec<_> = (0..4)
+            .map(|i| w.create_entity().with(FlaggedCvec(i)).build())
+            .collect();
+        let mut removed_id = w.write_storage::<FlaggedCvec>().track_removed();
+        let mut removed = BitSet::new();
+
+        w.delete_entity(entities[0]).unwrap();
+        w.maintain();
+
+        w.read_storage::<FlaggedCvec>()
+            .populate_removed(&mut removed_id, &mut removed);
+        assert!(removed.contains(entities[0].id()));
+        assert!(!removed.contains(entities[1].id()));
+
+        removed.clear();
+        w.write_storage::<FlaggedCvec>().clear();
+
+        w.read_storage::<FlaggedCvec>()
+            .populate_removed(&mut removed_id, &mut removed);
+        assert!(!removed.contains(entities[0].id()));
+        for e in &entities[1..] {
+            assert!(removed.contains(e.id()));
+        }
+    }
+
+    #[test]
+    fn net_changes() {
+        // xorshift, so failures are reproducible
+        fn next(state: &mut u32) -> u32 {
+            *state ^= *state << 13;
+            *state ^= *state >> 17;
+            *state ^= *state << 5;
+            *state
+        }
+
+        for seed in 1..100 {
+            let mut rng = seed;
+            let mut w = World::new();
+            w.register::<FlaggedCvec>();
+            let entities: Vec<_> = (0..8).map(|_| w.create_entity().build()).collect();
+
+            let mut s = w.write_storage::<FlaggedCvec>();
+            for &e in &entities {
+                if next(&mut rng) % 2 == 0 {
+                    s.insert(e, FlaggedCvec(0)).unwrap();
+                }
+            }
+            let before: Vec<_> = entities.iter().map(|&e| s.get(e).map(|c| c.0)).collect();
+            let mut touched = vec![false; entities.len()];
+            let mut readers = s.track_all();
+
+            for _ in 0..next(&mut rng) % 30 {
+                let i = next(&mut rng) as usize % entities.len();
+                let e = entities[i];
+                let v = next(&mut rng) % 4;
+                touched[i] = true;
+                match next(&mut rng) % 3 {
+                    0 => {
+                        s.insert(e, FlaggedCvec(v)).unwrap();
+                    }
+                    1 => if let Some(c) = s.get_mut(e) {
+                        c.0 = v;
+                    },
+                    _ => {
+                        s.remove(e);
+                    }
+                }
+            }
+
+            let net = s.net_changes(&mut readers);
+            for (i, &e) in entities.iter().enumerate() {
+                let id = e.id();
+                let after = s.get(e).map(|c| c.0);
+                let (inserted, modified, removed) = (
+                    net.inserted.contains(id),
+                    net.modified.contains(id),
+                    net.removed.contains(id),
+                );
+                let context = (seed, i, before[i], after);
+
+                assert_eq!(inserted, before[i].is_none() && after.is_some(), "{:?}", context);
+                assert_eq!(removed, before[i].is_some() && after.is_none(), "{:?}", context);
+                if modified {
+                    assert!(touched[i], "{:?}", context);
+                    assert!(before[i].is_some() && after.is_some(), "{:?}", context);
+                }
+                if before[i].is_some() && after.is_some() && before[i] != after {
+                    assert!(modified, "{:?}", context);
+                }
+            }
+
+            // Everything has been read.
+            let net = s.net_changes(&mut readers);
+            assert_eq!(net.inserted.iter().count(), 0);
+            assert_eq!(net.modified.iter().count(), 0);
+            assert_eq!(net.removed.iter().count(), 0);
+        }
+    }
+
+    #[test]
+    fn changed_mut() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let entities: Vec<_> = (0..6)
+            .map(|i| w.create_entity().with(FlaggedCvec(i)).build())
+            .collect();
+        let new = w.create_entity().build();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut readers = s.track_all();
+        s.get_mut(entities[1]).unwrap().0 = 10;
+        s.get_mut(entities[4]).unwrap().0 = 40;
+        s.remove(entities[2]);
+        s.insert(new, FlaggedCvec(100)).unwrap();
+
+        let mut visited = Vec::new();
+        for (e, c) in s.changed_mut(&mut readers) {
+            visited.push(e);
+            c.0 += 1;
+        }
+        visited.sort();
+        let mut expected = vec![entities[1], entities[4], new];
+        expected.sort();
+        assert_eq!(visited, expected);
+
+        assert_eq!(s.get(entities[1]), Some(&FlaggedCvec(11)));
+        assert_eq!(s.get(entities[4]), Some(&FlaggedCvec(41)));
+        assert_eq!(s.get(new), Some(&FlaggedCvec(101)));
+        assert_eq!(s.get(entities[0]), Some(&FlaggedCvec(0)));
+
+        // Nothing has been flagged again.
+        assert_eq!(s.changed_mut(&mut readers).count(), 0);
+    }
+
+    #[test]
+    fn move_to_keeps_component_alive() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Handle(Arc<AtomicUsize>);
+        impl Component for Handle {
+            type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+
+            fn on_remove(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        struct Parked(Handle);
+        impl Component for Parked {
+            type Storage = DenseVecStorage<Self>;
+
+            fn on_remove(&mut self) {
+                self.0.on_remove();
+            }
+        }
+        impl From<Handle> for Parked {
+            fn from(h: Handle) -> Parked {
+                Parked(h)
+            }
+        }
+
+        let released = Arc::new(AtomicUsize::new(0));
+        let mut w = World::new();
+        w.register::<Handle>();
+        w.register::<Parked>();
+        let e = w.create_entity().with(Handle(released.clone())).build();
+        let occupied = w
+            .create_entity()
+            .with(Handle(released.clone()))
+            .with(Parked(Handle(released.clone())))
+            .build();
+
+        let mut handles = w.write_storage::<Handle>();
+        let mut parked = w.write_storage::<Parked>();
+        let mut removed = handles.track_removed();
+
+        let mut mask = BitSet::new();
+        mask.add(e.id());
+        assert_eq!(handles.move_to(&mut parked, &mask), 1);
+        assert_eq!(released.load(Ordering::SeqCst), 0);
+        assert!(parked.contains(e));
+
+        let mut removed_ids = BitSet::new();
+        handles.populate_removed(&mut removed, &mut removed_ids);
+        assert!(removed_ids.contains(e.id()));
+
+        // the component `occupied` already had in `parked` is removed first
+        mask.clear();
+        mask.add(occupied.id());
+        assert_eq!(handles.move_to(&mut parked, &mask), 1);
+        assert_eq!(released.load(Ordering::SeqCst), 1);
+        assert!(parked.contains(occupied));
+    }
+
+    #[test]
+    fn compact_tracked() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Mass(u32);
+        impl FieldDiff for Mass {
+            type Fields = bool;
+
+            fn field_diff(old: &Self, new: &Self) -> bool {
+                old != new
+            }
+        }
+        impl Component for Mass {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Mass>();
+        let entities: Vec<_> = (0..1000)
+            .map(|i| w.create_entity().with(Mass(i)).build())
+            .collect();
+
+        {
+            let mut s = w.write_storage::<Mass>();
+            for &e in &entities {
+                s.get_mut(e).unwrap().0 += 1;
+            }
+            s.maintain_fields();
+        }
+        for &e in &entities[1..] {
+            w.delete_entity(e).unwrap();
+        }
+        w.maintain();
+
+        let mut s = w.write_storage::<Mass>();
+        // the changed fields of the deleted entities have been dropped
+        // already, but their memory is still allocated
+        assert!(s.compact_tracked() >= 999);
+        assert_eq!(s.compact_tracked(), 0);
+        assert!(s.field_changes(entities[0]));
+
+        // tracking still works afterwards
+        s.clear_field_changes();
+        let mut reader = s.track_modified();
+        s.get_mut(entities[0]).unwrap().0 = 7;
+        s.maintain_fields();
+        assert!(s.field_changes(entities[0]));
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut reader, &mut modified);
+        assert!(modified.contains(entities[0].id()));
+    }
+
+    #[test]
+    fn diff_snapshot() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Hp(u32);
+        impl Component for Hp {
+            type Storage = DenseVecStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Hp>();
+        let entities: Vec<_> = (0..6)
+            .map(|i| w.create_entity().with(Hp(i)).build())
+            .collect();
+        let new = w.create_entity().build();
+
+        let mut s = w.write_storage::<Hp>();
+        let snapshot = s.snapshot();
+        assert_eq!(snapshot.get(entities[2].id()), Some(&Hp(2)));
+
+        s.get_mut(entities[1]).unwrap().0 = 10;
+        // accessed mutably, but unchanged
+        s.get_mut(entities[2]).unwrap().0 = 2;
+        s.remove(entities[3]);
+        s.insert(entities[4], Hp(40)).unwrap();
+        s.remove(entities[5]);
+        s.insert(entities[5], Hp(5)).unwrap();
+        s.insert(new, Hp(6)).unwrap();
+
+        let changes = s.diff_snapshot(&snapshot);
+        let ids = |set: &BitSet| set.iter().collect::<Vec<_>>();
+        assert_eq!(ids(&changes.inserted), vec![new.id()]);
+        assert_eq!(
+            ids(&changes.modified),
+            vec![entities[1].id(), entities[4].id()]
+        );
+        assert_eq!(ids(&changes.removed), vec![entities[3].id()]);
+    }
+
+    #[test]
+    fn flagged_remove_returns_value() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let e = w.create_entity().with(FlaggedCvec(1)).build();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut reader = s.track_removed();
+        s.insert(e, FlaggedCvec(2)).unwrap();
+        s.get_mut(e).unwrap().0 += 1;
+
+        assert_eq!(s.remove(e), Some(FlaggedCvec(3)));
+        assert_eq!(s.remove(e), None);
+
+        let mut removed = BitSet::new();
+        s.populate_removed(&mut reader, &mut removed);
+        assert_eq!(removed.iter().collect::<Vec<_>>(), vec![e.id()]);
+    }
+
+    #[test]
+    fn get_tracked_mut() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let a = w.create_entity().with(FlaggedCvec(1)).build();
+        let b = w.create_entity().with(FlaggedCvec(2)).build();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut reader = s.track_modified();
+        {
+            let guard = s.get_tracked_mut(a).unwrap();
+            assert_eq!(guard.0, 1);
+            assert!(!guard.is_modified());
+        }
+        {
+            let mut guard = s.get_tracked_mut(b).unwrap();
+            guard.0 += 10;
+            assert!(guard.is_modified());
+        }
+
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut reader, &mut modified);
+        assert!(!modified.contains(a.id()));
+        assert!(modified.contains(b.id()));
+        assert_eq!(s.get(b), Some(&FlaggedCvec(12)));
+    }
+
+    #[test]
+    fn changed_join() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let entities: Vec<_> = (0..4)
+            .map(|i| w.create_entity().with(FlaggedCvec(i)).build())
+            .collect();
+        let new = w.create_entity().build();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        let mut readers = s.track_all();
+        s.remove(entities[0]);
+        s.get_mut(entities[2]).unwrap().0 = 20;
+        s.insert(new, FlaggedCvec(4)).unwrap();
+        // replaced twice, which counts as a single modification
+        s.remove(entities[3]);
+        s.insert(entities[3], FlaggedCvec(3)).unwrap();
+        s.remove(entities[3]);
+        s.insert(entities[3], FlaggedCvec(30)).unwrap();
+
+        let changes: Vec<_> = s.changed_join(&mut readers).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (entities[0], ChangeKind::Removed, None),
+                (entities[2], ChangeKind::Modified, Some(&FlaggedCvec(20))),
+                (entities[3], ChangeKind::Modified, Some(&FlaggedCvec(30))),
+                (new, ChangeKind::Inserted, Some(&FlaggedCvec(4))),
+            ]
+        );
+
+        assert_eq!(s.changed_join(&mut readers).count(), 0);
+    }
+
+    #[test]
+    fn append() {
+        #[derive(Debug, PartialEq)]
+        struct Dense(u32);
+        impl Component for Dense {
+            type Storage = DenseVecStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Dense>();
+        let entities: Vec<_> = (0..10).map(|_| w.create_entity().build()).collect();
+
+        let mut s = w.write_storage::<Dense>();
+        for &e in entities.iter().filter(|e| e.id() % 3 != 1) {
+            s.append(e, Dense(e.id())).unwrap();
+        }
+
+        for &e in &entities {
+            let expected = if e.id() % 3 != 1 { Some(&Dense(e.id())) } else { None };
+            assert_eq!(s.get(e), expected);
+            assert_eq!(s.mask().contains(e.id()), expected.is_some());
+        }
+
+        // Appending below a removed component's index still works.
+        s.remove(entities[9]);
+        s.remove(entities[8]);
+        s.append(entities[7], Dense(70)).unwrap();
+        assert_eq!(s.get(entities[7]), Some(&Dense(70)));
+        assert_eq!(s.get(entities[6]), Some(&Dense(6)));
+        assert_eq!(s.get(entities[8]), None);
+        assert_eq!((&s).join().count(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "tried to append a component for an entity which already has one")]
+    fn append_existing() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        let e = w.create_entity().with(Cvec(1)).build();
+
+        w.write_storage::<Cvec>().append(e, Cvec(2)).unwrap();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "components have to be appended in ascending order")]
+    fn append_descending() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        let entities: Vec<_> = (0..200).map(|_| w.create_entity().build()).collect();
+
+        let mut s = w.write_storage::<Cvec>();
+        s.append(entities[130], Cvec(130)).unwrap();
+        s.append(entities[3], Cvec(3)).unwrap();
+    }
+
+    #[test]
+    fn move_to() {
+        #[derive(Debug, PartialEq)]
+        struct Dormant(u32);
+        impl Component for Dormant {
+            type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+        }
+        impl From<FlaggedCvec> for Dormant {
+            fn from(c: FlaggedCvec) -> Dormant {
+                Dormant(c.0)
+            }
+        }
+
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        w.register::<Dormant>();
+        let entities: Vec<_> = (0..6)
+            .map(|i| w.create_entity().with(FlaggedCvec(i)).build())
+            .collect();
+        let without = w.create_entity().build();
+
+        let mut active = w.write_storage::<FlaggedCvec>();
+        let mut dormant = w.write_storage::<Dormant>();
+        let mut removed = active.track_removed();
+        let mut inserted = dormant.track_inserted();
+
+        let mut mask = BitSet::new();
+        mask.add(entities[1].id());
+        mask.add(entities[3].id());
+        mask.add(without.id());
+        assert_eq!(active.move_to(&mut dormant, &mask), 2);
+
+        for (i, &e) in entities.iter().enumerate() {
+            let moved = i == 1 || i == 3;
+            assert_eq!(active.mask().contains(e.id()), !moved);
+            assert_eq!(dormant.mask().contains(e.id()), moved);
+        }
+        assert!(!dormant.contains(without));
+        assert_eq!(dormant.get(entities[3]), Some(&Dormant(3)));
+
+        let mut removed_ids = BitSet::new();
+        active.populate_removed(&mut removed, &mut removed_ids);
+        let mut inserted_ids = BitSet::new();
+        dormant.populate_inserted(&mut inserted, &mut inserted_ids);
+        let expected = vec![entities[1].id(), entities[3].id()];
+        assert_eq!(removed_ids.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(inserted_ids.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn view() {
+        #[repr(C)]
+        struct Pair([u32; 2]);
+        impl Component for Pair {
+            type Storage = VecStorage<Self>;
+        }
+
+        #[repr(C)]
+        #[derive(Debug, PartialEq)]
+        struct Point {
+            x: u32,
+            y: u32,
+        }
+        unsafe impl ViewOf<Pair> for Point {}
+
+        let mut w = World::new();
+        w.register::<Pair>();
+        let a = w.create_entity().with(Pair([1, 2])).build();
+        let b = w.create_entity().build();
+        let c = w.create_entity().with(Pair([3, 4])).build();
+
+        let pairs = w.read_storage::<Pair>();
+        let view = pairs.view::<Point>();
+        assert_eq!(view.get(a), Some(&Point { x: 1, y: 2 }));
+        assert_eq!(view.get(b), None);
+        assert!(view.contains(c));
+
+        let points: Vec<_> = pairs.view::<Point>().join().collect();
+        assert_eq!(points, vec![&Point { x: 1, y: 2 }, &Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "a view must have the same size as the component")]
+    fn view_size_mismatch() {
+        struct Small(u32);
+        impl Component for Small {
+            type Storage = VecStorage<Self>;
+        }
+
+        struct Large(u64);
+        unsafe impl ViewOf<Small> for Large {}
+
+        let mut w = World::new();
+        w.register::<Small>();
+        w.read_storage::<Small>().view::<Large>();
+    }
+
+    #[test]
+    fn changed_flags() {
+        #[derive(Debug, PartialEq)]
+        struct Other(u32);
+
+        impl Component for Other {
+            type Storage = FlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        w.register::<Other>();
+        let entities: Vec<_> = (0..4)
+            .map(|i| {
+                w.create_entity()
+                    .with(FlaggedCvec(i))
+                    .with(Other(i))
+                    .build()
+            })
+            .collect();
+        // not part of the join
+        let outside = w.create_entity().with(FlaggedCvec(4)).build();
+
+        let mut first = w.write_storage::<FlaggedCvec>();
+        let mut second = w.write_storage::<Other>();
+        let mut first_id = first.track_modified();
+        let mut second_id = second.track_modified();
+
+        first.get_mut(entities[1]).unwrap().0 += 1;
+        first.get_mut(entities[3]).unwrap().0 += 1;
+        second.get_mut(entities[2]).unwrap().0 += 1;
+        second.get_mut(entities[3]).unwrap().0 += 1;
+        first.get_mut(outside).unwrap();
+
+        let mut first_changed = BitSet::new();
+        let mut second_changed = BitSet::new();
+        first.populate_modified(&mut first_id, &mut first_changed);
+        second.populate_modified(&mut second_id, &mut second_changed);
+
+        let changed = ChangedFlags::new(&[&first_changed, &second_changed]);
+        let flags: Vec<_> = (&first, &second, changed)
+            .join()
+            .map(|(a, _, flags)| (a.0, flags))
+            .collect();
+        assert_eq!(flags, vec![(0, 0b00), (2, 0b01), (2, 0b10), (4, 0b11)]);
+    }
+
+    #[test]
+    fn apply() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<FlaggedCvec>();
+
+        let e = w.create_entity().with(Cvec(1)).with(FlaggedCvec(1)).build();
+        let empty = w.create_entity().build();
+
+        let mut plain = w.write_storage::<Cvec>();
+        let mut flagged = w.write_storage::<FlaggedCvec>();
+
+        let mut modified = BitSet::new();
+        let mut modified_id = flagged.track_modified();
+
+        assert!(!plain.apply(e, |c| c.0 += 1));
+        assert!(flagged.apply(e, |c| c.0 += 1));
+        assert!(!flagged.apply(empty, |_| panic!("called for missing component")));
+
+        assert_eq!(plain.get(e), Some(&Cvec(2)));
+        assert_eq!(flagged.get(e), Some(&FlaggedCvec(2)));
+
+        flagged.populate_modified(&mut modified_id, &mut modified);
+        assert!(modified.contains(e.id()));
+        assert!(!modified.contains(empty.id()));
+    }
+
+    #[test]
+    fn eq_contents_and_diff() {
+        let mut a = World::new();
+        let mut b = World::new();
+        a.register::<Cvec>();
+        b.register::<Cvec>();
+
+        for i in 0..5 {
+            a.create_entity().with(Cvec(i)).build();
+            b.create_entity().with(Cvec(i)).build();
+        }
+
+        {
+            let (sa, sb) = (a.read_storage::<Cvec>(), b.read_storage::<Cvec>());
+            assert!(sa.eq_contents(&sb));
+            assert!(sa.diff(&sb).is_empty());
+        }
+
+        let e1 = b.entities().entity(1);
+        let e3 = b.entities().entity(3);
+        *b.write_storage::<Cvec>().get_mut(e1).unwrap() = Cvec(10);
+        b.write_storage::<Cvec>().remove(e3);
+        let e5 = b.create_entity().with(Cvec(5)).build();
+
+        let (sa, sb) = (a.read_storage::<Cvec>(), b.read_storage::<Cvec>());
+        assert!(!sa.eq_contents(&sb));
+        assert!(!sb.eq_contents(&sa));
+
+        let diff: Vec<_> = sa.diff(&sb).iter().collect();
+        assert_eq!(diff, vec![1, 3, e5.id()]);
+    }
+
+    #[test]
+    fn field_flagged() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Body {
+            pos: u32,
+            vel: u32,
+        }
+
+        impl FieldDiff for Body {
+            type Fields = u8;
+
+            fn field_diff(old: &Self, new: &Self) -> u8 {
+                (old.pos != new.pos) as u8 | ((old.vel != new.vel) as u8) << 1
+            }
+        }
+
+        impl Component for Body {
+            type Storage = FieldFlaggedStorage<Self, VecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Body>();
+        let a = w.create_entity().with(Body { pos: 0, vel: 0 }).build();
+        let b = w.create_entity().with(Body { pos: 0, vel: 0 }).build();
+        let c = w.create_entity().with(Body { pos: 0, vel: 0 }).build();
+
+        let mut bodies = w.write_storage::<Body>();
+        let mut modified = BitSet::new();
+        let mut modified_id = bodies.track_modified();
+
+        bodies.get_mut(a).unwrap().pos = 1;
+        bodies.get_mut(b).unwrap().vel = 1;
+        // accessed mutably, but unchanged
+        bodies.get_mut(c).unwrap();
+        bodies.maintain_fields();
+
+        assert_eq!(bodies.field_changes(a), 0b01);
+        assert_eq!(bodies.field_changes(b), 0b10);
+        assert_eq!(bodies.field_changes(c), 0);
+
+        // changes accumulate until cleared
+        bodies.get_mut(a).unwrap().vel = 1;
+        bodies.maintain_fields();
+        assert_eq!(bodies.field_changes(a), 0b11);
+
+        bodies.clear_field_changes();
+        assert_eq!(bodies.field_changes(a), 0);
+
+        bodies.populate_modified(&mut modified_id, &mut modified);
+        assert!(modified.contains(a.id()) && modified.contains(b.id()) && modified.contains(c.id()));
+    }
+
+    #[test]
+    fn get_mut_silent() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let e = w.create_entity().with(FlaggedCvec(1)).build();
+
+        let mut s1 = w.write_storage::<FlaggedCvec>();
+        let mut modified = BitSet::new();
+        let mut modified_id = s1.track_modified();
+
+        s1.get_mut_silent(e).unwrap().0 = 2;
+        s1.populate_modified(&mut modified_id, &mut modified);
+        assert_eq!(s1.get(e), Some(&FlaggedCvec(2)));
+        assert!(!modified.contains(e.id()));
+
+        s1.get_mut(e).unwrap().0 = 3;
+        s1.populate_modified(&mut modified_id, &mut modified);
+        assert!(modified.contains(e.id()));
+    }
+
+    #[test]
+    fn set_modified() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Derived(u32);
+
+        impl FieldDiff for Derived {
+            type Fields = bool;
+
+            fn field_diff(_: &Self, _: &Self) -> bool {
+                panic!("`set_modified` must not diff")
+            }
+        }
+
+        impl Component for Derived {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Derived>();
+        let e = w.create_entity().build();
+
+        let mut derived = w.write_storage::<Derived>();
+        let mut readers = derived.track_all();
+
+        assert_eq!(derived.set_modified(e, Derived(1)).unwrap(), None);
+        assert_eq!(derived.set_modified(e, Derived(2)).unwrap(), Some(Derived(1)));
+        assert_eq!(derived.set_modified(e, Derived(3)).unwrap(), Some(Derived(2)));
+
+        let records: Vec<_> = derived
+            .change_records(&mut readers)
+            .map(|r| (r.kind, r.old.cloned(), r.current.cloned()))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (ChangeKind::Inserted, None, Some(Derived(3))),
+                (ChangeKind::Modified, None, Some(Derived(3))),
+                (ChangeKind::Modified, None, Some(Derived(3))),
+            ]
+        );
+
+        // nothing to diff
+        derived.maintain_fields();
+        assert!(!derived.field_changes(e));
+
+        let dead = w.entities().create();
+        w.entities().delete(dead).unwrap();
+        drop(derived);
+        w.maintain();
+        assert!(w.write_storage::<Derived>().set_modified(dead, Derived(0)).is_err());
+    }
+
+    #[test]
+    fn auto_reset_tracked() {
+        #[derive(Clone)]
+        struct Reset(u32);
+
+        #[derive(Clone)]
+        struct Keep(u32);
+
+        impl FieldDiff for Reset {
+            type Fields = u8;
+
+            fn field_diff(old: &Self, new: &Self) -> u8 {
+                (old.0 != new.0) as u8
+            }
+        }
+
+        impl FieldDiff for Keep {
+            type Fields = u8;
+
+            fn field_diff(old: &Self, new: &Self) -> u8 {
+                (old.0 != new.0) as u8
+            }
+        }
+
+        impl Component for Reset {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        impl Component for Keep {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Reset>();
+        w.register::<Keep>();
+        w.auto_reset_tracked::<Reset>();
+        w.auto_reset_tracked::<Reset>();
+
+        let e = w.create_entity().with(Reset(0)).with(Keep(0)).build();
+
+        {
+            let (mut reset, mut keep) = (w.write_storage::<Reset>(), w.write_storage::<Keep>());
+            reset.get_mut(e).unwrap().0 = 1;
+            keep.get_mut(e).unwrap().0 = 1;
+            reset.maintain_fields();
+            keep.maintain_fields();
+            assert_eq!(reset.field_changes(e), 1);
+            assert_eq!(keep.field_changes(e), 1);
+        }
+
+        w.maintain();
+
+        assert_eq!(w.read_storage::<Reset>().field_changes(e), 0);
+        assert_eq!(w.read_storage::<Keep>().field_changes(e), 1);
+
+        w.write_storage::<Keep>().reset_tracked();
+        assert_eq!(w.read_storage::<Keep>().field_changes(e), 0);
+    }
+
+    #[test]
+    fn token_storage() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<Cmap>();
+
+        let first = w.create_entity().with(Cvec(0)).with(Cmap(0)).build();
+        for i in 1..5 {
+            w.create_entity().with(Cvec(i)).with(Cmap(i)).build();
+        }
+
+        let maps = w.read_storage::<Cmap>();
+        let mut vecs = w.write_storage::<Cvec>();
+        let (tokens, mut writer) = vecs.tokens_mut();
+
+        let mut first_token = None;
+        for (token, map) in (tokens, &maps).join() {
+            if token.id() == first.id() {
+                first_token = Some(token);
+            } else {
+                // Accumulate into the first component while joining.
+                writer.get_mut(first_token.unwrap()).0 += map.0;
+            }
+        }
+
+        assert_eq!(writer.get(first_token.unwrap()).0, 1 + 2 + 3 + 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "token is not valid for this storage")]
+    fn token_storage_foreign_token() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<Cmap>();
+
+        w.create_entity().with(Cmap(0)).build();
+        w.create_entity().with(Cvec(0)).build();
+
+        let mut maps = w.write_storage::<Cmap>();
+        let mut vecs = w.write_storage::<Cvec>();
+        let token = maps.tokens_mut().0.join().next().unwrap();
+        let (_, writer) = vecs.tokens_mut();
+        writer.get(token);
+    }
+
+    #[test]
+    fn change_records() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Pos(u32);
+
+        impl FieldDiff for Pos {
+            type Fields = bool;
+
+            fn field_diff(old: &Self, new: &Self) -> bool {
+                old != new
+            }
+        }
+
+        impl Component for Pos {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Pos>();
+        let a = w.create_entity().with(Pos(1)).build();
+        let b = w.create_entity().with(Pos(2)).build();
+
+        let mut pos = w.write_storage::<Pos>();
+        let mut readers = pos.track_all();
+
+        let c = w.entities().create();
+        pos.insert(c, Pos(3)).unwrap();
+        pos.get_mut(a).unwrap().0 = 10;
+        pos.remove(b);
+
+        let records: Vec<_> = pos
+            .change_records(&mut readers)
+            .map(|r| (r.entity, r.kind, r.old.cloned(), r.current.cloned()))
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (c, ChangeKind::Inserted, None, Some(Pos(3))),
+                (a, ChangeKind::Modified, Some(Pos(1)), Some(Pos(10))),
+                (b, ChangeKind::Removed, None, None),
+            ]
+        );
+        assert_eq!(pos.change_records(&mut readers).count(), 0);
+
+        // Old values are gone after the fields have been diffed.
+        pos.get_mut(a).unwrap().0 = 11;
+        pos.maintain_fields();
+        let record = pos.change_records(&mut readers).next().unwrap();
+        assert_eq!(record.old, None);
+        assert_eq!(record.current, Some(&Pos(11)));
+    }
+
+    #[test]
+    fn change_records_rev() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Pos(u32);
+
+        impl FieldDiff for Pos {
+            type Fields = bool;
+
+            fn field_diff(old: &Self, new: &Self) -> bool {
+                old != new
+            }
+        }
+
+        impl Component for Pos {
+            type Storage = FieldFlaggedStorage<Self>;
+        }
+
+        let mut w = World::new();
+        w.register::<Pos>();
+        let entities: Vec<_> = (0..4).map(|i| w.create_entity().with(Pos(i)).build()).collect();
+
+        let mut pos = w.write_storage::<Pos>();
+        let mut forward_readers = pos.track_all();
+        let mut backward_readers = pos.track_all();
+
+        pos.get_mut(entities[2]).unwrap().0 = 20;
+        pos.remove(entities[0]);
+        pos.get_mut(entities[1]).unwrap().0 = 10;
+        pos.insert(entities[0], Pos(5)).unwrap();
+
+        let summarize = |r: ChangeRecord<Pos>| (r.entity, r.kind, r.current.cloned());
+        let forward: Vec<_> = pos.change_records(&mut forward_readers)
+            .map(&summarize)
+            .collect();
+        let mut backward: Vec<_> = pos.change_records(&mut backward_readers)
+            .rev()
+            .map(&summarize)
+            .collect();
+
+        assert_eq!(forward.len(), 4);
+        assert_eq!(backward[0], (entities[0], ChangeKind::Removed, None));
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn on_remove() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Handle(Arc<AtomicUsize>);
+
+        impl Component for Handle {
+            type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+
+            fn on_remove(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let released = Arc::new(AtomicUsize::new(0));
+        let mut w = World::new();
+        w.register::<Handle>();
+
+        let entities: Vec<_> = (0..4)
+            .map(|_| {
+                w.create_entity()
+                    .with(Handle(released.clone()))
+                    .build()
+            })
+            .collect();
+
+        {
+            let mut handles = w.write_storage::<Handle>();
+            let mut modified_id = handles.track_modified();
+            assert!(handles.remove(entities[0]).is_some());
+            assert_eq!(released.load(Ordering::SeqCst), 1);
+
+            // Teardown doesn't count as a modification.
+            let mut modified = BitSet::new();
+            handles.populate_modified(&mut modified_id, &mut modified);
+            assert!(modified.is_empty());
+        }
+
+        w.delete_entity(entities[1]).unwrap();
+        w.maintain();
+        assert_eq!(released.load(Ordering::SeqCst), 2);
+
+        drop(w);
+        assert_eq!(released.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn values_mut() {
+        #[derive(Debug, PartialEq)]
+        struct Dense(u32);
+
+        impl Component for Dense {
+            type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Dense>();
+        let entities: Vec<_> = (0..5)
+            .map(|i| w.create_entity().with(Dense(i)).build())
+            .collect();
+        w.write_storage::<Dense>().remove(entities[1]);
+
+        let mut dense = w.write_storage::<Dense>();
+        let mut modified_id = dense.track_modified();
+        for value in dense.values_mut() {
+            value.0 += 10;
+        }
+
+        let mut modified = BitSet::new();
+        dense.populate_modified(&mut modified_id, &mut modified);
+        assert!((&modified ^ dense.mask()).iter().next().is_none());
+
+        let ids = dense.entities_slice().to_vec();
+        assert_eq!(ids.len(), 4);
+        for (&id, value) in ids.iter().zip(dense.values()) {
+            let e = w.entities().entity(id);
+            assert_eq!(dense.get(e), Some(value));
+            assert_eq!(value.0, id + 10);
+        }
+    }
+
+    #[test]
+    fn generation_checked() {
+        #[derive(Debug, PartialEq)]
+        struct Name(u32);
+        impl Component for Name {
+            type Storage = GenerationStorage<Self, VecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Name>();
+        let old = w.create_entity().with(Name(1)).build();
+        w.delete_entity(old).unwrap();
+        w.maintain();
+        let new = w.create_entity().with(Name(2)).build();
+        assert_eq!(old.id(), new.id());
+
+        let mut s = w.write_storage::<Name>();
+        assert_eq!(s.get_checked(old), None);
+        assert!(s.get_mut_checked(old).is_none());
+        assert_eq!(s.get_checked(new), Some(&Name(2)));
+        s.get_mut_checked(new).unwrap().0 = 3;
+        assert_eq!(s.get(new), Some(&Name(3)));
+
+        // a handle with the generation of the replaced entity doesn't match
+        // either, even when the component is replaced
+        s.insert(new, Name(4)).unwrap();
+        assert_eq!(s.get_checked(old), None);
+        assert_eq!(s.get_checked(new), Some(&Name(4)));
+
+        // components moved by index only have no generation
+        drop(s);
+        let moved = w.create_entity().with(Name(5)).build();
+        w.delete_entity(new).unwrap();
+        let remap = w.compact();
+        let moved = remap[&moved];
+        let s = w.read_storage::<Name>();
+        assert_eq!(s.get(moved), Some(&Name(5)));
+        assert_eq!(s.get_checked(moved), None);
+    }
+
+    #[test]
+    fn versioned_storage() {
+        #[derive(Debug, PartialEq)]
+        struct Mesh {
+            vertices: Vec<u32>,
+            version: u64,
+        }
+        impl Versioned for Mesh {
+            fn version(&self) -> u64 {
+                self.version
+            }
+        }
+        impl Component for Mesh {
+            type Storage = VersionedStorage<Self, VecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Mesh>();
+        let mut readers = w.write_storage::<Mesh>().track_all();
+        let entities: Vec<_> = (0..4)
+            .map(|i| {
+                w.create_entity()
+                    .with(Mesh {
+                        vertices: vec![i],
+                        version: 0,
+                    })
+                    .build()
+            })
+            .collect();
+
+        let mut s = w.write_storage::<Mesh>();
+        assert_eq!(s.maintain_versions(), 0);
+        let net = s.net_changes(&mut readers);
+        assert_eq!(net.inserted.iter().count(), 4);
+
+        // mutable access alone isn't a modification ...
+        for mesh in (&mut s).join() {
+            mesh.vertices.push(0);
+        }
+        // ... but bumping the version is
+        s.get_mut(entities[1]).unwrap().version += 1;
+        s.get_mut(entities[3]).unwrap().version = 7;
+        assert!(s.modified().read(&mut readers.modify).next().is_none());
+
+        assert_eq!(s.maintain_versions(), 2);
+        let net = s.net_changes(&mut readers);
+        assert_eq!(net.modified.iter().collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(s.maintain_versions(), 0);
+
+        s.remove(entities[0]);
+        let replaced = Mesh {
+            vertices: vec![],
+            version: 9,
+        };
+        s.insert(entities[0], replaced).unwrap();
+        assert_eq!(s.maintain_versions(), 0);
+        let net = s.net_changes(&mut readers);
+        assert_eq!(net.modified.iter().collect::<Vec<_>>(), vec![0]);
+
+        // only components accessed with `get_mut` are compared
+        s.get_mut(entities[2]).unwrap().version += 1;
+        s.remove(entities[2]);
+        s.get_mut_silent(entities[1]).unwrap().version += 1;
+        assert_eq!(s.maintain_versions(), 0);
+    }
+
+    #[test]
+    fn get_pair_mut() {
+        #[derive(Debug, PartialEq)]
+        struct Vel(i32, i32);
+        impl Component for Vel {
+            type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Vel>();
+        let a = w.create_entity().with(Vel(2, 0)).build();
+        let b = w.create_entity().with(Vel(-1, 1)).build();
+        let c = w.create_entity().with(Vel(0, 0)).build();
+        let without = w.create_entity().build();
+
+        let mut s = w.write_storage::<Vel>();
+        let mut reader = s.track_modified();
+
+        // an elastic collision of equal masses swaps the velocities
+        {
+            let (va, vb) = s.get_pair_mut(a, b).unwrap();
+            std::mem::swap(va, vb);
+        }
+        assert_eq!(s.get(a), Some(&Vel(-1, 1)));
+        assert_eq!(s.get(b), Some(&Vel(2, 0)));
+
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut reader, &mut modified);
+        assert!(modified.contains(a.id()));
+        assert!(modified.contains(b.id()));
+        assert!(!modified.contains(c.id()));
+
+        assert!(s.get_pair_mut(a, a).is_none());
+        assert!(s.get_pair_mut(a, without).is_none());
+        assert!(s.get_pair_mut(without, c).is_none());
+
+        let expected = if cfg!(feature = "stats") { 4 } else { 0 };
+        assert_eq!(s.stats().get_muts, expected);
+    }
+
+    #[test]
+    fn version_count_storage() {
+        struct Mesh(Vec<u32>);
+        impl Component for Mesh {
+            type Storage = VersionCountStorage<Self, VecStorage<Self>>;
+        }
+
+        let mut w = World::new();
+        w.register::<Mesh>();
+        let a = w.create_entity().with(Mesh(vec![])).build();
+        let b = w.create_entity().with(Mesh(vec![])).build();
+        let c = w.create_entity().build();
+
+        let mut s = w.write_storage::<Mesh>();
+        let va = s.version(a).unwrap();
+        let vb = s.version(b).unwrap();
+        assert_eq!(s.version(c), None);
+
+        // reading doesn't bump the version
+        assert_eq!(s.get(a).unwrap().0.len(), 0);
+        for mesh in (&s).join() {
+            assert!(mesh.0.is_empty());
+        }
+        assert_eq!(s.version(a), Some(va));
+        assert_eq!(s.version(b), Some(vb));
+
+        // mutating does
+        s.get_mut(a).unwrap().0.push(1);
+        assert!(s.version(a).unwrap() > va);
+        assert_eq!(s.version(b), Some(vb));
+
+        let va = s.version(a).unwrap();
+        for mesh in (&mut s).join() {
+            mesh.0.push(2);
+        }
+        assert!(s.version(a).unwrap() > va);
+        assert!(s.version(b).unwrap() > vb);
+
+        // the version keeps growing after reinsertion
+        let vb = s.version(b).unwrap();
+        s.remove(b);
+        assert_eq!(s.version(b), None);
+        s.insert(b, Mesh(vec![])).unwrap();
+        assert!(s.version(b).unwrap() > vb);
+    }
+
+    #[test]
+    fn change_throttle() {
+        use world::FrameCounter;
+
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let mut modified = w.write_storage::<FlaggedCvec>().track_modified();
+        let hot = w.create_entity().with(FlaggedCvec(0)).build();
+        let cold = w.create_entity().with(FlaggedCvec(0)).build();
+
+        let mut throttle = ChangeThrottle::new(3);
+        let mut fired = Vec::new();
+        for frame in 0..8 {
+            {
+                let mut s = w.write_storage::<FlaggedCvec>();
+                // `hot` changes every frame, `cold` only in frame 4
+                s.get_mut(hot).unwrap().0 += 1;
+                if frame == 4 {
+                    s.get_mut(cold).unwrap().0 += 1;
+                }
+
+                let mut changed = BitSet::new();
+                s.populate_modified(&mut modified, &mut changed);
+                let frame = w.read_resource::<FrameCounter>().frame();
+                let due = throttle.update(&changed, frame);
+                for e in &[hot, cold] {
+                    if due.contains(e.id()) {
+                        fired.push((frame, e.id()));
+                    }
+                }
+            }
+            w.maintain();
+        }
+
+        assert_eq!(
+            fired,
+            vec![(0, hot.id()), (3, hot.id()), (4, cold.id()), (6, hot.id())]
+        );
+
+        // a change which wasn't due is returned later
+        let mut changed = BitSet::new();
+        changed.add(hot.id());
+        assert!(throttle.update(&changed, 7).is_empty());
+        assert!(throttle.pending().contains(hot.id()));
+        assert!(throttle.update(&BitSet::new(), 9).contains(hot.id()));
+        assert!(throttle.pending().is_empty());
+
+        throttle.forget(hot.id());
+        assert!(throttle.update(&changed, 10).contains(hot.id()));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_iter_mut() {
+        use rayon::iter::ParallelIterator;
+
+        let mut w = World::new();
+        w.register::<Cvec>();
+        for i in 0..10_000 {
+            let e = w.create_entity().build();
+            if i % 7 != 0 {
+                w.write_storage::<Cvec>().insert(e, Cvec(i)).unwrap();
+            }
+        }
+
+        let serial: Vec<_> = {
+            let s = w.read_storage::<Cvec>();
+            (&s).join().map(|c| c.0 * 3 + 1).collect()
+        };
+        {
+            let mut s = w.write_storage::<Cvec>();
+            s.par_iter_mut().for_each(|(id, c)| {
+                assert_eq!(c.0, id);
+                c.0 = c.0 * 3 + 1;
+            });
+        }
+
+        let s = w.read_storage::<Cvec>();
+        let parallel: Vec<_> = (&s).join().map(|c| c.0).collect();
+        assert_eq!(parallel, serial);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_iter_mut_flagged() {
+        use rayon::iter::ParallelIterator;
+
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let mut reader = w.write_storage::<FlaggedCvec>().track_modified();
+        let entities: Vec<_> = (0..1_000)
+            .map(|i| w.create_entity().with(FlaggedCvec(i)).build())
+            .collect();
+
+        let mut s = w.write_storage::<FlaggedCvec>();
+        s.par_iter_mut().for_each(|(id, c)| {
+            assert_eq!(c.0, id);
+            c.0 += 1;
+        });
+
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut reader, &mut modified);
+        assert_eq!((&modified).iter().count(), entities.len());
+        for &e in &entities {
+            assert!(modified.contains(e.id()));
+            assert_eq!(s.get(e).unwrap().0, e.id() + 1);
+        }
+
+        // the recorded indices are cleared once the events are written
+        s.par_iter_mut().for_each(|_| ());
+        let mut modified = BitSet::new();
+        s.populate_modified(&mut reader, &mut modified);
+        assert_eq!((&modified).iter().count(), entities.len());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_insert() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<FlaggedCvec>();
+        let entities: Vec<_> = (0..1_000).map(|_| w.create_entity().build()).collect();
+        let dead = w.create_entity().build();
+        w.delete_entity(dead).unwrap();
+
+        let mut inserted = w.write_storage::<FlaggedCvec>().track_inserted();
+        {
+            let mut ints = w.write_storage::<Cvec>();
+            let mut flagged = w.write_storage::<FlaggedCvec>();
+            ParInsert::new()
+                .with(&mut ints, entities.iter().map(|&e| (e, Cvec(e.id()))).collect())
+                .with(
+                    &mut flagged,
+                    entities.iter().rev().map(|&e| (e, FlaggedCvec(e.id()))).collect(),
+                )
+                .run()
+                .unwrap();
+
+            assert!(entities.iter().all(|&e| ints.get(e) == Some(&Cvec(e.id()))));
+            assert!(
+                entities
+                    .iter()
+                    .all(|&e| flagged.get(e) == Some(&FlaggedCvec(e.id())))
+            );
+            assert_eq!(
+                flagged.inserted().read(&mut inserted).count(),
+                entities.len()
+            );
+        }
+
+        let mut ints = w.write_storage::<Cvec>();
+        let mut flagged = w.write_storage::<FlaggedCvec>();
+        let result = ParInsert::new()
+            .with(&mut ints, vec![(dead, Cvec(0))])
+            .with(&mut flagged, vec![(entities[0], FlaggedCvec(7))])
+            .run();
+        assert!(result.is_err());
+        assert_eq!(ints.get(dead), None);
+        assert_eq!(flagged.get(entities[0]), Some(&FlaggedCvec(7)));
+    }
+
+    #[test]
+    fn get_with_change() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+        let mut readers = w.write_storage::<FlaggedCvec>().track_all();
+        let unchanged = w.create_entity().with(FlaggedCvec(1)).build();
+        let modified = w.create_entity().with(FlaggedCvec(2)).build();
+        let absent = w.create_entity().build();
+        w.read_storage::<FlaggedCvec>().net_changes(&mut readers);
+
+        let inserted = w.create_entity().with(FlaggedCvec(3)).build();
+        w.write_storage::<FlaggedCvec>()
+            .get_mut(modified)
+            .unwrap()
+            .0 = 4;
+
+        let s = w.read_storage::<FlaggedCvec>();
+        let changes = s.net_changes(&mut readers);
+        assert_eq!(
+            s.get_with_change(unchanged, &changes),
+            Some((&FlaggedCvec(1), None))
+        );
+        assert_eq!(
+            s.get_with_change(inserted, &changes),
+            Some((&FlaggedCvec(3), Some(ChangeKind::Inserted)))
+        );
+        assert_eq!(
+            s.get_with_change(modified, &changes),
+            Some((&FlaggedCvec(4), Some(ChangeKind::Modified)))
+        );
+        assert_eq!(s.get_with_change(absent, &changes), None);
+    }
 }
@@ -0,0 +1,96 @@
+use fnv::FnvHashMap;
+use hibitset::{BitSet, BitSetLike};
+
+use world::Index;
+
+/// Throttles reactions to changed components, so each entity is reacted to
+/// at most once every `interval` frames.
+///
+/// Every frame, `update` is given the set of changed entities (e.g. the
+/// modified components read from a tracked storage, collected into a
+/// `BitSet`) along with the frame number, usually from `FrameCounter`. It
+/// returns the entities which are due: those which changed and haven't
+/// been returned in the last `interval` frames. Changes of entities which
+/// aren't due are kept, and the entities are returned as soon as they are
+/// due, so the last change is never lost.
+///
+/// This keeps components modified every frame from running an expensive
+/// reactive system every frame for every entity.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::ChangeThrottle;
+///
+/// let mut throttle = ChangeThrottle::new(3);
+/// let mut changed = BitSet::new();
+/// changed.add(7);
+///
+/// // changed every frame, but only due every third frame
+/// let due: Vec<_> = (0..7)
+///     .filter(|&frame| throttle.update(&changed, frame).contains(7))
+///     .collect();
+/// assert_eq!(due, vec![0, 3, 6]);
+/// ```
+pub struct ChangeThrottle {
+    interval: u64,
+    last_fired: FnvHashMap<Index, u64>,
+    pending: BitSet,
+}
+
+impl ChangeThrottle {
+    /// Creates a throttle returning each entity at most once every
+    /// `interval` frames; an interval of `0` or `1` returns every change
+    /// right away.
+    pub fn new(interval: u64) -> Self {
+        ChangeThrottle {
+            interval,
+            last_fired: FnvHashMap::default(),
+            pending: BitSet::new(),
+        }
+    }
+
+    /// Records the entities which changed in `frame`, and returns the ones
+    /// which are due, including those which changed in an earlier frame
+    /// but weren't due back then.
+    ///
+    /// Frames are expected to increase from call to call.
+    pub fn update(&mut self, changed: &BitSet, frame: u64) -> BitSet {
+        for id in changed.iter() {
+            self.pending.add(id);
+        }
+
+        let mut due = BitSet::new();
+        for id in (&self.pending).iter() {
+            let ready = match self.last_fired.get(&id) {
+                Some(&last) => frame.wrapping_sub(last) >= self.interval,
+                None => true,
+            };
+            if ready {
+                due.add(id);
+            }
+        }
+
+        for id in (&due).iter() {
+            self.pending.remove(id);
+            self.last_fired.insert(id, frame);
+        }
+
+        due
+    }
+
+    /// Returns the entities which changed, but haven't been returned by
+    /// `update` yet.
+    pub fn pending(&self) -> &BitSet {
+        &self.pending
+    }
+
+    /// Forgets everything about the entity at `id`, e.g. after it has
+    /// been deleted, so a new entity with the same index is returned
+    /// right away.
+    pub fn forget(&mut self, id: Index) {
+        self.pending.remove(id);
+        self.last_fired.remove(&id);
+    }
+}
@@ -1,11 +1,15 @@
+use std;
 use std::iter::Extend;
 use std::ops::{Deref, DerefMut};
 
+use fnv::FnvHashMap;
+use hibitset::{BitIter, BitSet, BitSetAll, BitSetAnd, BitSetLike, BitSetOr};
 use shrev::{EventChannel, ReaderId};
 
-use join::Join;
-use storage::{MaskedStorage, Storage};
-use world::{Component, Index};
+use error::{Error, WrongGeneration};
+use join::{Join, ParJoin};
+use storage::{InsertResult, MaskedStorage, Storage, UnprotectedStorage};
+use world::{Component, EntitiesRes, Entity, Index};
 
 /// `UnprotectedStorage`s that track modifications, insertions, and
 /// removals of components.
@@ -14,6 +18,25 @@ pub trait Tracked {
     fn channels(&self) -> &TrackChannels;
     /// Mutable event channels tracking modified/inserted/removed components.
     fn channels_mut(&mut self) -> &mut TrackChannels;
+
+    /// Clears the change state the storage accumulates itself, e.g. the
+    /// changed fields of a `FieldFlaggedStorage`. Events which were already
+    /// sent through the channels stay readable.
+    ///
+    /// Does nothing by default.
+    fn reset(&mut self) {}
+
+    /// Frees memory of the change state the storage accumulates itself,
+    /// dropping the state of indices not in `mask` (the indices which have
+    /// a component) and shrinking its allocations. Returns by how many
+    /// entries the capacity of the change state shrank.
+    ///
+    /// Does nothing and returns `0` by default. `FlaggedStorage` keeps no
+    /// state per index, only its event channels, whose buffers can't be
+    /// shrunk, so it uses the default.
+    fn compact(&mut self, _mask: &BitSet) -> usize {
+        0
+    }
 }
 
 /// All three types of tracked modifications to components.
@@ -31,6 +54,7 @@ pub struct TrackChannels {
     pub insert: EventChannel<InsertedFlag>,
     /// Removed event channel.
     pub remove: EventChannel<RemovedFlag>,
+    suppressed: bool,
 }
 
 impl TrackChannels {
@@ -38,6 +62,42 @@ impl TrackChannels {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Writes a modification event for `id`, unless tracking is suppressed.
+    pub fn flag_modified(&mut self, id: Index) {
+        if !self.suppressed {
+            self.modify.single_write(id.into());
+        }
+    }
+
+    /// Writes an insertion event for `id`, unless tracking is suppressed.
+    pub fn flag_inserted(&mut self, id: Index) {
+        if !self.suppressed {
+            self.insert.single_write(id.into());
+        }
+    }
+
+    /// Writes a removal event for `id`, unless tracking is suppressed.
+    pub fn flag_removed(&mut self, id: Index) {
+        if !self.suppressed {
+            self.remove.single_write(id.into());
+        }
+    }
+
+    /// Returns `true` if the `flag_*` methods discard events instead of
+    /// writing them, see `World::suppress_tracking`.
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed
+    }
+
+    /// Makes the `flag_*` methods discard events, or write them again.
+    ///
+    /// Storages have to write their events through these methods for this
+    /// to have an effect; events written to the channels directly are
+    /// never discarded.
+    pub fn set_suppressed(&mut self, suppressed: bool) {
+        self.suppressed = suppressed;
+    }
 }
 
 impl Default for TrackChannels {
@@ -46,10 +106,143 @@ impl Default for TrackChannels {
             modify: EventChannel::new(),
             insert: EventChannel::new(),
             remove: EventChannel::new(),
+            suppressed: false,
+        }
+    }
+}
+
+/// Readers for all three `TrackChannels` of a storage.
+///
+/// Returned by `Storage::track_all`.
+pub struct TrackReaders {
+    /// Reader of the modifications event channel.
+    pub modify: ReaderId<ModifiedFlag>,
+    /// Reader of the insertions event channel.
+    pub insert: ReaderId<InsertedFlag>,
+    /// Reader of the removals event channel.
+    pub remove: ReaderId<RemovedFlag>,
+}
+
+/// The kind of change done to a component of a tracked storage.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ChangeKind {
+    /// The component was inserted; see `InsertedFlag`.
+    Inserted,
+    /// The component was modified; see `ModifiedFlag`.
+    Modified,
+    /// The component was removed; see `RemovedFlag`.
+    Removed,
+}
+
+/// The net effect of all changes read from a tracked storage, returned by
+/// `Storage::net_changes` (or of all changes since a snapshot, returned by
+/// `Storage::diff_snapshot`).
+///
+/// Every index is in at most one of the sets.
+#[derive(Debug, Default)]
+pub struct NetChanges {
+    /// Components which didn't exist before and exist now.
+    pub inserted: BitSet,
+    /// Components which existed before and still exist, but have been
+    /// modified or replaced.
+    pub modified: BitSet,
+    /// Components which existed before and don't exist anymore.
+    pub removed: BitSet,
+}
+
+impl NetChanges {
+    /// Returns how the component at `id` changed, or `None` if it didn't.
+    pub fn kind(&self, id: Index) -> Option<ChangeKind> {
+        if self.inserted.contains(id) {
+            Some(ChangeKind::Inserted)
+        } else if self.modified.contains(id) {
+            Some(ChangeKind::Modified)
+        } else if self.removed.contains(id) {
+            Some(ChangeKind::Removed)
+        } else {
+            None
+        }
+    }
+}
+
+/// A `Join`-able structure yielding, for every index, which of a list of
+/// bitsets contain it, as flags.
+///
+/// Bit `i` of the flags is set if the index is in the `i`th bitset. This
+/// is meant to be joined together with several tracked storages, passing
+/// the bitsets their changes have been collected into (e.g. with
+/// `Storage::populate_modified`), to find out which of the joined
+/// components changed for an entity.
+///
+/// Since every index is yielded, this has to be joined with something
+/// constraining the indices, just like a `MaybeJoin`.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::storage::ChangedFlags;
+///
+/// # struct Pos(f32); impl Component for Pos { type Storage = FlaggedStorage<Self>; }
+/// # struct Vel(f32); impl Component for Vel { type Storage = FlaggedStorage<Self>; }
+/// struct Speed(f32);
+/// # impl Component for Speed { type Storage = VecStorage<Self>; }
+///
+/// fn recompute(pos: &ReadStorage<Pos>, vel: &ReadStorage<Vel>, speed: &mut WriteStorage<Speed>,
+///              pos_changed: &BitSet, vel_changed: &BitSet) {
+///     let changed = ChangedFlags::new(&[pos_changed, vel_changed]);
+///     for (_, vel, speed, flags) in (pos, vel, speed, changed).join() {
+///         // Only `Vel` matters, so `Pos` changes (`0b01`) are ignored.
+///         if flags & 0b10 != 0 {
+///             speed.0 = vel.0.abs();
+///         }
+///     }
+/// }
+/// ```
+pub struct ChangedFlags<'a> {
+    sets: Vec<&'a BitSet>,
+}
+
+impl<'a> ChangedFlags<'a> {
+    /// Creates flags over the given bitsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than 32 bitsets.
+    pub fn new(sets: &[&'a BitSet]) -> Self {
+        assert!(sets.len() <= 32, "at most 32 bitsets can be used as flags");
+
+        ChangedFlags {
+            sets: sets.to_vec(),
         }
     }
 }
 
+impl<'a> Join for ChangedFlags<'a> {
+    type Type = u32;
+    type Value = Vec<&'a BitSet>;
+    type Mask = BitSetAll;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (BitSetAll, self.sets)
+    }
+
+    unsafe fn get(sets: &mut Self::Value, id: Index) -> u32 {
+        sets.iter()
+            .enumerate()
+            .filter(|&(_, set)| set.contains(id))
+            .fold(0, |flags, (i, _)| flags | 1 << i)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        true
+    }
+}
+
+// `get` only reads the bitsets.
+unsafe impl<'a> ParJoin for ChangedFlags<'a> {}
+
 impl<'e, T, D> Storage<'e, T, D>
 where
     T: Component,
@@ -99,6 +292,91 @@ where
     {
         value.extend(self.removed().read(reader_id).map(|flag| *flag.as_ref()));
     }
+
+    /// Reads all pending events and collapses them into their net effect
+    /// for each index, e.g. a component which has been inserted and removed
+    /// again since the events have last been read is not reported at all,
+    /// and one which has been removed and inserted again counts as modified.
+    ///
+    /// This relies on insertions and removals of an index alternating, which
+    /// is the case unless they're flagged manually (with `flag_inserted` or
+    /// `flag_removed`).
+    pub fn net_changes(&self, readers: &mut TrackReaders) -> NetChanges {
+        let mut balance = FnvHashMap::<Index, i64>::default();
+        for flag in self.inserted().read(&mut readers.insert) {
+            *balance.entry(**flag).or_insert(0) += 1;
+        }
+        for flag in self.removed().read(&mut readers.remove) {
+            *balance.entry(**flag).or_insert(0) -= 1;
+        }
+        let mut touched = BitSet::new();
+        self.populate_modified(&mut readers.modify, &mut touched);
+
+        let mask = self.mask();
+        let mut net = NetChanges::default();
+        for (id, balance) in balance {
+            if balance > 0 {
+                net.inserted.add(id);
+            } else if balance < 0 {
+                net.removed.add(id);
+            } else if mask.contains(id) {
+                // removed and inserted again
+                touched.add(id);
+            }
+        }
+        for id in touched.iter() {
+            if mask.contains(id) && !net.inserted.contains(id) {
+                net.modified.add(id);
+            }
+        }
+
+        net
+    }
+
+    /// Reads all pending events and iterates over the net changes (see
+    /// `net_changes`) in ascending order of their indices, together with
+    /// the current component.
+    ///
+    /// The component is `None` for removals, since removed components
+    /// aren't retained. This is meant for processing all changes in a
+    /// single pass, e.g. for replicating them.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// use specs::storage::ChangeKind;
+    ///
+    /// # #[derive(Debug, PartialEq)]
+    /// struct Health(u32);
+    /// impl Component for Health {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// let mut readers = health.track_all();
+    /// let e = world.entities().create();
+    /// health.insert(e, Health(10)).unwrap();
+    ///
+    /// let changes: Vec<_> = health.changed_join(&mut readers).collect();
+    /// assert_eq!(changes, vec![(e, ChangeKind::Inserted, Some(&Health(10)))]);
+    /// ```
+    pub fn changed_join(&self, readers: &mut TrackReaders) -> ChangedJoin<T> {
+        let net = self.net_changes(readers);
+        let mut ids = BitSet::new();
+        ids.extend(BitSetOr(BitSetOr(&net.inserted, &net.modified), &net.removed).iter());
+
+        ChangedJoin {
+            ids: ids.iter(),
+            net,
+            mask: &self.data.mask,
+            storage: &self.data.inner,
+            entities: &self.entities,
+        }
+    }
 }
 
 impl<'e, T, D> Storage<'e, T, D>
@@ -127,6 +405,92 @@ where
         &mut self.channels_mut().remove
     }
 
+    /// Tries to mutate the data associated with an `Entity` without
+    /// flagging it as modified.
+    ///
+    /// This is meant for internal bookkeeping which shouldn't be visible as a
+    /// change. Keep in mind that any real change done through the returned
+    /// reference won't be tracked either, so readers of the modification
+    /// events won't see it.
+    pub fn get_mut_silent(&mut self, e: Entity) -> Option<&mut T> {
+        if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
+            Some(unsafe { self.data.inner.get_mut_silent(e.id()) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a guard for mutating the component of an entity, which only
+    /// flags it as modified if it has actually been accessed mutably.
+    ///
+    /// Unlike `get_mut`, which flags a component as soon as it's fetched,
+    /// the guard records the modification when it's dropped, and only if it
+    /// has been dereferenced mutably. This avoids flagging components that
+    /// are only conditionally modified, without comparing their values.
+    ///
+    /// The changed fields of a `FieldFlaggedStorage` aren't recorded this
+    /// way, only the modification event.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Health(u32);
+    /// impl Component for Health {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// let e = world.create_entity().with(Health(120)).build();
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// if let Some(mut h) = health.get_tracked_mut(e) {
+    ///     if h.0 > 100 {
+    ///         h.0 = 100;
+    ///     }
+    /// };
+    /// ```
+    pub fn get_tracked_mut(&mut self, e: Entity) -> Option<TrackedMut<T>> {
+        if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
+            Some(TrackedMut {
+                storage: &mut self.data.inner,
+                id: e.id(),
+                modified: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Inserts a component, always flagging it as modified if the entity
+    /// already has one, and returns the replaced component.
+    ///
+    /// Unlike `insert`, this never accesses the old component through
+    /// `get_mut`, so storages which diff mutably accessed components (like
+    /// `FieldFlaggedStorage`) don't need to clone and compare it. This is
+    /// meant for derived components which are recomputed every frame anyway.
+    /// If the entity doesn't have the component yet, it's flagged as
+    /// inserted.
+    pub fn set_modified(&mut self, e: Entity, mut v: T) -> InsertResult<T> {
+        if !self.data.mask.contains(e.id()) {
+            return self.insert(e, v).map(|_| None);
+        }
+
+        if !self.entities.is_alive(e) {
+            return Err(Error::WrongGeneration(WrongGeneration {
+                action: "insert component for entity",
+                actual_gen: self.entities.entity(e.id()).gen(),
+                entity: e,
+            }));
+        }
+
+        std::mem::swap(&mut v, unsafe { self.data.inner.get_mut_silent(e.id()) });
+        self.flag_modified(e.id());
+
+        Ok(Some(v))
+    }
+
     /// Starts tracking modified events.
     pub fn track_modified(&mut self) -> ReaderId<ModifiedFlag> {
         self.modified_mut().register_reader()
@@ -142,19 +506,191 @@ where
         self.removed_mut().register_reader()
     }
 
+    /// Starts tracking all three kinds of events.
+    pub fn track_all(&mut self) -> TrackReaders {
+        TrackReaders {
+            modify: self.track_modified(),
+            insert: self.track_inserted(),
+            remove: self.track_removed(),
+        }
+    }
+
     /// Flags an index as modified.
     pub fn flag_modified(&mut self, id: Index) {
-        self.modified_mut().single_write(id.into());
+        self.channels_mut().flag_modified(id);
     }
 
     /// Flags an index as inserted.
     pub fn flag_inserted(&mut self, id: Index) {
-        self.inserted_mut().single_write(id.into());
+        self.channels_mut().flag_inserted(id);
     }
 
     /// Flags an index as removed.
     pub fn flag_removed(&mut self, id: Index) {
-        self.removed_mut().single_write(id.into());
+        self.channels_mut().flag_removed(id);
+    }
+
+    /// Reads all pending events and iterates over the components which
+    /// have been inserted or modified since, see `Storage::net_changes`,
+    /// handing out mutable references.
+    ///
+    /// Accessing the components this way doesn't flag them as modified
+    /// again, so a system can update exactly the changed components without
+    /// seeing its own changes the next time it runs. Like with
+    /// `get_mut_silent`, changes done here aren't visible to other readers;
+    /// use `flag_modified` if they should be.
+    pub fn changed_mut(&mut self, readers: &mut TrackReaders) -> ChangedMut<T> {
+        let net = self.net_changes(readers);
+        let mut changed = BitSet::new();
+        // Manually flagged insertions might not exist anymore.
+        changed.extend(BitSetAnd(&net.inserted, self.mask()).iter());
+        changed.extend(net.modified.iter());
+
+        ChangedMut {
+            ids: changed.iter(),
+            storage: &mut self.data.inner,
+            entities: &self.entities,
+        }
+    }
+
+    /// Resets the accumulated change state of the storage, see
+    /// `Tracked::reset`.
+    ///
+    /// Use `World::auto_reset_tracked` to have this called on every
+    /// `World::maintain`.
+    ///
+    /// This never drops events: insertions, modifications and removals stay
+    /// readable through each `ReaderId` until that reader has read them, no
+    /// matter how many resets happen in between. A system handling removals
+    /// can therefore run at the start of the next frame, after the storage
+    /// has been reset, and still see every removal.
+    pub fn reset_tracked(&mut self) {
+        unsafe { self.open() }.1.reset();
+    }
+
+    /// Frees memory the storage uses for tracking changes, which otherwise
+    /// stays allocated after many components have been changed or removed,
+    /// see `Tracked::compact`. Returns by how many entries the capacity of
+    /// the change state shrank, i.e. `0` if there was nothing to free.
+    ///
+    /// Only storages keeping per-component state, like
+    /// `FieldFlaggedStorage`, have anything to free; for a `FlaggedStorage`
+    /// this always returns `0`.
+    ///
+    /// Changes of the remaining components are still tracked correctly.
+    pub fn compact_tracked(&mut self) -> usize {
+        let data = &mut *self.data;
+
+        data.inner.compact(&data.mask)
+    }
+}
+
+/// A guard for mutating a component of a tracked storage, returned by
+/// `Storage::get_tracked_mut`.
+///
+/// The component is flagged as modified when the guard is dropped, if it
+/// has been dereferenced mutably.
+pub struct TrackedMut<'a, T: Component + 'a>
+where
+    T::Storage: Tracked,
+{
+    storage: &'a mut T::Storage,
+    id: Index,
+    modified: bool,
+}
+
+impl<'a, T> TrackedMut<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    /// Returns `true` if the component has been accessed mutably, so it
+    /// will be flagged as modified.
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+}
+
+impl<'a, T> Deref for TrackedMut<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.storage.get(self.id) }
+    }
+}
+
+impl<'a, T> DerefMut for TrackedMut<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+
+        unsafe { self.storage.get_mut_silent(self.id) }
+    }
+}
+
+impl<'a, T> Drop for TrackedMut<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    fn drop(&mut self) {
+        if self.modified {
+            self.storage.channels_mut().flag_modified(self.id);
+        }
+    }
+}
+
+/// Iterator over the net changes of a tracked storage together with the
+/// changed components, returned by `Storage::changed_join`.
+pub struct ChangedJoin<'a, T: Component + 'a> {
+    ids: BitIter<BitSet>,
+    net: NetChanges,
+    mask: &'a BitSet,
+    storage: &'a T::Storage,
+    entities: &'a EntitiesRes,
+}
+
+impl<'a, T: Component> Iterator for ChangedJoin<'a, T> {
+    type Item = (Entity, ChangeKind, Option<&'a T>);
+
+    fn next(&mut self) -> Option<(Entity, ChangeKind, Option<&'a T>)> {
+        self.ids.next().map(|id| {
+            let kind = self.net.kind(id).unwrap_or(ChangeKind::Removed);
+            let comp = if kind != ChangeKind::Removed && self.mask.contains(id) {
+                Some(unsafe { self.storage.get(id) })
+            } else {
+                None
+            };
+
+            (self.entities.entity(id), kind, comp)
+        })
+    }
+}
+
+/// Iterator over the changed components of a tracked storage, returned by
+/// `Storage::changed_mut`.
+pub struct ChangedMut<'a, T: Component + 'a> {
+    ids: BitIter<BitSet>,
+    storage: &'a mut T::Storage,
+    entities: &'a EntitiesRes,
+}
+
+impl<'a, T: Component> Iterator for ChangedMut<'a, T> {
+    type Item = (Entity, &'a mut T);
+
+    fn next(&mut self) -> Option<(Entity, &'a mut T)> {
+        self.ids.next().map(|id| {
+            let value: *mut T = unsafe { self.storage.get_mut_silent(id) };
+            // This is safe because every index is only yielded once.
+            (self.entities.entity(id), unsafe { &mut *value })
+        })
     }
 }
 
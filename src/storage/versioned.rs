@@ -0,0 +1,203 @@
+use std::marker::PhantomData;
+use std::ops::DerefMut;
+
+use hibitset::{AtomicBitSet, BitSetLike};
+
+use storage::{DenseVecStorage, DistinctStorage, MaskedStorage, Storage, TrackChannels, Tracked,
+              TryDefault, UnprotectedStorage};
+use world::{Component, Entity, Index};
+
+/// A component which keeps a version counter, bumped whenever it changes.
+pub trait Versioned {
+    /// Returns the current version; it has to change whenever the
+    /// component is modified.
+    fn version(&self) -> u64;
+}
+
+/// Wrapper storage which tracks modifications by the version of the
+/// components (see `Versioned`), instead of flagging every mutable access
+/// like `FlaggedStorage` does.
+///
+/// Insertions and removals are flagged right away. Modifications are only
+/// found by `Storage::maintain_versions`, which compares the version of
+/// each component accessed with `get_mut` since the last call with the one
+/// it had back then, and flags the components whose version changed. Only
+/// the last seen version is kept per index; unlike with
+/// `FieldFlaggedStorage`, the components are never cloned or compared.
+///
+/// Mutable accesses are recorded in an atomic bitset, so parallel joins
+/// over a `VersionedStorage` are possible if the inner storage allows them.
+///
+/// # Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::storage::{Versioned, VersionedStorage};
+///
+/// struct Mesh {
+///     vertices: Vec<[f32; 3]>,
+///     version: u64,
+/// }
+///
+/// impl Versioned for Mesh {
+///     fn version(&self) -> u64 {
+///         self.version
+///     }
+/// }
+///
+/// impl Component for Mesh {
+///     type Storage = VersionedStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Mesh>();
+/// let mut modified = world.write_storage::<Mesh>().track_modified();
+/// let e = world
+///     .create_entity()
+///     .with(Mesh { vertices: vec![], version: 0 })
+///     .build();
+///
+/// let mut meshes = world.write_storage::<Mesh>();
+/// {
+///     let mesh = meshes.get_mut(e).unwrap();
+///     mesh.vertices.push([0.0; 3]);
+///     mesh.version += 1;
+/// }
+/// assert_eq!(meshes.maintain_versions(), 1);
+/// assert_eq!(meshes.modified().read(&mut modified).count(), 1);
+/// ```
+pub struct VersionedStorage<C, T = DenseVecStorage<C>> {
+    trackers: TrackChannels,
+    versions: Vec<u64>,
+    /// Indices accessed with `get_mut` since the last `maintain_versions`.
+    candidates: AtomicBitSet,
+    storage: T,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> VersionedStorage<C, T> {
+    fn set_version(&mut self, id: Index, version: u64) {
+        let id = id as usize;
+        if self.versions.len() <= id {
+            self.versions.resize(id + 1, 0);
+        }
+        self.versions[id] = version;
+    }
+}
+
+impl<C, T> Default for VersionedStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        VersionedStorage {
+            trackers: TrackChannels::default(),
+            versions: Vec::new(),
+            candidates: AtomicBitSet::new(),
+            storage: T::unwrap_default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T> UnprotectedStorage<C> for VersionedStorage<C, T>
+where
+    C: Component + Versioned,
+    T: UnprotectedStorage<C>,
+{
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        for id in (&has).iter() {
+            self.trackers.flag_removed(id);
+        }
+        self.versions.clear();
+        self.candidates.clear();
+        self.storage.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.storage.get(id)
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        // Parallel joins call this for distinct indices at the same time.
+        self.candidates.add_atomic(id);
+        self.storage.get_mut(id)
+    }
+
+    unsafe fn get_mut_silent(&mut self, id: Index) -> &mut C {
+        self.storage.get_mut_silent(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.trackers.flag_inserted(id);
+        self.set_version(id, comp.version());
+        self.storage.insert(id, comp);
+    }
+
+    unsafe fn insert_entity(&mut self, e: Entity, comp: C) {
+        self.trackers.flag_inserted(e.id());
+        self.set_version(e.id(), comp.version());
+        self.storage.insert_entity(e, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.trackers.flag_removed(id);
+        self.storage.remove(id)
+    }
+
+    fn grows(&self) -> usize {
+        self.storage.grows()
+    }
+}
+
+unsafe impl<C, T: DistinctStorage> DistinctStorage for VersionedStorage<C, T> {}
+
+impl<C, T> Tracked for VersionedStorage<C, T> {
+    fn channels(&self) -> &TrackChannels {
+        &self.trackers
+    }
+
+    fn channels_mut(&mut self) -> &mut TrackChannels {
+        &mut self.trackers
+    }
+}
+
+impl<'e, T, S, D> Storage<'e, T, D>
+where
+    T: Component<Storage = VersionedStorage<T, S>> + Versioned,
+    S: UnprotectedStorage<T>,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Flags every component whose version changed since the last call (or
+    /// since it has been inserted) as modified, and returns the number of
+    /// flagged components.
+    ///
+    /// Only the components accessed with `get_mut` since the last call are
+    /// visited, so the cost depends on the number of writes rather than on
+    /// the number of components. Changes made through `get_mut_silent` or
+    /// interior mutability aren't found.
+    pub fn maintain_versions(&mut self) -> usize {
+        let data = &mut *self.data;
+        let inner = &mut data.inner;
+        let mut flagged = 0;
+        for id in (&inner.candidates).iter() {
+            if !data.mask.contains(id) {
+                // removed since
+                continue;
+            }
+
+            let version = unsafe { inner.storage.get(id) }.version();
+            if inner.versions[id as usize] != version {
+                inner.versions[id as usize] = version;
+                inner.trackers.flag_modified(id);
+                flagged += 1;
+            }
+        }
+        inner.candidates.clear();
+
+        flagged
+    }
+}
@@ -0,0 +1,155 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+
+use hibitset::BitSet;
+
+use join::{Join, ParJoin};
+use storage::{MaskedStorage, Storage, UnprotectedStorage};
+use world::{Component, EntitiesRes, Entity, Index};
+
+/// Types which can be used as a read-only view of the component `T`,
+/// see `Storage::view`.
+///
+/// This allows reading a component as a different type without storing it
+/// twice, e.g. a plain `[f32; 16]` array as a matrix type of a math library.
+///
+/// # Safety
+///
+/// A `&T` is reinterpreted as a `&Self`, so the implementor has to make sure
+/// that
+///
+/// * `Self` has the same size as `T` and an alignment not greater than the
+///   one of `T`, which `Storage::view` asserts,
+/// * the layout of both types is fixed, i.e. they are `#[repr(C)]` or
+///   `#[repr(transparent)]` (or primitives and arrays of them) and every
+///   field of `Self` is at the offset of a compatible field of `T`,
+/// * every valid value of `T` is a valid value of `Self`, and
+/// * `Self` doesn't allow mutation through a shared reference where `T`
+///   doesn't (e.g. with `Cell`s).
+///
+/// # Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// use specs::storage::ViewOf;
+///
+/// #[repr(C)]
+/// struct RawMatrix([f32; 16]);
+///
+/// impl Component for RawMatrix {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[repr(C)]
+/// struct Matrix4 {
+///     cols: [[f32; 4]; 4],
+/// }
+///
+/// unsafe impl ViewOf<RawMatrix> for Matrix4 {}
+///
+/// let mut world = World::new();
+/// world.register::<RawMatrix>();
+/// let mut raw = [0.0; 16];
+/// raw[4] = 1.0;
+/// let e = world.create_entity().with(RawMatrix(raw)).build();
+///
+/// let matrices = world.read_storage::<RawMatrix>();
+/// let view = matrices.view::<Matrix4>();
+/// assert_eq!(view.get(e).unwrap().cols[1][0], 1.0);
+/// ```
+pub unsafe trait ViewOf<T> {}
+
+/// A read-only view of a storage as a different type, returned by
+/// `Storage::view`.
+///
+/// Can be joined over, yielding `&V`.
+pub struct View<'a, T: Component + 'a, V> {
+    mask: &'a BitSet,
+    storage: &'a T::Storage,
+    entities: &'a EntitiesRes,
+    phantom: PhantomData<&'a V>,
+}
+
+impl<'a, T, V> View<'a, T, V>
+where
+    T: Component,
+    V: ViewOf<T>,
+{
+    /// Returns the viewed component of an entity, if it has one.
+    pub fn get(&self, e: Entity) -> Option<&'a V> {
+        if self.mask.contains(e.id()) && self.entities.is_alive(e) {
+            Some(unsafe { cast(self.storage.get(e.id())) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the entity has the viewed component.
+    pub fn contains(&self, e: Entity) -> bool {
+        self.mask.contains(e.id()) && self.entities.is_alive(e)
+    }
+}
+
+impl<'a, T, V> Join for View<'a, T, V>
+where
+    T: Component,
+    V: ViewOf<T> + 'a,
+{
+    type Type = &'a V;
+    type Value = &'a T::Storage;
+    type Mask = &'a BitSet;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.mask, self.storage)
+    }
+
+    unsafe fn get(v: &mut Self::Value, i: Index) -> &'a V {
+        cast(v.get(i))
+    }
+}
+
+unsafe impl<'a, T, V> ParJoin for View<'a, T, V>
+where
+    T: Component,
+    T::Storage: Sync,
+    V: ViewOf<T> + Sync + 'a,
+{
+}
+
+unsafe fn cast<T, V: ViewOf<T>>(comp: &T) -> &V {
+    &*(comp as *const T as *const V)
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns a read-only view of this storage, reading the components
+    /// as `V` instead of `T`. See `ViewOf` for the invariants `V` has to
+    /// uphold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `V` doesn't have the same size as `T` or if its alignment
+    /// is greater than the one of `T`.
+    pub fn view<V: ViewOf<T>>(&self) -> View<T, V> {
+        assert_eq!(
+            mem::size_of::<V>(),
+            mem::size_of::<T>(),
+            "a view must have the same size as the component"
+        );
+        assert!(
+            mem::align_of::<V>() <= mem::align_of::<T>(),
+            "a view must not have a greater alignment than the component"
+        );
+
+        View {
+            mask: &self.data.mask,
+            storage: &self.data.inner,
+            entities: &self.entities,
+            phantom: PhantomData,
+        }
+    }
+}
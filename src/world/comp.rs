@@ -61,7 +61,39 @@ use storage::UnprotectedStorage;
 ///     type Storage = HashMapStorage<Self>;
 /// }
 /// ```
+///
+/// ## Teardown
+///
+/// `on_remove` is called whenever a component is taken out of its storage,
+/// be it by `Storage::remove`, by deleting its entity or by dropping the
+/// storage. It's called before the removal is recorded by tracked storages,
+/// so it happens before the `RemovedFlag` event is sent.
+///
+/// ```
+/// use specs::prelude::*;
+///
+/// pub struct Buffer {
+///     pub handle: u32,
+/// }
+///
+/// impl Component for Buffer {
+///     type Storage = DenseVecStorage<Self>;
+///
+///     fn on_remove(&mut self) {
+///         // release `self.handle` here
+///     }
+/// }
+/// ```
+///
+/// `on_remove` can't access the `World`. If the teardown needs resources,
+/// either keep a handle to them in the component or do the removal inside
+/// `LazyUpdate::exec`, which gets access to the `World`.
 pub trait Component: Any + Sized {
     /// Associated storage type for this component.
     type Storage: UnprotectedStorage<Self> + Any + Send + Sync;
+
+    /// Called right before the component is removed from its storage.
+    ///
+    /// Does nothing by default.
+    fn on_remove(&mut self) {}
 }
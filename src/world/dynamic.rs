@@ -0,0 +1,116 @@
+use std::any::{Any, TypeId};
+use std::mem;
+
+use fnv::FnvHashMap;
+use shred::{MetaTable, Resources, SystemData};
+
+use error::{Error, WrongType};
+use storage::{AnyStorage, MaskedStorage, WriteStorage};
+use world::{Component, Entity, Index};
+
+/// Type-erased functions accessing the storage of one component type.
+#[derive(Clone, Copy)]
+pub(crate) struct DynVtable {
+    pub(crate) get: fn(&mut Resources, Index) -> Option<&Any>,
+    pub(crate) insert: fn(&Resources, Entity, Box<Any>) -> Result<(), Error>,
+    pub(crate) register_any: fn(&Resources, &mut MetaTable<AnyStorage>),
+}
+
+/// Metadata of a registered component type, returned by
+/// `World::registered_components`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentInfo {
+    /// The `TypeId` of the component type.
+    pub type_id: TypeId,
+    /// The name of the component type.
+    ///
+    /// This is the full path of the type if the `nightly` feature is
+    /// enabled, and `"<unnamed>"` otherwise, unless it has been given
+    /// with `Registration::with_name`.
+    pub name: &'static str,
+    /// The `TypeId` of the storage type, `Component::Storage`.
+    pub storage: TypeId,
+    /// The size of the component type in bytes.
+    pub size: usize,
+}
+
+/// The `DynVtable`s of all registered components, by the `TypeId` of the
+/// component type, and their `ComponentInfo` in registration order.
+///
+/// This is added to the `World` by default, and filled when a component
+/// is registered.
+#[derive(Default)]
+pub(crate) struct DynComponents {
+    vtables: FnvHashMap<TypeId, DynVtable>,
+    infos: Vec<ComponentInfo>,
+}
+
+impl DynComponents {
+    pub(crate) fn register<T: Component>(&mut self) {
+        let infos = &mut self.infos;
+        self.vtables.entry(TypeId::of::<T>()).or_insert_with(|| {
+            #[cfg(feature = "nightly")]
+            let name = unsafe { ::std::intrinsics::type_name::<T>() };
+            #[cfg(not(feature = "nightly"))]
+            let name = "<unnamed>";
+
+            infos.push(ComponentInfo {
+                type_id: TypeId::of::<T>(),
+                name,
+                storage: TypeId::of::<T::Storage>(),
+                size: mem::size_of::<T>(),
+            });
+
+            DynVtable {
+                get: get::<T>,
+                insert: insert::<T>,
+                register_any: register_any::<T>,
+            }
+        });
+    }
+
+    pub(crate) fn unregister(&mut self, component: TypeId) {
+        self.vtables.remove(&component);
+        self.infos.retain(|info| info.type_id != component);
+    }
+
+    /// Registers the storages of all components in `storages`, in the
+    /// order they were registered.
+    pub(crate) fn register_any(&self, res: &Resources, storages: &mut MetaTable<AnyStorage>) {
+        for info in &self.infos {
+            (self.vtables[&info.type_id].register_any)(res, storages);
+        }
+    }
+
+    pub(crate) fn set_name(&mut self, component: TypeId, name: &'static str) {
+        if let Some(info) = self.infos.iter_mut().find(|info| info.type_id == component) {
+            info.name = name;
+        }
+    }
+
+    pub(crate) fn infos(&self) -> &[ComponentInfo] {
+        &self.infos
+    }
+
+    pub(crate) fn vtable(&self, component: TypeId) -> Option<DynVtable> {
+        self.vtables.get(&component).cloned()
+    }
+}
+
+fn get<T: Component>(res: &mut Resources, id: Index) -> Option<&Any> {
+    let storage: &MaskedStorage<T> = res.get_mut::<MaskedStorage<T>>()?;
+
+    storage.get(id).map(|c| c as &Any)
+}
+
+fn insert<T: Component>(res: &Resources, e: Entity, component: Box<Any>) -> Result<(), Error> {
+    let component = component.downcast::<T>().map_err(|_| WrongType {
+        expected: TypeId::of::<T>(),
+    })?;
+
+    WriteStorage::<T>::fetch(res).insert(e, *component).map(|_| ())
+}
+
+fn register_any<T: Component>(res: &Resources, storages: &mut MetaTable<AnyStorage>) {
+    storages.register(&*res.fetch::<MaskedStorage<T>>());
+}
@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+use std::mem;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use hibitset::{AtomicBitSet, BitSet, BitSetOr};
@@ -43,6 +45,28 @@ pub type Index = u32;
 /// ```
 pub type Entities<'a> = Read<'a, EntitiesRes>;
 
+/// The order in which the indices of deleted entities are reused, see
+/// `World::with_recycle_policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecyclePolicy {
+    /// Reuses the most recently freed index first, which is the default.
+    ///
+    /// The components of a new entity are likely to end up in memory which
+    /// has just been accessed.
+    Lifo,
+    /// Reuses the least recently freed index first.
+    ///
+    /// Spreads deletions over more indices, so the generation of each one
+    /// grows slower and stale `Entity` handles stay invalid for longer.
+    Fifo,
+}
+
+impl Default for RecyclePolicy {
+    fn default() -> Self {
+        RecyclePolicy::Lifo
+    }
+}
+
 /// Internally used structure for `Entity` allocation.
 #[derive(Default, Debug)]
 pub(crate) struct Allocator {
@@ -53,9 +77,17 @@ pub(crate) struct Allocator {
     killed: AtomicBitSet,
     cache: EntityCache,
     max_id: AtomicUsize,
+    /// Whether entities have been created, deleted or moved since the
+    /// last call to `take_changed`.
+    changed: bool,
 }
 
 impl Allocator {
+    /// Sets the order in which freed indices are reused.
+    pub(crate) fn set_recycle_policy(&mut self, policy: RecyclePolicy) {
+        self.cache.policy = policy;
+    }
+
     /// Kills a list of entities immediately.
     pub fn kill(&mut self, delete: &[Entity]) -> Result<(), WrongGeneration> {
         for &entity in delete {
@@ -73,6 +105,7 @@ impl Allocator {
                 self.generations[id] = self.generations[id].raised();
             }
             self.generations[id].die();
+            self.changed = true;
         }
 
         self.cache.extend(delete.iter().map(|e| e.0));
@@ -147,6 +180,7 @@ impl Allocator {
         self.alive.add(id as Index);
 
         self.generations[id as usize] = self.generations[id as usize].raised();
+        self.changed = true;
 
         Entity(id as Index, self.generations[id as usize])
     }
@@ -164,6 +198,7 @@ impl Allocator {
         for i in (&self.raised).iter() {
             self.generations[i as usize] = self.generations[i as usize].raised();
             self.alive.add(i);
+            self.changed = true;
         }
         self.raised.clear();
 
@@ -171,6 +206,7 @@ impl Allocator {
             self.alive.remove(i);
             deleted.push(Entity(i, self.generations[i as usize]));
             self.generations[i as usize].die();
+            self.changed = true;
         }
         self.killed.clear();
 
@@ -179,6 +215,128 @@ impl Allocator {
         deleted
     }
 
+    /// Moves all alive entities to the lowest indices, keeping their order,
+    /// and returns the moved entities with their new handles.
+    ///
+    /// Must be called right after `merge`, without any atomically
+    /// allocated or killed entities pending.
+    pub fn compact(&mut self) -> Vec<(Entity, Entity)> {
+        use hibitset::BitSetLike;
+
+        let alive: Vec<Index> = (&self.alive).iter().collect();
+        let mut moved = vec![];
+
+        // Entities only move to lower indices, and in ascending order, so
+        // the target index is either free or has been vacated already.
+        for (new, &old) in alive.iter().enumerate() {
+            let new = new as Index;
+            if new == old {
+                continue;
+            }
+
+            let from = Entity(old, self.generations[old as usize]);
+            self.alive.remove(old);
+            self.generations[old as usize].die();
+
+            let gen = self.generations[new as usize].raised();
+            self.generations[new as usize] = gen;
+            self.alive.add(new);
+
+            moved.push((from, Entity(new, gen)));
+            self.changed = true;
+        }
+
+        // All free indices are above the alive ones now.
+        self.cache.clear();
+        *self.max_id.get_mut() = alive.len();
+
+        moved
+    }
+
+    /// Returns whether entities have been created, deleted or moved since
+    /// the last call, and resets the flag.
+    pub(crate) fn take_changed(&mut self) -> bool {
+        mem::replace(&mut self.changed, false)
+    }
+
+    /// Copies the state of the allocator, to be restored with `restore`.
+    pub(crate) fn snapshot(&self) -> AllocatorSnapshot {
+        use hibitset::BitSetLike;
+
+        let mut raised = BitSet::new();
+        raised.extend((&self.raised).iter());
+        let mut killed = BitSet::new();
+        killed.extend((&self.killed).iter());
+        let len = self.cache.len.load(Ordering::Relaxed);
+
+        AllocatorSnapshot {
+            generations: self.generations.clone(),
+            alive: self.alive.clone(),
+            raised,
+            killed,
+            cache: self.cache.cache.iter().take(len).cloned().collect(),
+            max_id: self.max_id.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Restores the state of a snapshot, returning the entities which have
+    /// been created since and are dead now.
+    ///
+    /// Indices which were free when the snapshot was taken keep their
+    /// current generation (killed), so the handles of entities created in
+    /// the meantime don't become alive again with the next allocation.
+    pub(crate) fn restore(&mut self, snapshot: AllocatorSnapshot) -> Vec<Entity> {
+        use hibitset::BitSetLike;
+
+        let max_id = *self.max_id.get_mut();
+        self.update_generation_length(max_id);
+
+        let mut created = vec![];
+        for i in 0..self.generations.len() {
+            let id = i as Index;
+            let current = self.entity(id);
+            let old = snapshot
+                .generations
+                .get(i)
+                .cloned()
+                .unwrap_or(Generation(0));
+            let was_alive = snapshot.alive.contains(id) || snapshot.raised.contains(id);
+
+            // the entity at this index might have been deleted and another
+            // one created in its place
+            let old_entity = if old.is_alive() { old } else { old.raised() };
+            let is_alive = self.alive.contains(id) || self.raised.contains(id);
+            if is_alive && !(was_alive && current.gen() == old_entity) {
+                created.push(current);
+            }
+
+            self.generations[i] = if was_alive {
+                old
+            } else if current.gen().is_alive() {
+                Generation(-current.gen().id())
+            } else {
+                current.gen()
+            };
+        }
+
+        self.alive = snapshot.alive;
+        self.raised.clear();
+        for id in (&snapshot.raised).iter() {
+            self.raised.add(id);
+        }
+        self.killed.clear();
+        for id in (&snapshot.killed).iter() {
+            self.killed.add(id);
+        }
+
+        // Indices allocated since the snapshot are free again.
+        self.cache.set(snapshot.cache);
+        self.cache
+            .extend((snapshot.max_id..max_id).map(|id| id as Index));
+
+        created
+    }
+
     fn update_generation_length(&mut self, i: usize) {
         if self.generations.len() <= i as usize {
             self.generations.resize(i as usize + 1, Generation(0));
@@ -186,6 +344,17 @@ impl Allocator {
     }
 }
 
+/// The state of an `Allocator`, returned by `Allocator::snapshot`.
+#[derive(Debug)]
+pub(crate) struct AllocatorSnapshot {
+    generations: Vec<Generation>,
+    alive: BitSet,
+    raised: BitSet,
+    killed: BitSet,
+    cache: Vec<Index>,
+    max_id: usize,
+}
+
 /// An iterator for entity creation.
 /// Please note that you have to consume
 /// it because iterators are lazy.
@@ -223,6 +392,34 @@ impl Entity {
     pub fn gen(&self) -> Generation {
         self.1
     }
+
+    /// Returns `true` if both entities have the same index, no matter
+    /// their generation; i.e. if they are equal or one of them reuses the
+    /// slot of the other after it has been deleted.
+    #[inline]
+    pub fn same_index(self, other: Entity) -> bool {
+        self.id() == other.id()
+    }
+
+    /// Packs the index and the generation into a single `u64`, e.g. to send
+    /// it over the network or to use it as a key.
+    ///
+    /// The index makes up the lower 32 bits and the generation the upper
+    /// 32 bits. `from_bits` restores the entity.
+    #[inline]
+    pub fn to_bits(self) -> u64 {
+        u64::from((self.1).0 as u32) << 32 | u64::from(self.0)
+    }
+
+    /// Restores an entity packed with `to_bits`.
+    ///
+    /// This doesn't check whether the entity exists; any `u64` gives an
+    /// entity, which just isn't alive unless it came from `to_bits` of an
+    /// alive entity of the same world.
+    #[inline]
+    pub fn from_bits(bits: u64) -> Entity {
+        Entity(bits as Index, Generation((bits >> 32) as u32 as i32))
+    }
 }
 
 /// The entities of this ECS. This is a resource, stored in the `World`.
@@ -251,6 +448,13 @@ impl EntitiesRes {
     /// In case you have access to the `World`,
     /// you can also use `World::create_entity` which
     /// creates the entity and the components immediately.
+    ///
+    /// Since this only needs `&self`, it can be called while iterating
+    /// a join, e.g. to spawn one entity per joined element. The new entity
+    /// is reported as alive by `is_alive` right away and can get components
+    /// through a `WriteStorage` or `LazyUpdate`, but it only becomes a regular
+    /// entity after the next `World::maintain`. Whether a join over `Entities`
+    /// which is running at the same time visits it is unspecified.
     pub fn create(&self) -> Entity {
         self.alloc.allocate_atomic()
     }
@@ -399,18 +603,30 @@ impl Generation {
 
 #[derive(Default, Debug)]
 struct EntityCache {
-    cache: Vec<Index>,
+    /// The free indices; the next one to be reused is the last.
+    cache: VecDeque<Index>,
     len: AtomicUsize,
+    policy: RecyclePolicy,
 }
 
 impl EntityCache {
+    fn clear(&mut self) {
+        self.set(vec![]);
+    }
+
+    /// Replaces the free indices, keeping their order.
+    fn set(&mut self, cache: Vec<Index>) {
+        self.cache = VecDeque::from(cache);
+        *self.len.get_mut() = self.cache.len();
+    }
+
     fn pop_atomic(&self) -> Option<Index> {
         atomic_decrement(&self.len).map(|x| self.cache[x - 1])
     }
 
     fn pop(&mut self) -> Option<Index> {
         self.maintain();
-        let x = self.cache.pop();
+        let x = self.cache.pop_back();
         *self.len.get_mut() = self.cache.len();
         x
     }
@@ -423,7 +639,17 @@ impl EntityCache {
 impl Extend<Index> for EntityCache {
     fn extend<T: IntoIterator<Item = Index>>(&mut self, iter: T) {
         self.maintain();
-        self.cache.extend(iter);
+        match self.policy {
+            RecyclePolicy::Lifo => self.cache.extend(iter),
+            RecyclePolicy::Fifo => {
+                // The new indices go to the front, so they're reused in the
+                // order they have been freed, after the ones which have
+                // been free before.
+                for id in iter {
+                    self.cache.push_front(id);
+                }
+            }
+        }
         *self.len.get_mut() = self.cache.len();
     }
 }
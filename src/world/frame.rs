@@ -0,0 +1,44 @@
+/// Counts how often `World::maintain` has been called.
+///
+/// This is added to the `World` by default and incremented at the end of
+/// every `maintain`, so all systems of a dispatch see the same frame
+/// number. It can be overwritten, e.g. to restore the frame of a saved
+/// game or to replay a recorded session deterministically.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::world::FrameCounter;
+///
+/// let mut world = World::new();
+/// assert_eq!(world.read_resource::<FrameCounter>().frame(), 0);
+///
+/// world.maintain();
+/// assert_eq!(world.read_resource::<FrameCounter>().frame(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FrameCounter {
+    frame: u64,
+}
+
+impl FrameCounter {
+    /// Returns the number of the current frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Sets the number of the current frame.
+    pub fn set(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    /// Resets the counter to `0`.
+    pub fn reset(&mut self) {
+        self.set(0);
+    }
+
+    pub(crate) fn advance(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
@@ -222,6 +222,55 @@ impl LazyUpdate {
         });
     }
 
+    /// Lazily registers a component, see `World::register`.
+    ///
+    /// Does nothing if the component is already registered once the
+    /// update is applied. Updates are applied in order, so components can
+    /// be inserted lazily right after registering them.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// #
+    /// struct Pos;
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// let e = world.create_entity().build();
+    /// {
+    ///     let lazy = world.read_resource::<LazyUpdate>();
+    ///     lazy.register::<Pos>();
+    ///     lazy.insert(e, Pos);
+    /// }
+    ///
+    /// world.maintain();
+    /// assert!(world.read_storage::<Pos>().get(e).is_some());
+    /// ```
+    pub fn register<C>(&self)
+    where
+        C: Component + Send + Sync,
+        C::Storage: Default,
+    {
+        self.register_with_storage::<_, C>(Default::default);
+    }
+
+    /// Lazily registers a component with a given storage, see
+    /// `World::register_with_storage`.
+    ///
+    /// Does nothing if the component is already registered once the
+    /// update is applied.
+    pub fn register_with_storage<F, C>(&self, storage: F)
+    where
+        F: FnOnce() -> C::Storage + 'static + Send + Sync,
+        C: Component + Send + Sync,
+    {
+        self.exec_mut(move |world| world.register_with_storage::<_, C>(storage));
+    }
+
     /// Lazily executes a closure with world access.
     ///
     /// ## Examples
@@ -2,21 +2,41 @@
 
 pub use self::comp::Component;
 pub use self::entity::{CreateIterAtomic, Entities, EntitiesRes, Entity, EntityResBuilder,
-                       Generation, Index};
+                       Generation, Index, RecyclePolicy};
+pub use self::frame::FrameCounter;
 pub use self::lazy::{LazyBuilder, LazyUpdate};
+pub use self::stable::StableIds;
+
+pub use self::dynamic::ComponentInfo;
+
+pub(crate) use self::dynamic::DynComponents;
 
 use self::entity::Allocator;
 
+use std::any::{Any, TypeId};
 use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
 
+use fnv::FnvHashMap;
+use hibitset::BitSetLike;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
 use shred::{Fetch, FetchMut, MetaTable, Read, Resource, Resources, SystemData};
 
-use error::WrongGeneration;
-use storage::{AnyStorage, DenseVecStorage, MaskedStorage, ReadStorage, WriteStorage};
+use error::{Error, IdInUse, NotRegistered, RegisterError, WrongGeneration};
+use storage::{AnyStorage, DenseVecStorage, MaskedStorage, ReadStorage, StorageSnapshot, Tracked,
+              TryDefault, WithCapacity, WriteStorage};
 
 mod comp;
+mod dynamic;
 mod entity;
+mod frame;
 mod lazy;
+mod stable;
 #[cfg(test)]
 mod tests;
 
@@ -47,6 +67,20 @@ pub trait Builder {
     /// `World`.
     fn with<C: Component + Send + Sync>(self, c: C) -> Self;
 
+    /// Appends a component with its `Default` value, just like
+    /// `with(C::default())`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component hasn't been `register()`ed in the
+    /// `World`.
+    fn with_default<C: Component + Default + Send + Sync>(self) -> Self
+    where
+        Self: Sized,
+    {
+        self.with(C::default())
+    }
+
     /// Finishes the building and returns the entity.
     fn build(self) -> Entity;
 }
@@ -127,6 +161,132 @@ impl<'a> Drop for EntityBuilder<'a> {
     }
 }
 
+/// Configures the storage of a component which is being registered,
+/// returned by `World::register_with`.
+///
+/// The component is registered by `build`.
+#[must_use = "The component is only registered by `build`."]
+pub struct Registration<'a, T: Component> {
+    world: &'a mut World,
+    storage: Option<T::Storage>,
+    reset: Option<fn(&mut World)>,
+    rollback: Option<fn(&mut World)>,
+    cloning: Option<fn(&mut World)>,
+    name: Option<&'static str>,
+}
+
+impl<'a, T: Component> Registration<'a, T> {
+    /// Preallocates the storage for `capacity` components, see
+    /// `WithCapacity`.
+    pub fn with_capacity(mut self, capacity: usize) -> Self
+    where
+        T::Storage: WithCapacity,
+    {
+        self.storage = Some(T::Storage::with_capacity(capacity));
+
+        self
+    }
+
+    /// Makes `maintain` reset the tracked storage of the component, see
+    /// `World::auto_reset_tracked`.
+    pub fn auto_reset_tracked(mut self) -> Self
+    where
+        T::Storage: Tracked,
+    {
+        self.reset = Some(World::auto_reset_tracked::<T>);
+
+        self
+    }
+
+    /// Makes failed transactions restore the components, see
+    /// `World::enable_rollback`.
+    pub fn enable_rollback(mut self) -> Self
+    where
+        T: Clone,
+    {
+        self.rollback = Some(World::enable_rollback::<T>);
+
+        self
+    }
+
+    /// Makes `World::clone_entity` copy the component, see
+    /// `World::enable_cloning`.
+    pub fn enable_cloning(mut self) -> Self
+    where
+        T: Clone,
+    {
+        self.cloning = Some(World::enable_cloning::<T>);
+
+        self
+    }
+
+    /// Sets the name of the component returned by
+    /// `World::registered_components`, e.g. if the `nightly` feature isn't
+    /// enabled.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+
+        self
+    }
+
+    /// Registers the component with the configured storage, like
+    /// `World::register` does.
+    ///
+    /// Does nothing but the configuration which doesn't concern the storage
+    /// if the component was already registered.
+    pub fn build(self) {
+        let storage = self.storage.unwrap_or_else(TryDefault::unwrap_default);
+        self.world.register_with_storage::<_, T>(|| storage);
+
+        if let Some(reset) = self.reset {
+            reset(self.world);
+        }
+        if let Some(rollback) = self.rollback {
+            rollback(self.world);
+        }
+        if let Some(cloning) = self.cloning {
+            cloning(self.world);
+        }
+        if let Some(name) = self.name {
+            self.world
+                .write_resource::<DynComponents>()
+                .set_name(TypeId::of::<T>(), name);
+        }
+    }
+}
+
+/// A guard keeping the tracked storage of a component from recording
+/// changes until it's dropped, returned by `World::suppress_tracking`.
+///
+/// While the guard is alive, the storage discards its events instead of
+/// writing them (see `TrackChannels::set_suppressed`). Once it's dropped,
+/// the storage is reset (see `Storage::reset_tracked`), unless tracking
+/// was already suppressed before.
+#[must_use = "Tracking is only suppressed while the guard is alive."]
+pub struct SuppressTracking<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    world: &'a World,
+    was_suppressed: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T> Drop for SuppressTracking<'a, T>
+where
+    T: Component,
+    T::Storage: Tracked,
+{
+    fn drop(&mut self) {
+        let mut storage = self.world.write_storage::<T>();
+        storage.channels_mut().set_suppressed(self.was_suppressed);
+        if !self.was_suppressed {
+            storage.reset_tracked();
+        }
+    }
+}
+
 /// The `World` struct contains the component storages and
 /// other resources.
 ///
@@ -135,6 +295,15 @@ impl<'a> Drop for EntityBuilder<'a> {
 /// the borrowing rules of Rust (multiple reads xor one write),
 /// you will get a panic.
 ///
+/// The borrows are tracked by the cells `shred` stores resources in, which
+/// only know whether a resource is borrowed, not by whom; so the panic
+/// can't name the code holding the conflicting borrow. Run with
+/// `RUST_BACKTRACE=1` to find the borrow which failed, then look for
+/// fetches of the same storage or resource which are still alive at that
+/// point. Systems run by a `Dispatcher` never conflict with each other,
+/// since their data is fetched according to their `SystemData` (see
+/// `dispatch::conflicts`).
+///
 /// ## Examples
 ///
 /// ```
@@ -194,6 +363,39 @@ impl<'a> Drop for EntityBuilder<'a> {
 ///     assert!(pos_storage.get(empty).is_some());
 /// }
 /// ```
+///
+/// ## Running a single system
+///
+/// There's no need to build a `Dispatcher` to run a system once
+/// (e.g. in tests or tools); every `System` implements `RunNow`:
+///
+/// ```
+/// use specs::prelude::*;
+/// # #[derive(Debug, PartialEq)]
+/// # struct Pos { x: f32, y: f32, } impl Component for Pos { type Storage = VecStorage<Self>; }
+///
+/// struct Gravity;
+///
+/// impl<'a> System<'a> for Gravity {
+///     type SystemData = WriteStorage<'a, Pos>;
+///
+///     fn run(&mut self, mut pos: Self::SystemData) {
+///         for pos in (&mut pos).join() {
+///             pos.y -= 1.0;
+///         }
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// let mut gravity = Gravity;
+/// // Registers everything the system needs.
+/// RunNow::setup(&mut gravity, &mut world.res);
+///
+/// let e = world.create_entity().with(Pos { x: 0.0, y: 2.0 }).build();
+/// gravity.run_now(&world.res);
+///
+/// assert_eq!(world.read_storage::<Pos>().get(e), Some(&Pos { x: 0.0, y: 1.0 }));
+/// ```
 pub struct World {
     /// The resources used for this world.
     pub res: Resources,
@@ -205,12 +407,38 @@ impl World {
         Default::default()
     }
 
+    /// Creates a new empty `World` which reuses the indices of deleted
+    /// entities in the order given by `policy`.
+    ///
+    /// `World::new` uses `RecyclePolicy::Lifo`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    /// use specs::world::RecyclePolicy;
+    ///
+    /// let mut world = World::with_recycle_policy(RecyclePolicy::Fifo);
+    /// let a = world.create_entity().build();
+    /// let b = world.create_entity().build();
+    /// world.delete_entities(&[a, b]).unwrap();
+    ///
+    /// assert_eq!(world.create_entity().build().id(), a.id());
+    /// assert_eq!(world.create_entity().build().id(), b.id());
+    /// ```
+    pub fn with_recycle_policy(policy: RecyclePolicy) -> World {
+        let world = World::new();
+        world.entities_mut().alloc.set_recycle_policy(policy);
+
+        world
+    }
+
     /// Registers a new component, adding the component storage.
     ///
     /// Calls `register_with_storage` with `Default::default()`.
     ///
     /// Does nothing if the component was already
-    /// registered.
+    /// registered; `try_register` reports that as an error instead.
     ///
     /// ## Examples
     ///
@@ -230,6 +458,8 @@ impl World {
     /// world.register::<Pos>();
     /// // Register all other components like this
     /// ```
+    ///
+    /// Use `register_with` to configure the storage before registering.
     pub fn register<T: Component>(&mut self)
     where
         T::Storage: Default,
@@ -237,6 +467,144 @@ impl World {
         self.register_with_storage::<_, T>(Default::default);
     }
 
+    /// Returns a `Registration` for configuring the storage of a new
+    /// component; it's registered by `Registration::build`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world
+    ///     .register_with::<Pos>()
+    ///     .with_capacity(1024)
+    ///     .auto_reset_tracked()
+    ///     .build();
+    /// ```
+    pub fn register_with<T: Component>(&mut self) -> Registration<T>
+    where
+        T::Storage: Default,
+    {
+        Registration {
+            world: self,
+            storage: None,
+            reset: None,
+            rollback: None,
+            cloning: None,
+            name: None,
+        }
+    }
+
+    /// Registers a new component like `register`, but returns an error
+    /// instead of doing nothing if the component was already registered.
+    ///
+    /// This allows e.g. plugins which can't coordinate with each other to
+    /// detect that a component is already used by another one.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::any::TypeId;
+    /// use specs::error::RegisterError;
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// assert_eq!(world.try_register::<Pos>(), Ok(()));
+    /// assert_eq!(
+    ///     world.try_register::<Pos>(),
+    ///     Err(RegisterError::AlreadyRegistered(TypeId::of::<Pos>()))
+    /// );
+    /// ```
+    pub fn try_register<T: Component>(&mut self) -> Result<(), RegisterError>
+    where
+        T::Storage: Default,
+    {
+        let registered = self
+            .read_resource::<DynComponents>()
+            .vtable(TypeId::of::<T>())
+            .is_some();
+        if registered {
+            return Err(RegisterError::AlreadyRegistered(TypeId::of::<T>()));
+        }
+        self.register::<T>();
+
+        Ok(())
+    }
+
+    /// Removes a component type from the world, e.g. when unloading the
+    /// plugin which registered it: all components are removed (calling
+    /// `Component::on_remove`), the storage is replaced by an empty one and
+    /// the component is forgotten by `registered_components`,
+    /// `auto_reset_tracked`, `enable_rollback` and `enable_cloning`.
+    ///
+    /// Afterwards, the world behaves as if the component had never been
+    /// registered: `maintain` doesn't clean up the storage anymore,
+    /// `try_register` succeeds and registering it again starts with a new
+    /// storage. The storage can still be fetched in the meantime, it's just
+    /// empty; readers of a tracked storage have to be registered again.
+    /// Since this takes the world mutably, the storage can't be borrowed
+    /// while it's being removed.
+    ///
+    /// Returns an error if the component isn't registered.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(f32, f32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.create_entity().with(Pos(0.0, 0.0)).build();
+    ///
+    /// world.unregister::<Pos>().unwrap();
+    /// assert!(world.unregister::<Pos>().is_err());
+    ///
+    /// world.register::<Pos>();
+    /// assert_eq!(world.read_storage::<Pos>().join().count(), 0);
+    /// ```
+    pub fn unregister<T: Component>(&mut self) -> Result<(), NotRegistered> {
+        let id = TypeId::of::<T>();
+        if self.read_resource::<DynComponents>().vtable(id).is_none() {
+            return Err(NotRegistered { component: id });
+        }
+
+        {
+            // `Resources` can't remove the storage, so it's replaced instead;
+            // dropping the old one removes all components.
+            let storage = self.res.get_mut::<MaskedStorage<T>>().unwrap();
+            match TryDefault::try_default() {
+                Ok(inner) => *storage = MaskedStorage::new(inner),
+                Err(_) => storage.clear(),
+            }
+        }
+
+        self.write_resource::<DynComponents>().unregister(id);
+        self.write_resource::<ComponentHooks>()
+            .0
+            .retain(|&(other, _)| other != id);
+
+        // `MetaTable` can't unregister a single storage either.
+        let mut storages = MetaTable::new();
+        self.read_resource::<DynComponents>()
+            .register_any(&self.res, &mut storages);
+        *self.any_storages() = storages;
+
+        Ok(())
+    }
+
     /// Registers a new component with a given storage.
     ///
     /// Does nothing if the component was already registered.
@@ -256,14 +624,45 @@ impl World {
         F: FnOnce() -> T::Storage,
         T: Component,
     {
-        res.entry()
-            .or_insert_with(move || MaskedStorage::<T>::new(storage()));
+        // The storage of an unregistered component is still there, but
+        // should be replaced like a missing one.
+        if res.fetch::<DynComponents>().vtable(TypeId::of::<T>()).is_none() {
+            res.insert(MaskedStorage::<T>::new(storage()));
+        }
         res.fetch_mut::<MetaTable<AnyStorage>>()
             .register(&*res.fetch::<MaskedStorage<T>>());
+        res.fetch_mut::<DynComponents>().register::<T>();
+    }
+
+    /// Returns the metadata of all registered components, in the order
+    /// they were registered.
+    ///
+    /// This allows tools like inspectors to list the components of a world
+    /// without knowing the types; use `get_dyn` and `insert_dyn` to access
+    /// them by their `TypeId`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos; impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register_with::<Pos>().with_name("Pos").build();
+    ///
+    /// let names: Vec<_> = world.registered_components().iter().map(|c| c.name).collect();
+    /// assert_eq!(names, vec!["Pos"]);
+    /// ```
+    pub fn registered_components(&self) -> Vec<ComponentInfo> {
+        self.read_resource::<DynComponents>().infos().to_vec()
     }
 
     /// Gets `SystemData` `T` from the `World`.
     ///
+    /// `T` can be any combination of storages and resources, fetched the
+    /// same way the dispatcher fetches them for a system, so helpers can be
+    /// generic over the data they need instead of calling `read_storage`,
+    /// `write_resource` etc. one by one.
+    ///
     /// # Examples
     ///
     /// ```
@@ -396,6 +795,36 @@ impl World {
         }
     }
 
+    /// Adds the default value of a resource, unless it already exists.
+    ///
+    /// Systems fetching a resource with `Read` or `Write` insert its default
+    /// in `System::setup`, but if a system is run without being set up, or
+    /// fetches the resource with `ReadExpect` or `WriteExpect`, a missing
+    /// resource causes a panic. Calling this for the resources which have a
+    /// sensible default avoids that; resources without a call to this
+    /// still have to be added explicitly.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Score(u32);
+    ///
+    /// let mut world = World::new();
+    /// world.ensure_resource::<Score>();
+    /// assert_eq!(world.read_resource::<Score>().0, 0);
+    ///
+    /// // Doesn't overwrite existing resources.
+    /// world.write_resource::<Score>().0 = 5;
+    /// world.ensure_resource::<Score>();
+    /// assert_eq!(world.read_resource::<Score>().0, 5);
+    /// ```
+    pub fn ensure_resource<T: Resource + Default>(&mut self) {
+        self.res.entry::<T>().or_insert_with(T::default);
+    }
+
     /// Fetches a component's storage for reading.
     ///
     /// ## Panics
@@ -481,6 +910,100 @@ impl World {
         }
     }
 
+    /// Allows building an entity which has the stable id `id`, see
+    /// `StableIds`.
+    ///
+    /// Returns an error if `id` is already used by an entity which is alive.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// let e = world.create_entity_with_id(42).unwrap().build();
+    ///
+    /// assert_eq!(world.entity_by_id(42), Some(e));
+    /// assert!(world.create_entity_with_id(42).is_err());
+    /// ```
+    pub fn create_entity_with_id<'a>(
+        &'a mut self,
+        id: u64,
+    ) -> Result<EntityBuilder<'a>, IdInUse> {
+        if let Some(entity) = self.entity_by_id(id) {
+            return Err(IdInUse { id, entity });
+        }
+
+        let builder = self.create_entity_unchecked();
+        let entities = self.entities();
+        self.write_resource::<StableIds>()
+            .insert(id, builder.entity, &entities)?;
+
+        Ok(builder)
+    }
+
+    /// Returns the entity which has the stable id `id`, if it's alive.
+    pub fn entity_by_id(&self, id: u64) -> Option<Entity> {
+        self.read_resource::<StableIds>().get(id, &self.entities())
+    }
+
+    /// Inserts a type-erased component for an entity, for cases where the
+    /// component type is only known at runtime (e.g. scripting).
+    ///
+    /// `component` is the `TypeId` of the component type, and `value` has
+    /// to be of that type. Only registered components can be inserted this
+    /// way.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::any::TypeId;
+    /// use specs::prelude::*;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Pos(f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// let e = world.create_entity().build();
+    ///
+    /// world
+    ///     .insert_dyn(e, TypeId::of::<Pos>(), Box::new(Pos(1.0)))
+    ///     .unwrap();
+    /// assert!(world.insert_dyn(e, TypeId::of::<Pos>(), Box::new(1.0)).is_err());
+    ///
+    /// let pos = world.get_dyn(e, TypeId::of::<Pos>()).unwrap();
+    /// assert_eq!(pos.downcast_ref::<Pos>(), Some(&Pos(1.0)));
+    /// ```
+    pub fn insert_dyn(&self, e: Entity, component: TypeId, value: Box<Any>) -> Result<(), Error> {
+        let vtable = self
+            .read_resource::<DynComponents>()
+            .vtable(component)
+            .ok_or(NotRegistered { component })?;
+
+        (vtable.insert)(&self.res, e, value)
+    }
+
+    /// Returns the type-erased component of an entity, by the `TypeId` of
+    /// the component type.
+    ///
+    /// Returns `None` if the component type isn't registered, the entity
+    /// is dead or it doesn't have the component. See `insert_dyn` for an
+    /// example.
+    pub fn get_dyn(&mut self, e: Entity, component: TypeId) -> Option<&Any> {
+        if !self.entities().is_alive(e) {
+            return None;
+        }
+
+        let vtable = self.read_resource::<DynComponents>().vtable(component)?;
+
+        (vtable.get)(&mut self.res, e.id())
+    }
+
     /// Returns an iterator for entity creation.
     /// This makes it easy to create a whole collection
     /// of them.
@@ -552,9 +1075,27 @@ impl World {
     /// and deleted entities into the persistent generations vector.
     /// Also removes all the abandoned components.
     ///
-    /// Additionally, `LazyUpdate` will be merged.
-    pub fn maintain(&mut self) {
-        let deleted = self.entities_mut().alloc.merge();
+    /// Additionally, `LazyUpdate` will be merged and storages opted in
+    /// with `auto_reset_tracked` will be reset, after running the hooks
+    /// added with `add_maintain_hook`. Finally, the `FrameCounter` is
+    /// incremented.
+    ///
+    /// Returns `true` if any entity has been created or deleted (or moved
+    /// by `compact`) since the last call, so work depending only on the set
+    /// of entities, like rebuilding a spatial index, can be skipped
+    /// otherwise. Entities created by `LazyUpdate` or the maintain hooks
+    /// during this call are reported by the next one.
+    ///
+    /// With the `parallel` feature, the components of deleted entities are
+    /// removed from all storages in parallel if a thread pool has been set
+    /// with `set_thread_pool`.
+    pub fn maintain(&mut self) -> bool {
+        let (deleted, changed) = {
+            let mut entities = self.entities_mut();
+            let deleted = entities.alloc.merge();
+
+            (deleted, entities.alloc.take_changed())
+        };
         if !deleted.is_empty() {
             self.delete_components(&deleted);
         }
@@ -563,15 +1104,522 @@ impl World {
         let mut lazy = self.write_resource::<LazyUpdate>().take();
         lazy.maintain(&mut *self);
         self.write_resource::<LazyUpdate>().restore(lazy);
+
+        let mut hooks = mem::replace(&mut self.write_resource::<MaintainHooks>().0, Vec::new());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut i = 0;
+            while i < hooks.len() {
+                if (hooks[i])(self) {
+                    i += 1;
+                } else {
+                    drop(hooks.remove(i));
+                }
+            }
+        }));
+        // hooks added by hooks run from the next `maintain` on
+        let mut added = mem::replace(&mut self.write_resource::<MaintainHooks>().0, hooks);
+        self.write_resource::<MaintainHooks>().0.append(&mut added);
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+
+        let resets = self.read_resource::<ComponentHooks>().resets();
+        for reset in resets {
+            reset(&self.res);
+        }
+
+        self.write_resource::<FrameCounter>().advance();
+
+        changed
+    }
+
+    /// Sets the thread pool `maintain` cleans up storages on.
+    ///
+    /// This should be the pool given to `DispatcherBuilder::with_pool`, so
+    /// `maintain` doesn't keep the threads of another pool around. Without
+    /// a pool, storages are cleaned up one after another.
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_pool(&mut self, pool: Arc<ThreadPool>) {
+        self.add_resource(MaintainPool(pool));
+    }
+
+    /// Moves all alive entities to the lowest indices and their components
+    /// along with them, so storages indexed by entity ids (like
+    /// `VecStorage`) don't stay large after many entities have been deleted.
+    ///
+    /// This calls `maintain` first. Returns a map from the old handle to the
+    /// new one for every entity which has been moved; the order of entities
+    /// is kept.
+    ///
+    /// **This invalidates existing `Entity` handles of moved entities**,
+    /// e.g. ones stored in components or resources, unless they're looked up
+    /// in the returned map. `StableIds` are updated automatically.
+    /// Tracked storages report every move as a removal and an insertion.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// let entities: Vec<_> = world.create_iter().take(10).collect();
+    /// for &e in &entities[..9] {
+    ///     world.delete_entity(e).unwrap();
+    /// }
+    ///
+    /// let remap = world.compact();
+    /// let moved = remap[&entities[9]];
+    ///
+    /// assert_eq!(moved.id(), 0);
+    /// assert!(world.is_alive(moved));
+    /// assert!(!world.is_alive(entities[9]));
+    /// ```
+    pub fn compact(&mut self) -> FnvHashMap<Entity, Entity> {
+        self.maintain();
+
+        let moved = self.entities_mut().alloc.compact();
+        let moves: Vec<_> = moved
+            .iter()
+            .map(|&(from, to)| (from.id(), to.id()))
+            .collect();
+        for storage in self.any_storages().iter_mut(&self.res) {
+            storage.move_components(&moves);
+        }
+
+        let remap: FnvHashMap<_, _> = moved.into_iter().collect();
+        self.write_resource::<StableIds>().remap(&remap);
+
+        remap
+    }
+
+    /// Adds a callback which is run on every `maintain`, after entities
+    /// have been deleted and `LazyUpdate`s have been applied, but before
+    /// tracked storages are reset. Hooks run in the order they were added.
+    ///
+    /// This allows bookkeeping like spatial indices to stay in sync with the
+    /// entities of the world without having to be called manually.
+    ///
+    /// If a hook panics, the panic is propagated by `maintain` once all
+    /// hooks have been put back, so they still run on the next `maintain`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// #[derive(Default)]
+    /// struct Alive(usize);
+    ///
+    /// let mut world = World::new();
+    /// world.add_resource(Alive(0));
+    /// world.add_maintain_hook(|world: &mut World| {
+    ///     let alive = world.entities().join().count();
+    ///     world.write_resource::<Alive>().0 = alive;
+    /// });
+    ///
+    /// world.create_entity().build();
+    /// world.maintain();
+    /// assert_eq!(world.read_resource::<Alive>().0, 1);
+    /// ```
+    pub fn add_maintain_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&mut World) + Send + Sync + 'static,
+    {
+        let mut hook = hook;
+        self.write_resource::<MaintainHooks>()
+            .0
+            .push(Box::new(move |world: &mut World| {
+                hook(world);
+
+                true
+            }));
+    }
+
+    /// Returns `true` if any entity has a component of type `T`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `T` hasn't been registered or if its storage is borrowed
+    /// mutably.
+    pub fn has_any<T: Component>(&self) -> bool {
+        self.read_storage::<T>().mask().iter().next().is_some()
+    }
+
+    /// Runs `f` once, on the first `maintain` after a component of type
+    /// `T` has been inserted, e.g. to set up a subsystem only once it's
+    /// needed. If there already are components of type `T`, `f` runs on the
+    /// next `maintain`.
+    ///
+    /// This reads the insertion events of the tracked storage of `T`, so
+    /// components inserted with `LazyUpdate` count, and so do components
+    /// which have been removed again before `maintain`. The hook is removed
+    /// once `f` has run.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct RigidBody;
+    /// impl Component for RigidBody {
+    ///     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    /// }
+    ///
+    /// struct PhysicsActive(bool);
+    ///
+    /// let mut world = World::new();
+    /// world.register::<RigidBody>();
+    /// world.add_resource(PhysicsActive(false));
+    /// world.on_first_insert::<RigidBody, _>(|world: &mut World| {
+    ///     world.write_resource::<PhysicsActive>().0 = true;
+    /// });
+    ///
+    /// world.maintain();
+    /// assert!(!world.read_resource::<PhysicsActive>().0);
+    ///
+    /// world.create_entity().with(RigidBody).build();
+    /// world.maintain();
+    /// assert!(world.read_resource::<PhysicsActive>().0);
+    /// ```
+    pub fn on_first_insert<T, F>(&mut self, f: F)
+    where
+        T: Component,
+        T::Storage: Tracked,
+        F: FnOnce(&mut World) + Send + Sync + 'static,
+    {
+        let mut existing = self.has_any::<T>();
+        let mut reader = self.write_storage::<T>().track_inserted();
+        let mut f = Some(f);
+        self.write_resource::<MaintainHooks>()
+            .0
+            .push(Box::new(move |world: &mut World| {
+                let inserted = world
+                    .read_storage::<T>()
+                    .inserted()
+                    .read(&mut reader)
+                    .next()
+                    .is_some();
+                if !mem::replace(&mut existing, false) && !inserted {
+                    return true;
+                }
+                if let Some(f) = f.take() {
+                    f(world);
+                }
+
+                false
+            }));
+    }
+
+    /// Makes `maintain` reset the tracked storage of `T` (see
+    /// `Storage::reset_tracked`), after all other maintenance is done.
+    ///
+    /// Calling this more than once for the same component does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` hasn't been registered.
+    pub fn auto_reset_tracked<T>(&mut self)
+    where
+        T: Component,
+        T::Storage: Tracked,
+    {
+        fn reset<T>(res: &Resources)
+        where
+            T: Component,
+            T::Storage: Tracked,
+        {
+            WriteStorage::<T>::fetch(res).reset_tracked();
+        }
+
+        // make sure it panics now, instead of on `maintain`
+        self.write_storage::<T>();
+
+        self.write_resource::<ComponentHooks>()
+            .entry(TypeId::of::<T>())
+            .reset = Some(reset::<T>);
+    }
+
+    /// Keeps the tracked storage of `T` from recording insertions,
+    /// modifications and removals until the returned guard is dropped, e.g.
+    /// to load a level without flooding reactive systems with events.
+    ///
+    /// Since the guard borrows the world, entities are created with
+    /// `create_entity_unchecked` in the meantime. Readers registered while
+    /// tracking is suppressed receive the events written after the guard
+    /// has been dropped, like all other readers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` hasn't been registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Tile(u8);
+    ///
+    /// impl Component for Tile {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Tile>();
+    /// let mut inserted = world.write_storage::<Tile>().track_inserted();
+    ///
+    /// {
+    ///     let _guard = world.suppress_tracking::<Tile>();
+    ///     for _ in 0..100 {
+    ///         world.create_entity_unchecked().with(Tile(0)).build();
+    ///     }
+    /// }
+    ///
+    /// let tiles = world.read_storage::<Tile>();
+    /// assert_eq!(tiles.join().count(), 100);
+    /// assert_eq!(tiles.inserted().read(&mut inserted).count(), 0);
+    /// ```
+    pub fn suppress_tracking<T>(&self) -> SuppressTracking<T>
+    where
+        T: Component,
+        T::Storage: Tracked,
+    {
+        let mut storage = self.write_storage::<T>();
+        let was_suppressed = storage.channels().is_suppressed();
+        storage.channels_mut().set_suppressed(true);
+
+        SuppressTracking {
+            world: self,
+            was_suppressed,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Runs `f`, undoing its changes to the entities and the components of
+    /// storages opted in with `enable_rollback` if it returns an error.
+    ///
+    /// On rollback, entities created by `f` are deleted with all their
+    /// components and entities deleted by `f` are alive again, with the
+    /// same handles as before. Components of storages which aren't opted
+    /// in can't be restored for those entities, though; they're only
+    /// removed from created ones. Resources, including `LazyUpdate`s queued
+    /// by `f`, aren't rolled back either.
+    ///
+    /// The rollback is done by taking a snapshot (see `Storage::snapshot`)
+    /// of the storages opted in before running `f`, so every transaction
+    /// clones all their components; keep transactions to batches of edits,
+    /// rather than wrapping whole frames. Restoring a snapshot doesn't call
+    /// `Component::on_remove` for the components it replaces. Handles of
+    /// entities created by a failed transaction must not be used afterwards.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `f` panics; the world isn't rolled back then.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Health(u32);
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register_with::<Health>().enable_rollback().build();
+    /// let e = world.create_entity().with(Health(10)).build();
+    ///
+    /// let result: Result<(), _> = world.transaction(|world: &mut World| {
+    ///     world.write_storage::<Health>().get_mut(e).unwrap().0 = 0;
+    ///     world.create_entity().with(Health(5)).build();
+    ///
+    ///     Err("the player can't die in the editor")
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// let health = world.read_storage::<Health>();
+    /// assert_eq!(health.get(e), Some(&Health(10)));
+    /// assert_eq!(health.join().count(), 1);
+    /// ```
+    pub fn transaction<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut World) -> Result<R, E>,
+    {
+        let entities = self.entities().alloc.snapshot();
+        let rollbacks = self.read_resource::<ComponentHooks>().rollbacks();
+        let snapshots: Vec<_> = rollbacks
+            .iter()
+            .map(|&(snapshot, _)| snapshot(&self.res))
+            .collect();
+
+        let result = f(self);
+
+        if result.is_err() {
+            let created = self.entities_mut().alloc.restore(entities);
+            self.delete_components(&created);
+
+            for (&(_, restore), snapshot) in rollbacks.iter().zip(snapshots) {
+                restore(&self.res, snapshot);
+            }
+        }
+
+        result
+    }
+
+    /// Makes `transaction` restore the components of `T` if it fails.
+    ///
+    /// Calling this more than once for the same component does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` hasn't been registered.
+    pub fn enable_rollback<T>(&mut self)
+    where
+        T: Component + Clone,
+    {
+        fn snapshot<T>(res: &Resources) -> Box<Any>
+        where
+            T: Component + Clone,
+        {
+            Box::new(ReadStorage::<T>::fetch(res).snapshot())
+        }
+
+        fn restore<T>(res: &Resources, snapshot: Box<Any>)
+        where
+            T: Component + Clone,
+        {
+            let snapshot = snapshot
+                .downcast::<StorageSnapshot<T>>()
+                .expect("Bug: snapshot of the wrong component type");
+            WriteStorage::<T>::fetch(res).restore_snapshot(*snapshot);
+        }
+
+        // make sure it panics now, instead of on `transaction`
+        self.write_storage::<T>();
+
+        self.write_resource::<ComponentHooks>()
+            .entry(TypeId::of::<T>())
+            .rollback = Some((snapshot::<T>, restore::<T>));
+    }
+
+    /// Creates a new entity with a copy of every component of `src`, e.g.
+    /// to instantiate a prefab.
+    ///
+    /// Only components opted in with `enable_cloning` (or
+    /// `Registration::enable_cloning`) are copied, since others may not be
+    /// `Clone`; other components of `src` are skipped with a warning. The
+    /// copies are inserted like with `Storage::insert`, so tracked storages
+    /// record them as inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is dead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Sprite(&'static str);
+    ///
+    /// impl Component for Sprite {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register_with::<Sprite>().enable_cloning().build();
+    /// let prefab = world.create_entity().with(Sprite("tree.png")).build();
+    ///
+    /// let tree = world.clone_entity(prefab);
+    /// assert_ne!(tree, prefab);
+    /// assert_eq!(
+    ///     world.read_storage::<Sprite>().get(tree),
+    ///     Some(&Sprite("tree.png"))
+    /// );
+    /// ```
+    pub fn clone_entity(&mut self, src: Entity) -> Entity {
+        assert!(self.is_alive(src), "Can't clone a dead entity");
+
+        let dst = self.create_entity().build();
+        let cloners = self.read_resource::<ComponentHooks>().cloners();
+        for &(_, clone) in &cloners {
+            clone(&self.res, src, dst);
+        }
+
+        let infos = self.registered_components();
+        for info in infos {
+            if cloners.iter().any(|&(id, _)| id == info.type_id) {
+                continue;
+            }
+            if self.get_dyn(src, info.type_id).is_some() {
+                warn!(
+                    "Component {} of {:?} not cloned, since cloning isn't enabled for it",
+                    info.name, src
+                );
+            }
+        }
+
+        dst
+    }
+
+    /// Makes `clone_entity` copy the components of `T`.
+    ///
+    /// Calling this more than once for the same component does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` hasn't been registered.
+    pub fn enable_cloning<T>(&mut self)
+    where
+        T: Component + Clone,
+    {
+        fn clone<T>(res: &Resources, src: Entity, dst: Entity)
+        where
+            T: Component + Clone,
+        {
+            let mut storage = WriteStorage::<T>::fetch(res);
+            let copy = match storage.get(src) {
+                Some(component) => component.clone(),
+                None => return,
+            };
+            storage
+                .insert(dst, copy)
+                .expect("Bug: cloned onto a dead entity");
+        }
+
+        // make sure it panics now, instead of on `clone_entity`
+        self.write_storage::<T>();
+
+        self.write_resource::<ComponentHooks>()
+            .entry(TypeId::of::<T>())
+            .clone = Some(clone::<T>);
     }
 
     fn delete_components(&mut self, delete: &[Entity]) {
+        #[cfg(feature = "parallel")]
+        {
+            if let Some(pool) = self.res.try_fetch::<MaintainPool>() {
+                use rayon::prelude::*;
+
+                let storages = self.any_storages();
+                let storages: Vec<_> = storages.iter_mut(&self.res).map(SendStorage).collect();
+                pool.0.install(|| {
+                    storages
+                        .into_par_iter()
+                        .for_each(|storage| storage.0.drop(delete))
+                });
+
+                return;
+            }
+        }
+
         for storage in self.any_storages().iter_mut(&self.res) {
             storage.drop(delete);
         }
     }
 
     /// Adds the given bundle of resources/components.
+    ///
+    /// See `Bundle` for an example.
     pub fn add_bundle<B>(&mut self, bundle: B)
     where
         B: Bundle,
@@ -584,6 +1632,20 @@ impl World {
     }
 }
 
+/// The thread pool set with `World::set_thread_pool`.
+#[cfg(feature = "parallel")]
+struct MaintainPool(Arc<ThreadPool>);
+
+/// A storage which can be cleaned up on another thread.
+///
+/// Only `MaskedStorage`s are registered as `AnyStorage`, which are `Send`
+/// since components are, and each storage is only accessed by one thread.
+#[cfg(feature = "parallel")]
+struct SendStorage<'a>(&'a mut (AnyStorage + 'static));
+
+#[cfg(feature = "parallel")]
+unsafe impl<'a> Send for SendStorage<'a> {}
+
 unsafe impl Send for World {}
 
 unsafe impl Sync for World {}
@@ -604,12 +1666,107 @@ impl Default for World {
         res.insert(EntitiesRes::default());
         res.insert(LazyUpdate::default());
         res.insert(MetaTable::<AnyStorage>::new());
+        res.insert(StableIds::default());
+        res.insert(DynComponents::default());
+        res.insert(ComponentHooks::default());
+        res.insert(FrameCounter::default());
+        res.insert(MaintainHooks::default());
 
         World { res }
     }
 }
 
+/// A hook run by `World::maintain`, which is removed once it returns `false`.
+type MaintainHook = Box<FnMut(&mut World) -> bool + Send + Sync>;
+
+/// Callbacks added with `World::add_maintain_hook`.
+#[derive(Default)]
+struct MaintainHooks(Vec<MaintainHook>);
+
+type ResetFn = fn(&Resources);
+type SnapshotFn = fn(&Resources) -> Box<Any>;
+type RestoreFn = fn(&Resources, Box<Any>);
+type CloneFn = fn(&Resources, Entity, Entity);
+
+/// The functions a component type has been opted in to.
+#[derive(Clone, Copy, Default)]
+struct Hooks {
+    /// Resets the storage on `World::maintain`, see
+    /// `World::auto_reset_tracked`.
+    reset: Option<ResetFn>,
+    /// Snapshots and restores the storage for `World::transaction`, see
+    /// `World::enable_rollback`.
+    rollback: Option<(SnapshotFn, RestoreFn)>,
+    /// Copies a component for `World::clone_entity`, see
+    /// `World::enable_cloning`.
+    clone: Option<CloneFn>,
+}
+
+/// The `Hooks` of every component type which has been opted in to one of
+/// them, in the order they have been first opted in.
+#[derive(Default)]
+struct ComponentHooks(Vec<(TypeId, Hooks)>);
+
+impl ComponentHooks {
+    fn entry(&mut self, id: TypeId) -> &mut Hooks {
+        let i = match self.0.iter().position(|&(other, _)| other == id) {
+            Some(i) => i,
+            None => {
+                self.0.push((id, Hooks::default()));
+                self.0.len() - 1
+            }
+        };
+
+        &mut self.0[i].1
+    }
+
+    fn resets(&self) -> Vec<ResetFn> {
+        self.0.iter().filter_map(|&(_, hooks)| hooks.reset).collect()
+    }
+
+    fn rollbacks(&self) -> Vec<(SnapshotFn, RestoreFn)> {
+        self.0.iter().filter_map(|&(_, hooks)| hooks.rollback).collect()
+    }
+
+    fn cloners(&self) -> Vec<(TypeId, CloneFn)> {
+        self.0
+            .iter()
+            .filter_map(|&(id, hooks)| hooks.clone.map(|clone| (id, clone)))
+            .collect()
+    }
+}
+
 /// Trait used to bundle up resources/components for easy registration with `World`.
+///
+/// Since registering a component twice does nothing, different bundles may
+/// register the same components without depending on the order in which
+/// they're added.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::prelude::*;
+/// use specs::world::Bundle;
+///
+/// struct Pos(f32);
+/// impl Component for Pos { type Storage = VecStorage<Self>; }
+///
+/// #[derive(Default)]
+/// struct Gravity(f32);
+///
+/// struct PhysicsBundle;
+///
+/// impl Bundle for PhysicsBundle {
+///     fn add_to_world(self, world: &mut World) {
+///         world.register::<Pos>();
+///         world.add_resource(Gravity(9.81));
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.add_bundle(PhysicsBundle);
+/// world.create_entity().with(Pos(0.0)).build();
+/// ```
 pub trait Bundle {
     /// Add resources/components to `world`.
     fn add_to_world(self, world: &mut World);
@@ -0,0 +1,81 @@
+use fnv::FnvHashMap;
+
+use error::IdInUse;
+use world::{EntitiesRes, Entity};
+
+/// Maps stable ids, chosen by the caller, to entities.
+///
+/// Unlike the index of an `Entity`, which depends on the order entities
+/// have been created and deleted in, a stable id is authoritative: e.g. a
+/// server can tell its clients which id an entity has, and all of them can
+/// look it up, no matter how their own entities have been allocated.
+///
+/// An id becomes free again as soon as its entity is no longer alive.
+///
+/// This is added to the `World` by default, and used by
+/// `World::create_entity_with_id` and `World::entity_by_id`.
+#[derive(Debug, Default)]
+pub struct StableIds {
+    ids: FnvHashMap<u64, Entity>,
+}
+
+impl StableIds {
+    /// Associates `id` with an existing entity.
+    ///
+    /// Returns an error if `id` is used by another entity which is alive.
+    pub fn insert(
+        &mut self,
+        id: u64,
+        entity: Entity,
+        entities: &EntitiesRes,
+    ) -> Result<(), IdInUse> {
+        if let Some(other) = self.get(id, entities) {
+            if other != entity {
+                return Err(IdInUse { id, entity: other });
+            }
+        }
+
+        self.ids.insert(id, entity);
+
+        Ok(())
+    }
+
+    /// Atomically creates an entity with the stable id `id`.
+    ///
+    /// Just like with `EntitiesRes::create`, the entity is only visible
+    /// to joins after `World::maintain`.
+    pub fn create(&mut self, id: u64, entities: &EntitiesRes) -> Result<Entity, IdInUse> {
+        if let Some(entity) = self.get(id, entities) {
+            return Err(IdInUse { id, entity });
+        }
+
+        let entity = entities.create();
+        self.ids.insert(id, entity);
+
+        Ok(entity)
+    }
+
+    /// Returns the entity with the stable id `id`, if it's alive.
+    pub fn get(&self, id: u64, entities: &EntitiesRes) -> Option<Entity> {
+        self.ids
+            .get(&id)
+            .cloned()
+            .filter(|&e| entities.is_alive(e))
+    }
+
+    /// Replaces the entities which have been moved by `World::compact`.
+    ///
+    /// `World::compact` already does this for the `StableIds` of the world.
+    pub fn remap(&mut self, remap: &FnvHashMap<Entity, Entity>) {
+        for entity in self.ids.values_mut() {
+            if let Some(&new) = remap.get(entity) {
+                *entity = new;
+            }
+        }
+    }
+
+    /// Forgets the ids of entities which aren't alive anymore.
+    pub fn prune(&mut self, entities: &EntitiesRes) {
+        self.ids.retain(|_, e| entities.is_alive(*e));
+    }
+}
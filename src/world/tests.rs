@@ -57,6 +57,45 @@ fn lazy_insertion() {
     assert!(world.read_storage::<Vel>().get(e2).is_some());
 }
 
+#[test]
+fn lazy_registration_tracked() {
+    use storage::{ChangeKind, FieldDiff, FieldFlaggedStorage};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Health(u32);
+
+    impl FieldDiff for Health {
+        type Fields = bool;
+
+        fn field_diff(old: &Self, new: &Self) -> bool {
+            old != new
+        }
+    }
+
+    impl Component for Health {
+        type Storage = FieldFlaggedStorage<Self>;
+    }
+
+    let mut world = World::new();
+    let e = world.create_entity().build();
+    world.read_resource::<LazyUpdate>().register::<Health>();
+    world.maintain();
+
+    let mut health = world.write_storage::<Health>();
+    let mut readers = health.track_all();
+    assert_eq!(health.change_records(&mut readers).count(), 0);
+
+    health.insert(e, Health(10)).unwrap();
+    assert!(!health.field_changes(e));
+
+    let records: Vec<_> = health.change_records(&mut readers).collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].entity, e);
+    assert_eq!(records[0].kind, ChangeKind::Inserted);
+    assert_eq!(records[0].old, None);
+    assert_eq!(records[0].current, Some(&Health(10)));
+}
+
 #[test]
 fn lazy_removal() {
     let mut world = World::new();
@@ -124,6 +163,371 @@ fn delete_twice() {
     assert!(world.entities().delete(e).is_err());
 }
 
+#[test]
+fn wrong_generation_message() {
+    let mut world = World::new();
+
+    let e = world.create_entity().build();
+    world.delete_entity(e).unwrap();
+    world.maintain();
+
+    let err = world.delete_entity(e).unwrap_err();
+    assert_eq!(err.entity, e);
+    assert_eq!(err.supplied_gen(), e.gen());
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Tried to delete entity {} with generation 1, but the current generation is 1 (dead)",
+            e.id()
+        )
+    );
+
+    let e2 = world.create_entity().build();
+    let err = world.delete_entity(e).unwrap_err();
+    assert_eq!(err.actual_gen, e2.gen());
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Tried to delete entity {} with generation 1, but the current generation is 2",
+            e.id()
+        )
+    );
+}
+
+#[test]
+fn stable_ids() {
+    let mut world = World::new();
+    world.register::<Pos>();
+
+    // Entities created in a different order get the same stable ids.
+    world.create_entity().build();
+    let a = world.create_entity_with_id(7).unwrap().with(Pos).build();
+    assert_eq!(world.entity_by_id(7), Some(a));
+    assert_eq!(
+        world.create_entity_with_id(7).err(),
+        Some(::error::IdInUse { id: 7, entity: a })
+    );
+
+    let b = {
+        let entities = world.entities();
+        world
+            .write_resource::<StableIds>()
+            .create(8, &entities)
+            .unwrap()
+    };
+    world.maintain();
+    assert_eq!(world.entity_by_id(8), Some(b));
+
+    // The id is free again once the entity is deleted.
+    world.delete_entity(a).unwrap();
+    assert_eq!(world.entity_by_id(7), None);
+    let c = world.create_entity_with_id(7).unwrap().build();
+    assert_eq!(world.entity_by_id(7), Some(c));
+}
+
+#[test]
+fn compact() {
+    #[derive(Debug, PartialEq)]
+    struct Num(u32);
+
+    impl Component for Num {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Num>();
+
+    let entities: Vec<_> = (0..20)
+        .map(|i| world.create_entity().with(Num(i)).build())
+        .collect();
+    let kept = world.create_entity_with_id(3).unwrap().with(Num(20)).build();
+    for (i, &e) in entities.iter().enumerate() {
+        if i % 4 != 0 {
+            world.delete_entity(e).unwrap();
+        }
+    }
+    // Deleted atomically, so it's only dead after `maintain`.
+    world.entities().delete(entities[16]).unwrap();
+
+    let remap = world.compact();
+    assert_eq!(remap.len(), 4);
+    assert!(!remap.contains_key(&entities[0]));
+
+    let nums = world.read_storage::<Num>();
+    let compacted: Vec<_> = (&*world.entities(), &nums)
+        .join()
+        .map(|(e, n)| (e.id(), n.0))
+        .collect();
+    assert_eq!(compacted, vec![(0, 0), (1, 4), (2, 8), (3, 12), (4, 20)]);
+
+    assert_eq!(nums.get(remap[&entities[8]]), Some(&Num(8)));
+    assert_eq!(nums.get(entities[8]), None);
+    assert_eq!(world.entity_by_id(3), Some(remap[&kept]));
+    drop(nums);
+
+    // Freed indices are reused with new generations.
+    let e = world.create_entity().build();
+    assert_eq!(e.id(), 5);
+    assert!(world.is_alive(e));
+    let old: Vec<_> = remap.keys().cloned().collect();
+    let new: Vec<_> = world.create_iter().take(20).collect();
+    assert!(new.iter().all(|e| !old.contains(e)));
+    assert!(old.iter().all(|&e| !world.is_alive(e)));
+}
+
+#[test]
+fn dynamic_access() {
+    use std::any::TypeId;
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Name>();
+    let e = world.create_entity().build();
+    let name = TypeId::of::<Name>();
+
+    assert!(world.get_dyn(e, name).is_none());
+    world.insert_dyn(e, name, Box::new(Name("a"))).unwrap();
+    world.insert_dyn(e, name, Box::new(Name("b"))).unwrap();
+    assert_eq!(
+        world.get_dyn(e, name).and_then(|c| c.downcast_ref()),
+        Some(&Name("b"))
+    );
+    assert_eq!(world.read_storage::<Name>().get(e), Some(&Name("b")));
+
+    match world.insert_dyn(e, name, Box::new("c")) {
+        Err(::error::Error::WrongType(err)) => assert_eq!(err.expected, name),
+        _ => panic!("expected a type mismatch"),
+    }
+
+    let pos = TypeId::of::<Pos>();
+    assert!(world.get_dyn(e, pos).is_none());
+    match world.insert_dyn(e, pos, Box::new(Pos)) {
+        Err(::error::Error::NotRegistered(err)) => assert_eq!(err.component, pos),
+        _ => panic!("expected an unregistered component"),
+    }
+
+    world.delete_entity(e).unwrap();
+    assert!(world.get_dyn(e, name).is_none());
+}
+
+#[test]
+fn register_with_capacity() {
+    use storage::DenseVecStorage;
+
+    struct Dense(u32);
+
+    impl Component for Dense {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register_with::<Dense>().with_capacity(100).build();
+    assert!(world.read_storage::<Dense>().values().is_empty());
+
+    let e = world.create_entity().with(Dense(1)).build();
+    assert_eq!(world.read_storage::<Dense>().values().len(), 1);
+
+    // Registering again keeps the existing storage.
+    world.register_with::<Dense>().with_capacity(5).build();
+    assert_eq!(world.read_storage::<Dense>().get(e).map(|d| d.0), Some(1));
+}
+
+#[test]
+fn register_auto_reset_tracked() {
+    use storage::{FieldDiff, FieldFlaggedStorage};
+
+    #[derive(Clone)]
+    struct Health(u32);
+
+    impl FieldDiff for Health {
+        type Fields = bool;
+
+        fn field_diff(old: &Self, new: &Self) -> bool {
+            old.0 != new.0
+        }
+    }
+
+    impl Component for Health {
+        type Storage = FieldFlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world
+        .register_with::<Health>()
+        .with_capacity(10)
+        .auto_reset_tracked()
+        .build();
+    let e = world.create_entity().with(Health(3)).build();
+
+    {
+        let mut health = world.write_storage::<Health>();
+        health.get_mut(e).unwrap().0 = 2;
+        health.maintain_fields();
+        assert!(health.field_changes(e));
+    }
+
+    world.maintain();
+    assert!(!world.read_storage::<Health>().field_changes(e));
+}
+
+#[test]
+fn removals_survive_reset() {
+    use hibitset::{BitSet, BitSetLike};
+    use storage::FlaggedStorage;
+
+    struct Marker;
+
+    impl Component for Marker {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world.register_with::<Marker>().auto_reset_tracked().build();
+    let mut removed_id = world.write_storage::<Marker>().track_removed();
+
+    let e = world.create_entity().with(Marker).build();
+    world.write_storage::<Marker>().remove(e);
+
+    // Two frames end before the removal is handled.
+    world.maintain();
+    world.maintain();
+
+    let mut removed = BitSet::new();
+    world
+        .read_storage::<Marker>()
+        .populate_removed(&mut removed_id, &mut removed);
+    assert!(removed.contains(e.id()));
+
+    // Once read, it's gone for this reader.
+    removed.clear();
+    world.maintain();
+    world
+        .read_storage::<Marker>()
+        .populate_removed(&mut removed_id, &mut removed);
+    assert!(removed.iter().next().is_none());
+}
+
+#[test]
+fn entity_same_index() {
+    let mut world = World::new();
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().build();
+    assert!(a.same_index(a));
+    assert!(!a.same_index(b));
+
+    let mut previous = a;
+    for _ in 0..3 {
+        world.delete_entity(previous).unwrap();
+        let recycled = world.create_entity().build();
+
+        assert_ne!(recycled, previous);
+        assert!(recycled.same_index(previous));
+        assert!(recycled.same_index(a));
+        assert!(!recycled.same_index(b));
+
+        previous = recycled;
+    }
+}
+
+#[test]
+fn maintain_hooks() {
+    #[derive(Default)]
+    struct Seen(Vec<(&'static str, usize, usize)>);
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.add_resource(Seen::default());
+
+    for name in &["first", "second"] {
+        world.add_maintain_hook(move |world: &mut World| {
+            let alive = world.entities().join().count();
+            let with_pos = world.read_storage::<Pos>().join().count();
+            world
+                .write_resource::<Seen>()
+                .0
+                .push((*name, alive, with_pos));
+        });
+    }
+
+    let a = world.create_entity().with(Pos).build();
+    world.create_entity().with(Pos).build();
+    world.maintain();
+
+    world.delete_entity(a).unwrap();
+    world.maintain();
+
+    assert_eq!(
+        world.read_resource::<Seen>().0,
+        vec![("first", 2, 2), ("second", 2, 2), ("first", 1, 1), ("second", 1, 1)]
+    );
+}
+
+#[test]
+fn maintain_hooks_survive_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[derive(Default)]
+    struct Runs(u32);
+
+    let mut world = World::new();
+    world.add_resource(Runs::default());
+    world.add_maintain_hook(|world: &mut World| {
+        world.write_resource::<Runs>().0 += 1;
+        if world.read_resource::<Runs>().0 == 1 {
+            panic!("first run");
+        }
+    });
+    world.add_maintain_hook(|world: &mut World| {
+        world.write_resource::<Runs>().0 += 10;
+    });
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| world.maintain()));
+    assert!(result.is_err());
+    assert_eq!(world.read_resource::<Runs>().0, 1);
+
+    world.maintain();
+    assert_eq!(world.read_resource::<Runs>().0, 12);
+}
+
+#[test]
+fn builder_with_default() {
+    use storage::FlaggedStorage;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world.register::<Health>();
+    let mut inserted_id = world.write_storage::<Health>().track_inserted();
+
+    let e = world.create_entity().with_default::<Health>().build();
+    let lazy = world
+        .read_resource::<LazyUpdate>()
+        .create_entity(&world.entities())
+        .with_default::<Health>()
+        .build();
+    world.maintain();
+
+    let health = world.read_storage::<Health>();
+    assert_eq!(health.get(e), Some(&Health(0)));
+    assert_eq!(health.get(lazy), Some(&Health(0)));
+
+    let mut inserted = Vec::new();
+    health.populate_inserted(&mut inserted_id, &mut inserted);
+    assert_eq!(inserted, vec![e.id(), lazy.id()]);
+}
+
 #[test]
 fn test_bundle() {
     let mut world = World::new();
@@ -144,6 +548,33 @@ fn test_bundle() {
     assert_eq!(12, world.read_resource::<SomeResource>().v);
 }
 
+#[test]
+fn bundle_components_and_resource() {
+    #[derive(Default)]
+    struct Gravity(f32);
+
+    struct PhysicsBundle;
+
+    impl Bundle for PhysicsBundle {
+        fn add_to_world(self, world: &mut World) {
+            world.register::<Pos>();
+            world.register::<Vel>();
+            world.add_resource(Gravity(9.81));
+        }
+    }
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    let e = world.create_entity().with(Pos).build();
+
+    world.add_bundle(PhysicsBundle);
+
+    // Already registered storages are kept
+    assert!(world.read_storage::<Pos>().get(e).is_some());
+    world.create_entity().with(Pos).with(Vel).build();
+    assert_eq!(9.81, world.read_resource::<Gravity>().0);
+}
+
 #[test]
 fn delete_and_lazy() {
     let mut world = World::new();
@@ -164,3 +595,673 @@ fn delete_and_lazy() {
 
     world.delete_all();
 }
+
+#[test]
+fn bulk_load_unchecked() {
+    struct Id(u32);
+
+    impl Component for Id {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Id>();
+    world.register::<Pos>();
+
+    let loaded: Vec<_> = {
+        let entities = world.entities();
+        let mut ids = world.write_storage::<Id>();
+        let mut pos = world.write_storage::<Pos>();
+
+        entities
+            .create_iter()
+            .take(100_000)
+            .enumerate()
+            .map(|(i, e)| {
+                unsafe {
+                    ids.insert_unchecked(e, Id(i as u32));
+                    if i % 2 == 0 {
+                        pos.insert_unchecked(e, Pos);
+                    }
+                }
+                e
+            })
+            .collect()
+    };
+
+    world.maintain();
+
+    let ids = world.read_storage::<Id>();
+    let pos = world.read_storage::<Pos>();
+    assert_eq!((&*world.entities(), &ids).join().count(), 100_000);
+    assert_eq!((&*world.entities(), &pos).join().count(), 50_000);
+    for (i, &e) in loaded.iter().enumerate() {
+        assert!(world.is_alive(e));
+        assert_eq!(ids.get(e).unwrap().0, i as u32);
+        assert_eq!(pos.get(e).is_some(), i % 2 == 0);
+    }
+}
+
+#[test]
+fn frame_counter() {
+    use world::FrameCounter;
+
+    let mut world = World::new();
+    assert_eq!(world.read_resource::<FrameCounter>().frame(), 0);
+
+    for frame in 1..4 {
+        world.maintain();
+        assert_eq!(world.read_resource::<FrameCounter>().frame(), frame);
+    }
+
+    world.write_resource::<FrameCounter>().set(100);
+    world.maintain();
+    assert_eq!(world.read_resource::<FrameCounter>().frame(), 101);
+
+    world.write_resource::<FrameCounter>().reset();
+    assert_eq!(world.read_resource::<FrameCounter>().frame(), 0);
+}
+
+#[test]
+fn system_data_mixed() {
+    use shred::Write;
+    use storage::ReadStorage;
+
+    struct Value(u32);
+
+    impl Component for Value {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Default)]
+    struct Sum(u32);
+
+    let mut world = World::new();
+    world.register::<Value>();
+    world.add_resource(Sum(0));
+    world.create_entity().with(Value(1)).build();
+    world.create_entity().with(Value(2)).build();
+
+    {
+        let (values, mut sum): (ReadStorage<Value>, Write<Sum>) = world.system_data();
+        sum.0 = (&values).join().map(|v| v.0).sum();
+    }
+
+    assert_eq!(world.read_resource::<Sum>().0, 3);
+}
+
+#[test]
+fn ensure_resource() {
+    use shred::{ReadExpect, RunNow, System};
+
+    #[derive(Default)]
+    struct Gravity(u32);
+
+    struct Fall(u32);
+
+    impl<'a> System<'a> for Fall {
+        type SystemData = ReadExpect<'a, Gravity>;
+
+        fn run(&mut self, gravity: Self::SystemData) {
+            self.0 += gravity.0 + 1;
+        }
+    }
+
+    let mut world = World::new();
+    world.ensure_resource::<Gravity>();
+
+    let mut fall = Fall(0);
+    fall.run_now(&world.res);
+    assert_eq!(fall.0, 1);
+
+    world.write_resource::<Gravity>().0 = 9;
+    world.ensure_resource::<Gravity>();
+    fall.run_now(&world.res);
+    assert_eq!(fall.0, 11);
+}
+
+#[test]
+fn on_first_insert() {
+    use storage::FlaggedStorage;
+
+    struct Body;
+
+    impl Component for Body {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Default)]
+    struct Fired(u32);
+
+    let mut world = World::new();
+    world.register::<Body>();
+    world.add_resource(Fired(0));
+    world.on_first_insert::<Body, _>(|world: &mut World| {
+        world.write_resource::<Fired>().0 += 1;
+    });
+
+    assert!(!world.has_any::<Body>());
+    world.maintain();
+    assert_eq!(world.read_resource::<Fired>().0, 0);
+
+    // Inserted and removed again before `maintain`.
+    let a = world.create_entity().with(Body).build();
+    world.write_storage::<Body>().remove(a);
+    assert!(!world.has_any::<Body>());
+    world.maintain();
+    assert_eq!(world.read_resource::<Fired>().0, 1);
+
+    world.create_entity().with(Body).build();
+    world.create_entity().with(Body).build();
+    assert!(world.has_any::<Body>());
+    world.maintain();
+    world.delete_all();
+    world.maintain();
+    world.create_entity().with(Body).build();
+    world.maintain();
+    assert_eq!(world.read_resource::<Fired>().0, 1);
+}
+
+#[test]
+fn on_first_insert_existing() {
+    use storage::FlaggedStorage;
+
+    struct Body;
+
+    impl Component for Body {
+        type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Default)]
+    struct Fired(u32);
+
+    let mut world = World::new();
+    world.register::<Body>();
+    world.add_resource(Fired(0));
+    world.create_entity().with(Body).build();
+    world.maintain();
+
+    world.on_first_insert::<Body, _>(|world: &mut World| {
+        world.write_resource::<Fired>().0 += 1;
+    });
+    world.maintain();
+    world.maintain();
+    assert_eq!(world.read_resource::<Fired>().0, 1);
+}
+
+#[test]
+fn entity_bits() {
+    for &(index, gen) in &[(0, 1), (1, 1), (7, 2), (!0, 1), (42, i32::max_value()), (3, -5)] {
+        let e = Entity::new(index, Generation::new(gen));
+        assert_eq!(Entity::from_bits(e.to_bits()), e);
+    }
+
+    let mut world = World::new();
+    let a = world.create_entity().build();
+    world.delete_entity(a).unwrap();
+    world.maintain();
+    let b = world.create_entity().build();
+
+    assert!(a.same_index(b));
+    assert_ne!(a.to_bits(), b.to_bits());
+    assert_eq!(Entity::from_bits(b.to_bits()), b);
+    assert!(world.is_alive(Entity::from_bits(b.to_bits())));
+    assert!(!world.is_alive(Entity::from_bits(a.to_bits())));
+}
+
+#[test]
+fn transaction_rollback() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.register_with::<Health>().enable_rollback().build();
+
+    let a = world.create_entity().with(Health(1)).build();
+    let b = world.create_entity().with(Health(2)).with(Pos).build();
+    let c = world.create_entity().build();
+    world.delete_entity(c).unwrap();
+
+    let result: Result<(), &str> = world.transaction(|world: &mut World| {
+        world.write_storage::<Health>().get_mut(a).unwrap().0 = 10;
+        world.write_storage::<Health>().remove(b);
+        world.delete_entity(a).unwrap();
+        world.create_entity().with(Health(3)).with(Pos).build();
+        world.entities().create();
+
+        Err("failed")
+    });
+    assert_eq!(result, Err("failed"));
+
+    assert!(world.is_alive(a));
+    assert!(world.is_alive(b));
+    assert!(!world.is_alive(c));
+    assert_eq!(world.entities().join().collect::<Vec<_>>(), vec![a, b]);
+    {
+        let health = world.read_storage::<Health>();
+        assert_eq!(health.get(a), Some(&Health(1)));
+        assert_eq!(health.get(b), Some(&Health(2)));
+        assert_eq!(health.join().count(), 2);
+        assert_eq!(world.read_storage::<Pos>().join().count(), 1);
+    }
+
+    // the indices freed by the rollback are reused, with new generations
+    world.maintain();
+    let d = world.create_entity().build();
+    let e = world.create_entity().build();
+    assert!(world.is_alive(d) && world.is_alive(e));
+    assert_eq!(world.entities().join().count(), 4);
+
+    let result: Result<u32, ()> = world.transaction(|world: &mut World| {
+        world.write_storage::<Health>().get_mut(a).unwrap().0 = 10;
+        world.delete_entity(b).unwrap();
+
+        Ok(10)
+    });
+    assert_eq!(result, Ok(10));
+    assert_eq!(world.read_storage::<Health>().get(a), Some(&Health(10)));
+    assert!(!world.is_alive(b));
+}
+
+#[test]
+fn transaction_rollback_keeps_components() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    struct Handle(Arc<AtomicUsize>, u32);
+
+    impl Component for Handle {
+        type Storage = VecStorage<Self>;
+
+        fn on_remove(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let released = Arc::new(AtomicUsize::new(0));
+    let mut world = World::new();
+    world.register_with::<Handle>().enable_rollback().build();
+    let a = world
+        .create_entity()
+        .with(Handle(released.clone(), 1))
+        .build();
+
+    let result: Result<(), ()> = world.transaction(|world: &mut World| {
+        world.write_storage::<Handle>().get_mut(a).unwrap().1 = 2;
+
+        Err(())
+    });
+    assert_eq!(result, Err(()));
+
+    assert_eq!(released.load(Ordering::SeqCst), 0);
+    assert_eq!(world.read_storage::<Handle>().get(a).unwrap().1, 1);
+
+    world.delete_entity(a).unwrap();
+    world.maintain();
+    assert_eq!(released.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn try_register() {
+    use error::{Error, RegisterError};
+
+    let mut world = World::new();
+    assert_eq!(world.try_register::<Pos>(), Ok(()));
+    world.create_entity().with(Pos).build();
+
+    let err = world.try_register::<Pos>().unwrap_err();
+    assert_eq!(err, RegisterError::AlreadyRegistered(TypeId::of::<Pos>()));
+    match Error::from(err) {
+        Error::Register(RegisterError::AlreadyRegistered(id)) => {
+            assert_eq!(id, TypeId::of::<Pos>())
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    // the storage is left alone
+    assert_eq!(world.read_storage::<Pos>().join().count(), 1);
+    world.register::<Pos>();
+    assert_eq!(world.read_storage::<Pos>().join().count(), 1);
+}
+
+#[test]
+fn registered_components() {
+    use std::mem;
+
+    use storage::DenseVecStorage;
+
+    struct Vel(f32, f32);
+
+    impl Component for Vel {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    assert!(world.registered_components().is_empty());
+
+    world.register_with::<Pos>().with_name("Pos").build();
+    world.register_with::<Vel>().with_name("Vel").build();
+    world.register::<Pos>();
+
+    let components = world.registered_components();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].type_id, TypeId::of::<Pos>());
+    assert_eq!(components[0].name, "Pos");
+    assert_eq!(components[1].type_id, TypeId::of::<Vel>());
+    assert_eq!(components[1].name, "Vel");
+    assert_eq!(components[1].storage, TypeId::of::<DenseVecStorage<Vel>>());
+    assert_eq!(components[1].size, mem::size_of::<Vel>());
+}
+
+#[test]
+fn maintain_cleans_many_storages() {
+    use storage::{DenseVecStorage, HashMapStorage};
+
+    struct Dense(u32);
+
+    impl Component for Dense {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    struct Sparse(u32);
+
+    impl Component for Sparse {
+        type Storage = HashMapStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.register::<Dense>();
+    world.register::<Sparse>();
+    #[cfg(feature = "parallel")]
+    {
+        let pool = ::rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        world.set_thread_pool(::std::sync::Arc::new(pool));
+    }
+
+    let entities: Vec<_> = (0..100)
+        .map(|i| {
+            world
+                .create_entity()
+                .with(Pos)
+                .with(Dense(i))
+                .with(Sparse(i))
+                .build()
+        })
+        .collect();
+    let (deleted, kept): (Vec<_>, Vec<_>) = entities.iter().cloned().partition(|e| e.id() % 3 == 0);
+    world.delete_entities(&deleted).unwrap();
+    world.maintain();
+
+    let (pos, dense, sparse) = (
+        world.read_storage::<Pos>(),
+        world.read_storage::<Dense>(),
+        world.read_storage::<Sparse>(),
+    );
+    assert_eq!((&pos, &dense, &sparse).join().count(), kept.len());
+    for &e in &kept {
+        assert_eq!(dense.get(e).unwrap().0, e.id());
+        assert_eq!(sparse.get(e).unwrap().0, e.id());
+    }
+    assert_eq!(pos.join().count(), kept.len());
+    assert_eq!(sparse.join().count(), kept.len());
+}
+
+#[test]
+fn clone_entity() {
+    use storage::{DenseVecStorage, FlaggedStorage};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(String);
+
+    impl Component for Name {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world.register_with::<Name>().enable_cloning().build();
+    world.register_with::<Health>().enable_cloning().build();
+    world.register::<Pos>();
+    let mut inserted = world.write_storage::<Health>().track_inserted();
+
+    let src = world
+        .create_entity()
+        .with(Name("orc".to_owned()))
+        .with(Health(30))
+        .with(Pos)
+        .build();
+    let other = world.create_entity().with(Name("elf".to_owned())).build();
+    // skip the insertion of the original
+    world.read_storage::<Health>().inserted().read(&mut inserted).count();
+
+    let dst = world.clone_entity(src);
+    assert_ne!(dst, src);
+    assert_eq!(
+        world.read_storage::<Name>().get(dst),
+        Some(&Name("orc".to_owned()))
+    );
+    assert_eq!(world.read_storage::<Health>().get(dst), Some(&Health(30)));
+    // not enabled for cloning
+    assert!(!world.read_storage::<Pos>().contains(dst));
+
+    let copies = world
+        .read_storage::<Health>()
+        .inserted()
+        .read(&mut inserted)
+        .count();
+    assert_eq!(copies, 1);
+
+    // only the components `other` has
+    let copy = world.clone_entity(other);
+    assert_eq!(
+        world.read_storage::<Name>().get(copy),
+        Some(&Name("elf".to_owned()))
+    );
+    assert!(!world.read_storage::<Health>().contains(copy));
+}
+
+#[test]
+fn suppress_tracking() {
+    use storage::{DenseVecStorage, FlaggedStorage};
+
+    struct Tile(u8);
+
+    impl Component for Tile {
+        type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world.register::<Tile>();
+    let mut readers = world.write_storage::<Tile>().track_all();
+    let existing = world.create_entity().with(Tile(0)).build();
+    world
+        .read_storage::<Tile>()
+        .inserted()
+        .read(&mut readers.insert)
+        .count();
+
+    let created: Vec<_> = {
+        let _guard = world.suppress_tracking::<Tile>();
+        let created = (0..10)
+            .map(|_| world.create_entity_unchecked().with(Tile(1)).build())
+            .collect();
+        let mut tiles = world.write_storage::<Tile>();
+        tiles.get_mut(existing).unwrap().0 = 2;
+        tiles.remove(existing);
+
+        created
+    };
+
+    {
+        let tiles = world.read_storage::<Tile>();
+        assert_eq!(tiles.join().count(), 10);
+        assert_eq!(tiles.inserted().read(&mut readers.insert).count(), 0);
+        assert_eq!(tiles.modified().read(&mut readers.modify).count(), 0);
+        assert_eq!(tiles.removed().read(&mut readers.remove).count(), 0);
+    }
+
+    // tracked again after the guard is dropped
+    world.write_storage::<Tile>().get_mut(created[0]).unwrap().0 = 3;
+    let tiles = world.read_storage::<Tile>();
+    assert_eq!(tiles.modified().read(&mut readers.modify).count(), 1);
+}
+
+#[test]
+fn suppress_tracking_new_reader() {
+    use storage::{DenseVecStorage, FlaggedStorage};
+
+    struct Tile;
+
+    impl Component for Tile {
+        type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+    }
+
+    let mut world = World::new();
+    world.register::<Tile>();
+
+    let mut inserted = {
+        let _outer = world.suppress_tracking::<Tile>();
+        let inserted = world.write_storage::<Tile>().track_inserted();
+        {
+            let _inner = world.suppress_tracking::<Tile>();
+            world.create_entity_unchecked().with(Tile).build();
+        }
+        world.create_entity_unchecked().with(Tile).build();
+
+        inserted
+    };
+    assert_eq!(
+        world
+            .read_storage::<Tile>()
+            .inserted()
+            .read(&mut inserted)
+            .count(),
+        0
+    );
+
+    // the reader registered while suppressed receives later events
+    world.create_entity().with(Tile).build();
+    assert_eq!(
+        world
+            .read_storage::<Tile>()
+            .inserted()
+            .read(&mut inserted)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn unregister() {
+    use std::any::TypeId;
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.register::<Vel>();
+    let a = world.create_entity().with(Pos).with(Vel).build();
+    world.create_entity().with(Pos).build();
+
+    assert_eq!(world.unregister::<Pos>(), Ok(()));
+    assert_eq!(world.read_storage::<Pos>().join().count(), 0);
+    assert!(
+        world
+            .registered_components()
+            .iter()
+            .all(|info| info.type_id != TypeId::of::<Pos>())
+    );
+    assert_eq!(
+        world.unregister::<Pos>(),
+        Err(NotRegistered {
+            component: TypeId::of::<Pos>(),
+        })
+    );
+
+    // other storages are still maintained
+    world.delete_entity(a).unwrap();
+    world.maintain();
+    assert_eq!(world.read_storage::<Vel>().join().count(), 0);
+
+    // registering again starts fresh
+    assert_eq!(world.try_register::<Pos>(), Ok(()));
+    assert_eq!(world.read_storage::<Pos>().join().count(), 0);
+    let b = world.create_entity().with(Pos).build();
+    assert!(world.read_storage::<Pos>().contains(b));
+}
+
+#[test]
+fn unregister_hooks() {
+    use storage::DenseVecStorage;
+
+    #[derive(Clone)]
+    struct Name;
+
+    impl Component for Name {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register_with::<Name>().enable_cloning().build();
+    world.unregister::<Name>().unwrap();
+
+    // registered again without cloning
+    world.register::<Name>();
+    let src = world.create_entity().with(Name).build();
+    let dst = world.clone_entity(src);
+    assert!(!world.read_storage::<Name>().contains(dst));
+}
+
+#[test]
+fn recycle_policy() {
+    fn reused(policy: RecyclePolicy) -> Vec<Index> {
+        let mut world = World::with_recycle_policy(policy);
+        let entities: Vec<_> = (0..6).map(|_| world.create_entity().build()).collect();
+
+        world.delete_entities(&entities[..2]).unwrap();
+        world.entities().delete(entities[2]).unwrap();
+        world.entities().delete(entities[3]).unwrap();
+        world.maintain();
+
+        (0..4).map(|_| world.create_entity().build().id()).collect()
+    }
+
+    assert_eq!(reused(RecyclePolicy::Lifo), vec![3, 2, 1, 0]);
+    assert_eq!(reused(RecyclePolicy::Fifo), vec![0, 1, 2, 3]);
+    assert_eq!(RecyclePolicy::default(), RecyclePolicy::Lifo);
+}
+
+#[test]
+fn maintain_reports_changes() {
+    let mut world = World::new();
+    assert!(!world.maintain());
+
+    let e = world.create_entity().build();
+    assert!(world.maintain());
+    assert!(!world.maintain());
+
+    world.entities().create();
+    assert!(world.maintain());
+    assert!(!world.maintain());
+
+    world.delete_entity(e).unwrap();
+    assert!(world.maintain());
+    assert!(!world.maintain());
+
+    let e = world.create_entity().build();
+    world.maintain();
+    world.entities().delete(e).unwrap();
+    assert!(world.maintain());
+    assert!(!world.maintain());
+}
@@ -547,3 +547,995 @@ fn maintain_entity_deletion() {
     world.maintain();
     check.run_now(&world.res);
 }
+
+#[test]
+fn thread_local_after_parallel() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::thread::{self, ThreadId};
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    struct ParSys;
+
+    impl<'a> System<'a> for ParSys {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            log.0.push("par");
+        }
+    }
+
+    // `Rc` makes this system `!Send`.
+    struct LocalSys(Rc<Cell<Option<ThreadId>>>);
+
+    impl<'a> System<'a> for LocalSys {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            self.0.set(Some(thread::current().id()));
+            log.0.push("local");
+        }
+    }
+
+    let thread_id = Rc::new(Cell::new(None));
+    let mut world = create_world();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with_thread_local(LocalSys(thread_id.clone()))
+        .with(ParSys, "par_a", &[])
+        .with(ParSys, "par_b", &[])
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(world.read_resource::<Log>().0, vec!["par", "par", "local"]);
+    assert_eq!(thread_id.get(), Some(thread::current().id()));
+}
+
+#[test]
+fn create_during_join() {
+    struct Parent(Entity);
+
+    impl Component for Parent {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct Spawner;
+
+    impl<'a> System<'a> for Spawner {
+        type SystemData = (Entities<'a>, ReadStorage<'a, CompInt>, Read<'a, LazyUpdate>);
+
+        fn run(&mut self, (entities, ints, lazy): Self::SystemData) {
+            for (parent, _) in (&*entities, &ints).join() {
+                let child = entities.create();
+                assert!(entities.is_alive(child));
+                lazy.insert(child, Parent(parent));
+            }
+        }
+    }
+
+    let mut world = create_world();
+    world.register::<Parent>();
+    let parents: Vec<_> = (0..10)
+        .map(|i| world.create_entity().with(CompInt(i)).build())
+        .collect();
+
+    Spawner.run_now(&world.res);
+    world.maintain();
+
+    let entities = world.entities();
+    let children = world.read_storage::<Parent>();
+    let mut spawned: Vec<_> = (&*entities, &children)
+        .join()
+        .map(|(child, parent)| {
+            assert!(world.is_alive(child));
+            parent.0
+        })
+        .collect();
+    spawned.sort();
+
+    assert_eq!(spawned, parents);
+}
+
+#[test]
+fn join_rev() {
+    let mut world = create_world();
+    for i in 0..100 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 3 == 0 {
+            builder = builder.with(CompBool(true));
+        }
+        builder.build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let forward: Vec<_> = (&ints, &bools).join().map(|(i, _)| i.0).collect();
+    let mut reverse: Vec<_> = (&ints, &bools).join().rev().map(|(i, _)| i.0).collect();
+    assert_eq!(reverse.len(), 34);
+    assert!(reverse.windows(2).all(|w| w[0] > w[1]));
+
+    reverse.reverse();
+    assert_eq!(forward, reverse);
+
+    // both ends can be consumed alternately
+    let mut iter = (&ints).join();
+    assert_eq!(iter.next(), Some(&CompInt(0)));
+    assert_eq!(iter.next_back(), Some(&CompInt(99)));
+    assert_eq!(iter.next(), Some(&CompInt(1)));
+    assert_eq!(iter.count(), 97);
+}
+
+#[test]
+fn chunked_join() {
+    use specs::join::ChunkedJoin;
+
+    #[derive(Debug, PartialEq)]
+    struct Pos(u32);
+
+    impl Component for Pos {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    let mut world = create_world();
+    world.register::<Pos>();
+
+    for i in 0..23 {
+        let builder = world.create_entity().with(Pos(i));
+        if i % 3 != 0 {
+            builder.with(CompInt(i as i8)).build();
+        } else {
+            builder.build();
+        }
+    }
+
+    // `CompInt` uses a `VecStorage`, which is dense too.
+    let pos = world.read_storage::<Pos>();
+    let ints = world.read_storage::<CompInt>();
+
+    let mut visited = Vec::new();
+    let mut sizes = Vec::new();
+    let mut chunks = (&pos, &ints).chunked_join(4);
+    while let Some(chunk) = chunks.next_chunk() {
+        sizes.push(chunk.len());
+        for &(p, i) in chunk {
+            assert_eq!(p.0 as i8, i.0);
+            visited.push(p.0);
+        }
+    }
+
+    let expected: Vec<_> = (0..23).filter(|i| i % 3 != 0).collect();
+    assert_eq!(visited, expected);
+    assert_eq!(sizes, vec![4, 4, 4, 3]);
+}
+
+#[test]
+fn join_paging() {
+    let mut world = create_world();
+    for i in 0..100 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 3 != 0 {
+            builder = builder.with(CompBool(true));
+        }
+        builder.build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let all: Vec<_> = (&ints, &bools).join().map(|(i, _)| i.0).collect();
+    let mut paged = Vec::new();
+    let mut page = 0;
+    loop {
+        let items: Vec<_> = (&ints, &bools)
+            .skip(page * 7)
+            .take(7)
+            .join()
+            .map(|(i, _)| i.0)
+            .collect();
+        if items.is_empty() {
+            break;
+        }
+        assert!(items.len() <= 7);
+        paged.extend(items);
+        page += 1;
+    }
+    assert_eq!(paged, all);
+
+    // Pages spanning multiple words and layers of the bitset.
+    let mut set = BitSet::new();
+    let indices: Vec<u32> = (0..2000).map(|i| i * 131).collect();
+    for &i in &indices {
+        set.add(i);
+    }
+
+    let mut paged = Vec::new();
+    for page in 0..40 {
+        paged.extend((&set).skip(page * 53).take(53).join());
+    }
+    assert_eq!(paged, indices);
+    assert_eq!((&set).skip(2000).join().count(), 0);
+    assert_eq!((&set).take(0).join().count(), 0);
+}
+
+#[test]
+fn join_indexed() {
+    let mut world = create_world();
+    for i in 0..50 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 3 == 0 {
+            builder = builder.with(CompBool(true));
+        }
+        builder.build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let indexed: Vec<_> = (&ints, &bools).indexed().join().collect();
+    let mask: Vec<_> = (ints.mask(), bools.mask())
+        .join()
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(indexed.iter().map(|&(i, _)| i).collect::<Vec<_>>(), mask);
+    assert!(indexed.iter().all(|&(i, (int, _))| int.0 as u32 == i));
+
+    // It composes with other adapters.
+    let paged: Vec<_> = (&ints, &bools)
+        .indexed()
+        .skip(2)
+        .take(3)
+        .join()
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(paged, vec![6, 9, 12]);
+}
+
+#[test]
+fn join_exclude() {
+    let mut world = create_world();
+    for i in 0..10 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 2 == 0 {
+            builder = builder.with(CompBool(true));
+        }
+        builder.build();
+    }
+
+    let entities = world.entities();
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+    let mut processed = BitSet::new();
+
+    let visit = |processed: &BitSet| -> Vec<i8> {
+        (&ints, &bools)
+            .exclude(processed)
+            .join()
+            .map(|(i, _)| i.0)
+            .collect()
+    };
+
+    assert_eq!(visit(&processed), vec![0, 2, 4, 6, 8]);
+
+    for (e, _) in (&*entities, &bools).join().take(2) {
+        processed.add(e.id());
+    }
+    assert_eq!(visit(&processed), vec![4, 6, 8]);
+
+    // It composes with other join terms.
+    let rest: Vec<_> = ((&ints).exclude(&processed), &bools)
+        .join()
+        .map(|(i, _)| i.0)
+        .collect();
+    assert_eq!(rest, vec![4, 6, 8]);
+}
+
+#[test]
+fn join_map() {
+    struct View<'a> {
+        int: &'a CompInt,
+        flag: Option<&'a CompBool>,
+    }
+
+    impl<'a> View<'a> {
+        fn value(&self) -> i8 {
+            match self.flag {
+                Some(&CompBool(true)) => -self.int.0,
+                _ => self.int.0,
+            }
+        }
+    }
+
+    let mut world = create_world();
+    for i in 0..6 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 2 == 0 {
+            builder = builder.with(CompBool(i % 4 == 0));
+        }
+        builder.build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+    let views = || (&ints, (&bools).maybe()).map_join(|(int, flag)| View { int, flag });
+
+    let values: Vec<_> = views().join().map(|view| view.value()).collect();
+    assert_eq!(values, vec![0, 1, 2, 3, -4, 5]);
+
+    // It composes with other joins and adapters.
+    let values: Vec<_> = (views(), &bools)
+        .join()
+        .map(|(view, _)| view.value())
+        .collect();
+    assert_eq!(values, vec![0, 2, -4]);
+
+    // `MaybeJoin` can't be joined in parallel, so require the flag here.
+    let mut values: Vec<_> = (&ints, &bools)
+        .map_join(|(int, flag)| View {
+            int,
+            flag: Some(flag),
+        })
+        .par_join()
+        .map(|view| view.value())
+        .collect();
+    values.sort();
+    assert_eq!(values, vec![-4, 0, 2]);
+}
+
+#[test]
+fn join_collect_bitset() {
+    let mut world = create_world();
+    for i in 0..10 {
+        let mut builder = world.create_entity().with(CompInt(i));
+        if i % 3 == 0 {
+            builder = builder.with(CompBool(true));
+        }
+        builder.build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let without_bool = (&ints).exclude(bools.mask()).join().collect_bitset();
+    let collected: Vec<_> = (&ints, &without_bool)
+        .join()
+        .map(|(i, _)| i.0)
+        .collect();
+    assert_eq!(collected, vec![1, 2, 4, 5, 7, 8]);
+
+    // Indices already visited aren't part of the set.
+    let mut iter = (&ints, &bools).join();
+    iter.next();
+    let rest = iter.collect_bitset();
+    let collected: Vec<_> = (&ints, &rest).join().map(|(i, _)| i.0).collect();
+    assert_eq!(collected, vec![3, 6, 9]);
+}
+
+#[test]
+fn join_unique_by() {
+    let mut world = create_world();
+    for i in 0..10 {
+        world.create_entity().with(CompInt(i)).build();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let firsts: Vec<_> = (&ints)
+        .join()
+        .unique_by(|i| i.0 % 3)
+        .map(|i| i.0)
+        .collect();
+    assert_eq!(firsts, vec![0, 1, 2]);
+
+    // Composes with join adapters.
+    let firsts: Vec<_> = (&ints)
+        .skip(4)
+        .join()
+        .unique_by(|i| i.0 / 4)
+        .map(|i| i.0)
+        .collect();
+    assert_eq!(firsts, vec![4, 8]);
+}
+
+#[test]
+fn join_step_by() {
+    let mut world = create_world();
+    let entities: Vec<_> = (0..10)
+        .map(|i| world.create_entity().with(CompInt(i)).build())
+        .collect();
+    for &e in &entities[2..5] {
+        world.delete_entity(e).unwrap();
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let all: Vec<_> = (&ints).join().map(|i| i.0).collect();
+    let halves: Vec<_> = (&ints).step_by(2).join().map(|i| i.0).collect();
+    assert_eq!(all, vec![0, 1, 5, 6, 7, 8, 9]);
+    // by position in the join, not by index
+    assert_eq!(halves, vec![0, 5, 7, 9]);
+    assert_eq!((&ints).step_by(1).join().count(), all.len());
+
+    // stable when joined again
+    let again: Vec<_> = (&ints).step_by(2).join().map(|i| i.0).collect();
+    assert_eq!(again, halves);
+}
+
+#[test]
+fn join_windows() {
+    let mut world = create_world();
+    let entities: Vec<_> = (0..6)
+        .map(|i| world.create_entity().with(CompInt(i)).build())
+        .collect();
+    world.delete_entity(entities[2]).unwrap();
+
+    let ints = world.read_storage::<CompInt>();
+    let windows: Vec<Vec<i8>> = (&ints)
+        .join()
+        .windows(3)
+        .map(|w| w.iter().map(|i| i.0).collect())
+        .collect();
+    // consecutive in iteration order, skipping the deleted entity
+    assert_eq!(
+        windows,
+        vec![vec![0, 1, 3], vec![1, 3, 4], vec![3, 4, 5]]
+    );
+
+    assert_eq!((&ints).join().windows(1).count(), 5);
+    assert_eq!((&ints).join().windows(5).count(), 1);
+    assert_eq!((&ints).join().windows(6).count(), 0);
+}
+
+#[test]
+fn system_conflicts() {
+    use specs::dispatch::conflicts;
+
+    #[derive(Default)]
+    struct Time(f32);
+
+    type Movement<'a> = (Read<'a, Time>, WriteStorage<'a, CompInt>, ReadStorage<'a, CompBool>);
+    type Render<'a> = (ReadStorage<'a, CompInt>, ReadStorage<'a, CompBool>);
+    type Clock<'a> = Write<'a, Time>;
+
+    assert_eq!(conflicts::<Movement, Render>().len(), 1);
+    assert_eq!(conflicts::<Render, Movement>().len(), 1);
+    assert_eq!(conflicts::<Movement, Clock>().len(), 1);
+    assert!(conflicts::<Render, Clock>().is_empty());
+    assert!(conflicts::<Render, Render>().is_empty());
+    // Two instances of the same system can't run in parallel either.
+    assert_eq!(conflicts::<Movement, Movement>().len(), 1);
+}
+
+#[test]
+fn conditional_system() {
+    use specs::dispatch::Conditional;
+
+    struct Paused(bool);
+
+    #[derive(Default)]
+    struct Ticks(u32);
+
+    struct TickSys;
+
+    impl<'a> System<'a> for TickSys {
+        type SystemData = Write<'a, Ticks>;
+
+        fn run(&mut self, mut ticks: Self::SystemData) {
+            ticks.0 += 1;
+        }
+    }
+
+    let mut world = create_world();
+    world.add_resource(Paused(false));
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(
+            Conditional::new(TickSys, |paused: &Paused| !paused.0),
+            "tick",
+            &[],
+        )
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Ticks>().0, 1);
+
+    world.write_resource::<Paused>().0 = true;
+    dispatcher.dispatch(&world.res);
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Ticks>().0, 1);
+
+    world.write_resource::<Paused>().0 = false;
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Ticks>().0, 2);
+}
+
+#[test]
+fn best_effort_system() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use specs::dispatch::{BestEffort, FrameBudget};
+
+    #[derive(Default)]
+    struct Ticks(u32);
+
+    struct Work(Arc<AtomicUsize>, Arc<AtomicUsize>);
+
+    impl<'a> System<'a> for Work {
+        type SystemData = ();
+
+        fn run(&mut self, _: ()) {
+            // advance the fake clock by the cost of this frame
+            self.1.fetch_add(self.0.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+    }
+
+    struct Background;
+
+    impl<'a> System<'a> for Background {
+        type SystemData = Write<'a, Ticks>;
+
+        fn run(&mut self, mut ticks: Self::SystemData) {
+            ticks.0 += 1;
+        }
+    }
+
+    let cost = Arc::new(AtomicUsize::new(0));
+    let now = Arc::new(AtomicUsize::new(0));
+
+    let mut world = create_world();
+    let clock = now.clone();
+    world.add_resource(FrameBudget::with_clock(Duration::from_millis(10), move || {
+        Duration::from_millis(clock.load(Ordering::SeqCst) as u64)
+    }));
+
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(Work(cost.clone(), now.clone()), "work", &[])
+        .with(BestEffort::new(Background), "background", &["work"])
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    let mut frame = |world: &mut World, frame_cost| {
+        cost.store(frame_cost, Ordering::SeqCst);
+        world.write_resource::<FrameBudget>().start_frame();
+        dispatcher.dispatch(&world.res);
+        world.read_resource::<Ticks>().0
+    };
+
+    assert_eq!(frame(&mut world, 5), 1);
+    // The budget is exhausted, so the background system is skipped ...
+    assert_eq!(frame(&mut world, 15), 1);
+    assert_eq!(frame(&mut world, 11), 1);
+    // ... and runs again once there's time left.
+    assert_eq!(frame(&mut world, 10), 2);
+    assert_eq!(frame(&mut world, 0), 3);
+}
+
+#[test]
+fn fixed_timestep() {
+    use std::time::Duration;
+
+    use specs::dispatch::{FixedStep, FixedTimestep};
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    struct Logger(&'static str);
+
+    impl<'a> System<'a> for Logger {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            log.0.push(self.0);
+        }
+    }
+
+    let mut world = create_world();
+    world.add_resource(FixedTimestep::new(Duration::from_millis(10)).with_max_steps(3));
+
+    let physics = DispatcherBuilder::new()
+        .with(Logger("integrate"), "integrate", &[])
+        .with(Logger("collide"), "collide", &["integrate"])
+        .build();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(Logger("input"), "input", &[])
+        .with_thread_local(FixedStep::new(physics))
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    // a fake clock, in milliseconds since the start
+    let mut last = 0;
+    let mut frame = |world: &mut World, now: u64| {
+        world
+            .write_resource::<FixedTimestep>()
+            .advance(Duration::from_millis(now - last));
+        last = now;
+        world.write_resource::<Log>().0.clear();
+        dispatcher.dispatch(&world.res);
+        let steps = world.read_resource::<FixedTimestep>().steps();
+        let log = world.read_resource::<Log>().0.clone();
+        assert_eq!(log.len(), 1 + 2 * steps as usize);
+
+        log
+    };
+
+    assert_eq!(
+        frame(&mut world, 25),
+        vec!["input", "integrate", "collide", "integrate", "collide"]
+    );
+    assert!((world.read_resource::<FixedTimestep>().alpha() - 0.5).abs() < 1e-9);
+    // 5ms left over from the last frame
+    assert_eq!(frame(&mut world, 32), vec!["input", "integrate", "collide"]);
+    assert_eq!(frame(&mut world, 37), vec!["input"]);
+    assert_eq!(frame(&mut world, 40), vec!["input", "integrate", "collide"]);
+    // at most 3 steps, the rest of the time is dropped
+    assert_eq!(frame(&mut world, 200).len(), 7);
+    assert_eq!(frame(&mut world, 205).len(), 1);
+}
+
+#[test]
+fn dispatch_group() {
+    use specs::dispatch::GroupDispatcherBuilder;
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    struct Logger(&'static str);
+
+    impl<'a> System<'a> for Logger {
+        type SystemData = Write<'a, Log>;
+
+        fn run(&mut self, mut log: Self::SystemData) {
+            log.0.push(self.0);
+        }
+    }
+
+    let mut world = create_world();
+    let mut dispatcher = GroupDispatcherBuilder::new()
+        .with_groups(Logger("plan"), "plan", &[], &["ai"])
+        .with(Logger("physics"), "physics", &["plan"])
+        .with_groups(Logger("steer"), "steer", &["physics"], &["ai", "movement"])
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    dispatcher.dispatch_group(&world.res, "ai");
+    assert_eq!(world.read_resource::<Log>().0, vec!["plan", "steer"]);
+
+    world.write_resource::<Log>().0.clear();
+    dispatcher.dispatch_group(&world.res, "movement");
+    assert_eq!(world.read_resource::<Log>().0, vec!["steer"]);
+
+    world.write_resource::<Log>().0.clear();
+    dispatcher.dispatch_group(&world.res, "unknown");
+    assert!(world.read_resource::<Log>().0.is_empty());
+
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Log>().0, vec!["plan", "physics", "steer"]);
+}
+
+#[test]
+fn hash_storage_deterministic() {
+    fn run() -> Vec<(u32, bool)> {
+        let mut world = create_world();
+        let entities: Vec<_> = (0..200).map(|_| world.create_entity().build()).collect();
+
+        let mut bools = world.write_storage::<CompBool>();
+        for (i, &e) in entities.iter().enumerate().rev() {
+            if i % 7 != 3 {
+                bools.insert(e, CompBool(i % 2 == 0)).unwrap();
+            }
+        }
+        for &e in entities.iter().filter(|e| e.id() % 5 == 0) {
+            bools.remove(e);
+        }
+
+        let entities = world.entities();
+        (&*entities, &bools)
+            .join()
+            .map(|(e, b)| (e.id(), b.0))
+            .collect()
+    }
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn join_find_entity() {
+    let mut world = create_world();
+    let entities: Vec<_> = (0..10)
+        .map(|i| world.create_entity().with(CompInt(i)).build())
+        .collect();
+
+    let ints = world.read_storage::<CompInt>();
+    let mut checked = 0;
+    let found = (&ints).join().find_entity(&world.entities(), |i| {
+        checked += 1;
+        i.0 == 3
+    });
+    assert_eq!(found, Some(entities[3]));
+    assert_eq!(checked, 4);
+
+    let mut iter = (&ints).join();
+    let entities_res = world.entities();
+    assert_eq!(iter.find_entity(&entities_res, |i| i.0 % 4 == 1), Some(entities[1]));
+    assert_eq!(iter.find_entity(&entities_res, |i| i.0 % 4 == 1), Some(entities[5]));
+    assert_eq!(iter.find_entity(&entities_res, |i| i.0 > 20), None);
+}
+
+#[test]
+fn thread_local_dependencies() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use specs::dispatch::ThreadLocalDispatcherBuilder;
+
+    struct Logger(&'static str, Rc<RefCell<Vec<&'static str>>>);
+
+    impl<'a> System<'a> for Logger {
+        type SystemData = ();
+
+        fn run(&mut self, _: ()) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut builder = ThreadLocalDispatcherBuilder::new();
+    let submit = Logger("submit", log.clone());
+    builder.add_thread_local_with_deps(submit, "submit", &["setup", "upload"]);
+    builder.add_thread_local_with_deps(Logger("upload", log.clone()), "upload", &["setup"]);
+    builder.add_thread_local_with_deps(Logger("ui", log.clone()), "ui", &[]);
+    builder.add_thread_local_with_deps(Logger("setup", log.clone()), "setup", &[]);
+    let mut dispatcher = builder.build().unwrap();
+
+    let mut world = create_world();
+    dispatcher.setup(&mut world.res);
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(*log.borrow(), vec!["ui", "setup", "upload", "submit"]);
+}
+
+#[test]
+fn thread_local_dependency_errors() {
+    use specs::dispatch::ThreadLocalDispatcherBuilder;
+    use specs::error::DependencyError;
+
+    struct Nop;
+
+    impl<'a> System<'a> for Nop {
+        type SystemData = ();
+
+        fn run(&mut self, _: ()) {}
+    }
+
+    let cyclic = ThreadLocalDispatcherBuilder::new()
+        .with_thread_local_with_deps(Nop, "c", &[])
+        .with_thread_local_with_deps(Nop, "a", &["b", "c"])
+        .with_thread_local_with_deps(Nop, "b", &["a"])
+        .build();
+    assert_eq!(
+        cyclic.err(),
+        Some(DependencyError::Cyclic(vec!["a".to_owned(), "b".to_owned()]))
+    );
+
+    let missing = ThreadLocalDispatcherBuilder::new()
+        .with_thread_local_with_deps(Nop, "a", &["b"])
+        .build();
+    assert_eq!(
+        missing.err(),
+        Some(DependencyError::Missing {
+            system: "a".to_owned(),
+            dependency: "b".to_owned(),
+        })
+    );
+
+    let duplicate = ThreadLocalDispatcherBuilder::new()
+        .with_thread_local_with_deps(Nop, "a", &[])
+        .with_thread_local_with_deps(Nop, "a", &[])
+        .build();
+    assert_eq!(duplicate.err(), Some(DependencyError::Duplicate("a".to_owned())));
+}
+
+#[test]
+fn zip_joins() {
+    use specs::join::zip_joins;
+
+    let mut world = create_world();
+    for i in 0..6 {
+        let builder = world.create_entity();
+        if i < 3 {
+            builder.with(CompInt(i)).build();
+        } else {
+            builder.with(CompBool(i % 2 == 0)).build();
+        }
+    }
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    // The pools don't share any entity, so a tuple join is empty.
+    assert_eq!((&ints, &bools).join().count(), 0);
+
+    let pairs: Vec<_> = zip_joins(&ints, &bools).map(|(i, b)| (i.0, b.0)).collect();
+    assert_eq!(pairs, vec![(0, false), (1, true), (2, false)]);
+
+    // Stops with the shorter join.
+    let pairs = zip_joins(&ints, (&bools).take(2)).count();
+    assert_eq!(pairs, 2);
+}
+
+#[test]
+fn collect_map() {
+    let mut world = create_world();
+    let entities: Vec<_> = (0..10)
+        .map(|i| {
+            let builder = world.create_entity().with(CompInt(i));
+            if i % 3 == 0 {
+                builder.with(CompBool(i % 2 == 0)).build()
+            } else {
+                builder.build()
+            }
+        })
+        .collect();
+
+    let ents = world.entities();
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let map = (&*ents, &ints).join().collect_map();
+    assert_eq!(map.len(), 10);
+    assert_eq!(map[&entities[4]].0, 4);
+
+    let map = (&*ents, (&ints, &bools)).join().collect_map();
+    assert_eq!(map.len(), 4);
+    let (int, b) = map[&entities[6]];
+    assert_eq!((int.0, b.0), (6, true));
+    assert!(!map.contains_key(&entities[5]));
+}
+
+#[test]
+fn union_join() {
+    use specs::join::union_join;
+
+    let mut world = create_world();
+    world.create_entity().with(CompInt(1)).build();
+    world.create_entity().with(CompBool(true)).build();
+    world
+        .create_entity()
+        .with(CompInt(3))
+        .with(CompBool(false))
+        .build();
+    world.create_entity().build();
+
+    let ints = world.read_storage::<CompInt>();
+    let bools = world.read_storage::<CompBool>();
+
+    let items: Vec<_> = union_join(&ints, &bools)
+        .join()
+        .map(|(i, b)| (i.map(|i| i.0), b.map(|b| b.0)))
+        .collect();
+    assert_eq!(
+        items,
+        vec![(Some(1), None), (None, Some(true)), (Some(3), Some(false))]
+    );
+}
+
+#[test]
+fn checked_dispatcher_missing_write() {
+    use specs::dispatch::CheckedDispatcherBuilder;
+    use specs::shred::ResourceId;
+
+    struct Score(u32);
+
+    struct Unregistered;
+
+    impl Component for Unregistered {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct AddPoint;
+
+    impl<'a> System<'a> for AddPoint {
+        type SystemData = (ReadStorage<'a, CompInt>, WriteExpect<'a, Score>);
+
+        fn run(&mut self, (_, mut score): Self::SystemData) {
+            score.0 += 1;
+        }
+    }
+
+    struct ReadUnregistered;
+
+    impl<'a> System<'a> for ReadUnregistered {
+        type SystemData = ReadStorage<'a, Unregistered>;
+
+        fn run(&mut self, _: Self::SystemData) {}
+    }
+
+    let mut world = create_world();
+    let err = CheckedDispatcherBuilder::new()
+        .with(AddPoint, "add_point", &[])
+        .with(ReadUnregistered, "read_unregistered", &[])
+        .build(&world.res)
+        .err()
+        .unwrap();
+
+    assert_eq!(err.missing.len(), 2);
+    assert_eq!(err.missing[0].system, "add_point");
+    assert_eq!(err.missing[0].resource, ResourceId::new::<Score>());
+    assert!(err.missing[0].write);
+    assert_eq!(err.missing[1].system, "read_unregistered");
+    assert!(!err.missing[1].write);
+    assert!(err.to_string().contains("System `add_point` writes"));
+
+    world.add_resource(Score(0));
+    world.register::<Unregistered>();
+    let mut dispatcher = CheckedDispatcherBuilder::new()
+        .with(AddPoint, "add_point", &[])
+        .with(ReadUnregistered, "read_unregistered", &[])
+        .build(&world.res)
+        .unwrap();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Score>().0, 1);
+}
+
+#[test]
+fn hot_reload_system() {
+    use specs::dispatch::HotReload;
+
+    #[derive(Default)]
+    struct Score(u32);
+
+    struct Add(u32);
+
+    impl<'a> System<'a> for Add {
+        type SystemData = Write<'a, Score>;
+
+        fn run(&mut self, mut score: Self::SystemData) {
+            score.0 += self.0;
+        }
+    }
+
+    struct Double;
+
+    impl<'a> System<'a> for Double {
+        type SystemData = (Write<'a, Score>, ReadStorage<'a, CompInt>);
+
+        fn run(&mut self, (mut score, _): Self::SystemData) {
+            score.0 *= 2;
+        }
+    }
+
+    struct Count;
+
+    impl<'a> System<'a> for Count {
+        type SystemData = (Read<'a, Score>, WriteStorage<'a, CompInt>);
+
+        fn run(&mut self, _: Self::SystemData) {}
+    }
+
+    let mut world = create_world();
+    let slot = HotReload::new(Double);
+    let handle = slot.handle();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(Add(1), "add", &[])
+        .with(slot, "slot", &["add"])
+        .build();
+    dispatcher.setup(&mut world.res);
+
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Score>().0, 2);
+
+    // a subset of the declared access
+    handle.replace(Add(10)).unwrap();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Score>().0, 13);
+
+    // `CompInt` is only read by the slot
+    let err = handle.replace(Count).unwrap_err();
+    assert!(err.reads.is_empty());
+    assert_eq!(err.writes.len(), 1);
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<Score>().0, 24);
+}